@@ -1,296 +1,5745 @@
-use criterion::{criterion_group, criterion_main, Criterion};
-use std::sync::{mpsc, Arc, Mutex, RwLock};
+mod support;
+
+use basic_rust_concurrency_benchmarks::locks::{McsLock, SeqLock, TicketLock};
+use basic_rust_concurrency_benchmarks::{
+    available_threads, run_contention_scenario, scaled_thread_counts, thread_cpu_time, HostInfo,
+    LockStall, Scenario, StallPlacement, StallPolicy, StartMode, StripedMutex, Workload,
+};
+use basic_rust_concurrency_benchmarks::{counter, registry, scenario};
+use criterion::measurement::WallTime;
+use criterion::{
+    black_box, criterion_group, Bencher, BenchmarkGroup, BenchmarkId, Criterion, Throughput,
+};
+use crossbeam_deque::{Steal, Worker};
+use crossbeam_queue::{ArrayQueue, SegQueue};
+use crossbeam_utils::sync::WaitGroup;
+use crossbeam_utils::{Backoff, CachePadded};
+use dashmap::DashMap;
+use rand::distr::Distribution;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rand_distr::Zipf;
+use rayon::prelude::*;
+use std::collections::{HashMap, VecDeque};
+use std::env;
+use std::future::Future;
+use std::panic;
+use std::sync::atomic::{AtomicBool, AtomicU64, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc, Barrier, Condvar, Mutex, OnceLock, PoisonError, RwLock};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use support::sharded::ShardedCounter;
+use support::spin::SpinLock;
+use support::treiber::TreiberStack;
+
+/// Counts the allocations `channel_allocations` measures; everything else
+/// runs through it with the counting gate closed.
+#[cfg(feature = "count-allocations")]
+#[global_allocator]
+static ALLOCATOR: basic_rust_concurrency_benchmarks::allocations::CountingAllocator =
+    basic_rust_concurrency_benchmarks::allocations::CountingAllocator;
+
+/// Environment variable that overrides the thread counts swept by the benchmarks,
+/// as a single count such as `BENCH_THREADS=24` or a comma-separated list.
+const BENCH_THREADS_ENV: &str = "BENCH_THREADS";
+
+/// Returns the thread counts every thread-scaled benchmark is run with.
+/// Defaults to `scaled_thread_counts`, half, all, and twice the machine's
+/// available parallelism, unless overridden through `BENCH_THREADS`, which is
+/// read once at startup. Benchmark ids always carry the resolved count, so
+/// baselines recorded on machines with different core counts never mix.
+fn thread_counts() -> &'static [usize] {
+    static THREAD_COUNTS: OnceLock<Vec<usize>> = OnceLock::new();
+    THREAD_COUNTS.get_or_init(|| {
+        if let Ok(value) = env::var(BENCH_THREADS_ENV) {
+            return value
+                .split(',')
+                .map(|count| match count.trim().parse() {
+                    Ok(count) if count > 0 => count,
+                    _ => panic!(
+                        "{} must list positive thread counts, got {:?}",
+                        BENCH_THREADS_ENV, value
+                    ),
+                })
+                .collect();
+        }
+        scaled_thread_counts()
+    })
+}
+
+/// Registers `name` as a benchmark group with one benchmark per thread count,
+/// so criterion plots how the workload scales with the number of threads.
+/// Each thread is counted as one operation, so reports show operations per second.
+fn bench_over_threads<F>(c: &mut Criterion, name: &str, routine: F)
+where
+    F: FnMut(&mut Bencher, usize),
+{
+    bench_over_threads_with_elements(c, name, |threads| threads as u64, routine);
+}
 
-/// Introduces a delay of 25ms for every even iteration.
-/// This function simulates potential stalls in the execution of threads,
-/// adding a delay to every even-numbered iteration.
-fn even_iteration_delay(i: usize) {
-    if i % 2 == 0 {
-        thread::sleep(Duration::from_millis(25));
+/// Like `bench_over_threads`, for workloads where a thread does more than one
+/// operation: `elements` maps a thread count to the total operations per iteration,
+/// which criterion reports as throughput.
+fn bench_over_threads_with_elements<E, F>(
+    c: &mut Criterion,
+    name: &str,
+    elements: E,
+    mut routine: F,
+) where
+    E: Fn(usize) -> u64,
+    F: FnMut(&mut Bencher, usize),
+{
+    let mut group = c.benchmark_group(name);
+    for &threads in thread_counts() {
+        group.throughput(Throughput::Elements(elements(threads)));
+        group.bench_with_input(
+            BenchmarkId::new("threads", threads),
+            &threads,
+            |b, &threads| routine(b, threads),
+        );
     }
+    group.finish();
 }
 
-/// Benchmark for read-heavy workloads using Arc<Mutex>.
-/// This function measures the performance of multiple threads
-/// concurrently reading from an Arc-wrapped Mutex-protected integer,
-/// with a delay added for every even iteration.
-fn arc_mutex_read_heavy(c: &mut Criterion) {
-    let data = Arc::new(Mutex::new(0));
-    c.bench_function("arc_mutex_read_heavy", |b| {
-        b.iter(|| {
-            let mut handles = vec![];
-            for i in 0..10 {
-                let data_clone = Arc::clone(&data);
-                let handle = thread::spawn(move || {
-                    let _unused = data_clone.lock().unwrap();
-                    even_iteration_delay(i);
+/// Operations each thread performs per iteration in the lock and atomic benchmarks.
+/// With a single operation the measurement is dominated by thread spawn and join;
+/// at 10,000 the lock or atomic itself dominates, so primitives can be told apart.
+const OPS_PER_THREAD: [u64; 3] = [1, 100, 10_000];
+
+/// Names the benchmark function `base` for `ops` operations per thread.
+/// One operation keeps the plain `base` name, so ids such as `threads/8`
+/// still match baselines recorded before the operation count was swept.
+fn ops_function_name(base: &str, ops: u64) -> String {
+    if ops == 1 {
+        base.to_string()
+    } else {
+        format!("{}_{}_ops", base, ops)
+    }
+}
+
+/// Like `bench_over_threads`, but also sweeps `OPS_PER_THREAD`, registering
+/// ids such as `threads/8` and `threads_10000_ops/8` in the same group,
+/// with throughput counted as threads × operations per thread.
+fn bench_over_threads_and_ops<F>(c: &mut Criterion, name: &str, mut routine: F)
+where
+    F: FnMut(&mut Bencher, usize, u64),
+{
+    let mut group = c.benchmark_group(name);
+    for ops in OPS_PER_THREAD {
+        for &threads in thread_counts() {
+            group.throughput(Throughput::Elements(threads as u64 * ops));
+            group.bench_with_input(
+                BenchmarkId::new(ops_function_name("threads", ops), threads),
+                &threads,
+                |b, &threads| routine(b, threads, ops),
+            );
+        }
+    }
+    group.finish();
+}
+
+/// Returns the stall policies every stalled benchmark is registered with,
+/// paired with the group name each one is reported under: the historical
+/// 25ms even-iteration delay keeps `name`, and `{name}_no_stall` runs the
+/// same workload with no delay so differences between primitives show up.
+fn stall_variants(name: &str) -> [(String, StallPolicy); 2] {
+    [
+        (name.to_string(), StallPolicy::EVEN_25MS),
+        (format!("{}_no_stall", name), StallPolicy::None),
+    ]
+}
+
+/// Like `bench_over_threads`, but registers the workload once per entry
+/// of `stall_variants` and hands the routine the stall policy to apply.
+fn bench_with_stalls<F>(c: &mut Criterion, name: &str, mut routine: F)
+where
+    F: FnMut(&mut Bencher, usize, StallPolicy),
+{
+    for (name, stall) in stall_variants(name) {
+        bench_over_threads(c, &name, |b, threads| routine(b, threads, stall));
+    }
+}
+
+/// Like `bench_with_stalls`, but also sweeps `OPS_PER_THREAD` and hands the
+/// routine the operation count, for the atomic benchmarks.
+fn bench_ops_with_stalls<F>(c: &mut Criterion, name: &str, mut routine: F)
+where
+    F: FnMut(&mut Bencher, usize, u64, StallPolicy),
+{
+    for (name, stall) in stall_variants(name) {
+        bench_over_threads_and_ops(c, &name, |b, threads, ops| routine(b, threads, ops, stall));
+    }
+}
+
+/// Runs `scenario` against `state` for one criterion iteration and checks that
+/// the shared value grew by exactly the number of writes the threads performed
+/// and that every read observed a value from within the iteration.
+fn run_checked_scenario<S: scenario::SharedState>(scenario: &Scenario, state: &S) {
+    let result = run_contention_scenario(scenario, state);
+    assert_eq!(result.final_value, result.initial_value + result.writes());
+    assert!(result.reads_in_range());
+}
+
+/// Returns the stall variants every lock benchmark is registered with.
+/// The 25ms delay runs both while the guard is held, under
+/// `{name}_sleep_in_critical_section`, and after it is dropped, under
+/// `{name}_sleep_outside_critical_section`, so the cost of sleeping while
+/// holding a lock is visible side by side; `{name}_no_stall` has no delay.
+fn lock_stall_variants(name: &str) -> [(String, LockStall); 3] {
+    [
+        (
+            format!("{}_sleep_in_critical_section", name),
+            LockStall {
+                policy: StallPolicy::EVEN_25MS,
+                placement: StallPlacement::InCriticalSection,
+            },
+        ),
+        (
+            format!("{}_sleep_outside_critical_section", name),
+            LockStall {
+                policy: StallPolicy::EVEN_25MS,
+                placement: StallPlacement::OutsideCriticalSection,
+            },
+        ),
+        (
+            format!("{}_no_stall", name),
+            LockStall {
+                policy: StallPolicy::None,
+                placement: StallPlacement::InCriticalSection,
+            },
+        ),
+    ]
+}
+
+/// Like `bench_ops_with_stalls`, but for lock benchmarks: registers the workload
+/// once per entry of `lock_stall_variants` and hands the routine a `LockStall`.
+fn bench_lock_with_stalls<F>(c: &mut Criterion, name: &str, mut routine: F)
+where
+    F: FnMut(&mut Bencher, usize, u64, LockStall),
+{
+    for (name, stall) in lock_stall_variants(name) {
+        bench_over_threads_and_ops(c, &name, |b, threads, ops| routine(b, threads, ops, stall));
+    }
+}
+
+/// Registers a lock benchmark that runs the library's contention scenario
+/// against `state` with `write_percent` of the operations being writes,
+/// for every stall variant, thread count, and operation count.
+fn bench_lock_scenario<S: scenario::SharedState>(
+    c: &mut Criterion,
+    name: &str,
+    state: S,
+    write_percent: u32,
+) {
+    bench_lock_with_stalls(c, name, |b, threads, ops, stall| {
+        let scenario =
+            Scenario::new(threads, ops, write_percent).with_stall(stall.policy, stall.placement);
+        b.iter(|| run_checked_scenario(&scenario, &state));
+    });
+}
+
+/// Spawns `threads` threads that each run `work` with their iteration index,
+/// then waits for all of them to finish and returns what each thread returned.
+/// This keeps the thread-spawn pattern identical to the other benchmarks
+/// while letting the caller supply only the per-thread lock operation.
+fn spawn_and_join<F, R>(threads: usize, work: F) -> Vec<R>
+where
+    F: Fn(usize) -> R + Send + Sync + 'static,
+    R: Send + 'static,
+{
+    let work = Arc::new(work);
+    let mut handles = vec![];
+    for i in 0..threads {
+        let work_clone = Arc::clone(&work);
+        let handle = thread::spawn(move || work_clone(i));
+        handles.push(handle);
+    }
+    handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .collect()
+}
+
+/// Value stored in the shared integer of the read-heavy benchmarks.
+/// Nothing writes to it, so every read must observe exactly this value.
+const READ_VALUE: u64 = 42;
+
+/// Asserts that each per-thread sum returned by a read-heavy benchmark is
+/// `READ_VALUE` times the `reads` that thread performed, which both checks the
+/// workload and keeps the compiler from discarding the reads.
+fn assert_read_sums(sums: &[u64], reads: u64) {
+    for &sum in sums {
+        assert_eq!(sum, READ_VALUE * reads);
+    }
+}
+
+/// Asserts that each per-thread sum returned by a mixed benchmark is consistent
+/// with a counter that went from `before` to `after` during the iteration:
+/// each of a thread's `ops` operations observed a value in `before..=after`.
+fn assert_observed_sums(sums: &[u64], ops: u64, before: u64, after: u64) {
+    for &sum in sums {
+        assert!(
+            (before * ops..=after * ops).contains(&sum),
+            "observed sum {} outside {}..={} for {} operations",
+            sum,
+            before * ops,
+            after * ops,
+            ops
+        );
+    }
+}
+
+/// Asserts that a counter read as `before` at the top of a criterion iteration
+/// grew by exactly `writes` by the end of it, so a primitive that loses updates
+/// fails the benchmark instead of posting a good time.
+fn assert_counter_delta(before: u64, after: u64, writes: u64) {
+    assert_eq!(
+        after - before,
+        writes,
+        "counter grew by {} instead of {}",
+        after - before,
+        writes
+    );
+}
+
+/// The scenario a hand-written mixed benchmark draws its reads and writes
+/// from, with `Scenario::operations`, so it mixes them the way
+/// `run_contention_scenario` does and counts its writes with
+/// `Scenario::expected_writes`.
+fn mixed_scenario(threads: usize, ops: u64) -> Scenario {
+    Scenario::new(threads, ops, Workload::Mixed.write_percent())
+}
+
+/// Decides whether thread `i` of `threads` receives in the mixed channel benchmarks.
+/// Even indices receive and odd indices send, except that an unpaired last
+/// thread sends, so receivers never outnumber senders and an odd thread count
+/// cannot leave a receiver blocked forever.
+fn is_channel_receiver(i: usize, threads: usize) -> bool {
+    i.is_multiple_of(2) && i + 1 < threads
+}
+
+/// A set of long-lived worker threads that run a fixed piece of work on demand.
+/// Workers are spawned once per bench function and then signalled every
+/// iteration, so the measurement covers only the signal, work, and acknowledgement
+/// round trip instead of thread creation and teardown.
+struct WorkerPool<R = ()> {
+    job_txs: Vec<mpsc::Sender<()>>,
+    done_rx: mpsc::Receiver<R>,
+    handles: Vec<thread::JoinHandle<()>>,
+}
+
+impl<R: Send + 'static> WorkerPool<R> {
+    /// Spawns `threads` workers that each call `work` with their index once per signal.
+    fn new<F>(threads: usize, work: F) -> Self
+    where
+        F: Fn(usize) -> R + Send + Sync + 'static,
+    {
+        let work = Arc::new(work);
+        let (done_tx, done_rx) = mpsc::channel();
+        let mut job_txs = vec![];
+        let mut handles = vec![];
+        for i in 0..threads {
+            let (job_tx, job_rx) = mpsc::channel::<()>();
+            let work_clone = Arc::clone(&work);
+            let done_tx_clone = done_tx.clone();
+            let handle = thread::spawn(move || {
+                while job_rx.recv().is_ok() {
+                    done_tx_clone.send(work_clone(i)).unwrap();
+                }
+            });
+            job_txs.push(job_tx);
+            handles.push(handle);
+        }
+        WorkerPool {
+            job_txs,
+            done_rx,
+            handles,
+        }
+    }
+
+    /// Signals every worker once, waits until all of them have reported completion,
+    /// and returns what each worker's `work` returned, in completion order.
+    fn run(&self) -> Vec<R> {
+        for job_tx in &self.job_txs {
+            job_tx.send(()).unwrap();
+        }
+        (0..self.job_txs.len())
+            .map(|_| self.done_rx.recv().unwrap())
+            .collect()
+    }
+}
+
+impl<R> Drop for WorkerPool<R> {
+    /// Closes the job channels so the workers exit, then joins them.
+    fn drop(&mut self) {
+        self.job_txs.clear();
+        for handle in self.handles.drain(..) {
+            handle.join().unwrap();
+        }
+    }
+}
+
+/// Builds the multi-threaded tokio runtime shared by the async benchmarks.
+/// The runtime is created once per bench function so its startup cost
+/// is not part of the measurement.
+fn tokio_runtime() -> tokio::runtime::Runtime {
+    tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+        .unwrap()
+}
+
+/// Spawns `tasks` tokio tasks that each run `work` with their iteration index,
+/// then waits for all of them to finish and returns what each task returned.
+/// This is the async counterpart of `spawn_and_join`.
+async fn spawn_tasks_and_join<F, Fut, R>(tasks: usize, work: F) -> Vec<R>
+where
+    F: Fn(usize) -> Fut,
+    Fut: Future<Output = R> + Send + 'static,
+    R: Send + 'static,
+{
+    let mut handles = vec![];
+    for i in 0..tasks {
+        handles.push(tokio::spawn(work(i)));
+    }
+    let mut results = vec![];
+    for handle in handles {
+        results.push(handle.await.unwrap());
+    }
+    results
+}
+
+/// Read-heavy counters start at `READ_VALUE` so the read sums can be checked
+/// against it; the others start at zero.
+fn counter_initial_value(workload: Workload) -> u64 {
+    match workload {
+        Workload::ReadHeavy => READ_VALUE,
+        Workload::WriteHeavy | Workload::Mixed => 0,
+    }
+}
+
+/// Benchmark for `workload` on an Arc-wrapped counter of type `C`.
+/// This function measures the performance of multiple threads reading from
+/// and/or incrementing the shared counter, registered as `name` with every
+/// lock stall variant, thread count, and operation count. Mixed workloads
+/// also get a write ratio sweep.
+fn bench_counter<C>(c: &mut Criterion, name: &str, workload: Workload)
+where
+    C: counter::SharedCounter + From<u64> + 'static,
+{
+    let data = Arc::new(C::from(counter_initial_value(workload)));
+    #[cfg(feature = "latency")]
+    bench_acquisition_latency(c, name, &data, workload.write_percent());
+    if workload == Workload::Mixed {
+        bench_write_ratios(c, name, &data);
+    }
+    bench_lock_scenario(c, name, data, workload.write_percent());
+}
+
+/// Write percentages the write ratio sweep runs at, from nearly read-only,
+/// where an RwLock should pay off, to nearly write-only.
+const WRITE_PERCENTS: [u32; 5] = [1, 10, 25, 50, 90];
+
+/// Threads in the write ratio sweep, fixed so the ratio is the only variable.
+const WRITE_RATIO_THREADS: usize = 8;
+
+/// Operations each thread performs per iteration of the write ratio sweep,
+/// enough for a 1% ratio to mean about a hundred writes per thread.
+const WRITE_RATIO_OPS: u64 = 10_000;
+
+/// Registers `{name}_write_ratio`, a sibling of the mixed `name` benchmark
+/// with one benchmark per `WRITE_PERCENTS` entry under ids such as
+/// `write_percent/10`, so criterion plots iteration time against the write
+/// ratio. Each operation is drawn from the scenario's seeded RNG, so every
+/// primitive sees the same reads and writes at a given ratio.
+fn bench_write_ratios<S: scenario::SharedState>(c: &mut Criterion, name: &str, state: &S) {
+    let mut group = c.benchmark_group(format!("{}_write_ratio", name));
+    group.throughput(Throughput::Elements(
+        WRITE_RATIO_THREADS as u64 * WRITE_RATIO_OPS,
+    ));
+    for write_percent in WRITE_PERCENTS {
+        let scenario = Scenario::new(WRITE_RATIO_THREADS, WRITE_RATIO_OPS, write_percent);
+        let expected_writes = scenario.expected_writes();
+        group.bench_with_input(
+            BenchmarkId::new("write_percent", write_percent),
+            &scenario,
+            |b, scenario| {
+                b.iter(|| {
+                    let result = run_contention_scenario(scenario, state);
+                    assert_counter_delta(result.initial_value, result.final_value, expected_writes);
+                    assert!(result.reads_in_range());
                 });
-                handles.push(handle);
+            },
+        );
+    }
+    group.finish();
+}
+
+/// Operations each thread performs per iteration in the acquisition latency
+/// benchmarks, enough for the histogram tails to mean something.
+#[cfg(feature = "latency")]
+const LATENCY_OPS_PER_THREAD: u64 = 1_000;
+
+/// Registers `{name}_acquisition_latency`, a sibling of the `name` lock benchmark
+/// that records how long every operation waited to acquire the lock, without
+/// stalls, and prints the p50, p90, p99, p99.9, and max of each thread count's
+/// merged histogram once criterion is done with it.
+#[cfg(feature = "latency")]
+fn bench_acquisition_latency<S: scenario::SharedState>(
+    c: &mut Criterion,
+    name: &str,
+    state: &S,
+    write_percent: u32,
+) {
+    use basic_rust_concurrency_benchmarks::latency::{self, LatencySummary};
+
+    let group = format!("{}_acquisition_latency", name);
+    bench_over_threads_with_elements(
+        c,
+        &group,
+        |threads| threads as u64 * LATENCY_OPS_PER_THREAD,
+        |b, threads| {
+            let scenario =
+                Scenario::new(threads, LATENCY_OPS_PER_THREAD, write_percent).with_latency();
+            // Counted once up front, since the count replays every draw.
+            let expected_writes = scenario.expected_writes();
+            let mut merged = latency::new_histogram();
+            b.iter(|| {
+                let result = run_contention_scenario(&scenario, state);
+                assert_eq!(result.final_value, result.initial_value + expected_writes);
+                merged
+                    .add(result.acquisition_latency.as_ref().unwrap())
+                    .unwrap();
+            });
+            if !merged.is_empty() {
+                println!(
+                    "{}/threads/{}: {}",
+                    group,
+                    threads,
+                    LatencySummary::of(&merged)
+                );
             }
-            for handle in handles {
-                handle.join().unwrap();
+        },
+    );
+}
+
+/// Registers one workload of a generic benchmark for a single implementation
+/// under the given benchmark name, such as `bench_counter` for one lock type.
+type WorkloadBench = fn(&mut Criterion, &str, Workload);
+
+/// A set of primitives crossed with a set of workloads.
+/// Registering the matrix registers every primitive with every workload as
+/// `{primitive}_{workload}`, and each of those with every stall variant,
+/// thread count, and operation count its bench function sweeps, so adding a
+/// primitive is one `primitive` call.
+struct ScenarioMatrix {
+    primitives: Vec<(&'static str, WorkloadBench)>,
+    workloads: Vec<Workload>,
+}
+
+impl ScenarioMatrix {
+    /// Creates a matrix of `workloads` with no primitives yet.
+    fn new(workloads: &[Workload]) -> Self {
+        ScenarioMatrix {
+            primitives: vec![],
+            workloads: workloads.to_vec(),
+        }
+    }
+
+    /// Adds a primitive, benchmarked by `bench`, under the name prefix `name`.
+    fn primitive(mut self, name: &'static str, bench: WorkloadBench) -> Self {
+        self.primitives.push((name, bench));
+        self
+    }
+
+    /// Registers every primitive with every workload.
+    fn register(&self, c: &mut Criterion) {
+        for &(name, bench) in &self.primitives {
+            for &workload in &self.workloads {
+                bench(c, &format!("{}_{}", name, workload.name()), workload);
             }
-        });
-    });
+        }
+    }
 }
 
-/// Benchmark for write-heavy workloads using Arc<Mutex>.
-/// This function measures the performance of multiple threads
-/// concurrently writing to an Arc-wrapped Mutex-protected integer,
-/// with a delay added for every even iteration.
-fn arc_mutex_write_heavy(c: &mut Criterion) {
-    let data = Arc::new(Mutex::new(0));
-    c.bench_function("arc_mutex_write_heavy", |b| {
-        b.iter(|| {
-            let mut handles = vec![];
-            for i in 0..10 {
+/// Registers every scenario in the library's `registry::SCENARIOS` with
+/// `bench_counter`, so the benchmarks, `concbench run`, and `concbench list`
+/// share one list of names. Adding a lock means implementing `SharedCounter`
+/// for it, registering it in the library, and adding a line here.
+fn counter_benches(c: &mut Criterion) {
+    for registered in &registry::SCENARIOS {
+        let bench: WorkloadBench = match registered.primitive {
+            "mutex" => bench_counter::<Mutex<u64>>,
+            "rwlock" => bench_counter::<RwLock<u64>>,
+            "parking_lot_mutex" => bench_counter::<parking_lot::Mutex<u64>>,
+            "parking_lot_rwlock" => bench_counter::<parking_lot::RwLock<u64>>,
+            other => panic!(
+                "registered scenario {} uses `{}`, which has no counter benchmark",
+                registered.name, other
+            ),
+        };
+        bench(c, registered.name, registered.workload);
+    }
+}
+
+/// Benchmark for write-heavy workloads using Arc<Mutex> under each stall policy.
+/// This function runs the arc_mutex_write_heavy workload with 8 threads
+/// once per StallPolicy, so the effect of fixed, uniform, and Poisson-distributed
+/// stalls inside the critical section can be compared directly.
+fn arc_mutex_stall_policies(c: &mut Criterion) {
+    let data = Arc::new(Mutex::new(0u64));
+    let policies = [
+        ("none", StallPolicy::None),
+        ("fixed_even_25ms", StallPolicy::EVEN_25MS),
+        (
+            "uniform_0_10ms",
+            StallPolicy::Uniform {
+                min: Duration::ZERO,
+                max: Duration::from_millis(10),
+            },
+        ),
+        (
+            "poisson_5ms",
+            StallPolicy::Poisson {
+                mean: Duration::from_millis(5),
+            },
+        ),
+    ];
+    let mut group = c.benchmark_group("arc_mutex_stall_policies");
+    group.throughput(Throughput::Elements(8));
+    for (label, stall) in policies {
+        group.bench_function(label, |b| {
+            b.iter(|| {
+                let before = *data.lock().unwrap();
                 let data_clone = Arc::clone(&data);
-                let handle = thread::spawn(move || {
+                spawn_and_join(8, move |i| {
                     let mut num = data_clone.lock().unwrap();
                     *num += 1;
-                    even_iteration_delay(i);
+                    stall.apply(i);
                 });
-                handles.push(handle);
-            }
-            for handle in handles {
-                handle.join().unwrap();
-            }
+                assert_counter_delta(before, *data.lock().unwrap(), 8);
+            });
         });
-    });
+    }
+    group.finish();
 }
 
-/// Benchmark for read-heavy workloads using Arc<RwLock>.
-/// This function measures the performance of multiple threads
-/// concurrently reading from an Arc-wrapped RwLock-protected integer,
-/// with a delay added for every even iteration.
-fn arc_rwlock_read_heavy(c: &mut Criterion) {
-    let data = Arc::new(RwLock::new(0));
-    c.bench_function("arc_rwlock_read_heavy", |b| {
-        b.iter(|| {
-            let mut handles = vec![];
-            for i in 0..10 {
-                let data_clone = Arc::clone(&data);
-                let handle = thread::spawn(move || {
-                    let _unused = data_clone.read().unwrap();
-                    even_iteration_delay(i);
+/// Benchmark for write-heavy workloads using Arc<Mutex>, split into lock wait and hold time.
+/// This function runs the arc_mutex_write_heavy workload with 100 operations per
+/// thread and lock timing recorded, once per lock stall variant, and prints the
+/// average time per iteration that threads spent waiting for the lock and
+/// holding it, so moving the delay inside versus outside the critical section
+/// shows up as time moving between the two.
+fn arc_mutex_wait_vs_hold(c: &mut Criterion) {
+    let data = Arc::new(Mutex::new(0u64));
+    for (name, stall) in lock_stall_variants("arc_mutex_wait_vs_hold") {
+        bench_over_threads_with_elements(
+            c,
+            &name,
+            |threads| threads as u64 * 100,
+            |b, threads| {
+                let scenario = Scenario::new(threads, 100, 100)
+                    .with_stall(stall.policy, stall.placement)
+                    .with_timing();
+                let mut iterations = 0u32;
+                let mut waiting = Duration::ZERO;
+                let mut holding = Duration::ZERO;
+                let expected_writes = scenario.expected_writes();
+                b.iter(|| {
+                    let result = run_contention_scenario(&scenario, &data);
+                    assert_eq!(result.final_value, result.initial_value + expected_writes);
+                    iterations += 1;
+                    waiting += result.waiting();
+                    holding += result.in_critical_section();
                 });
-                handles.push(handle);
-            }
-            for handle in handles {
-                handle.join().unwrap();
-            }
+                if iterations > 0 {
+                    println!(
+                        "{}/threads/{}: waiting {:.3?}, holding {:.3?} per iteration across all threads",
+                        name,
+                        threads,
+                        waiting / iterations,
+                        holding / iterations
+                    );
+                }
+            },
+        );
+    }
+}
+
+/// Benchmark for write-heavy workloads using Arc<Mutex> with and without thread pinning.
+/// This function runs the arc_mutex_write_heavy workload with 10,000
+/// operations per thread and no stall, once with the scheduler free to migrate
+/// threads and once with every thread pinned to its own physical core, so the
+/// cost of migration shows up as the difference between the two. Thread
+/// counts beyond the number of physical cores run unpinned, with a warning.
+#[cfg(feature = "pin")]
+fn pinned_vs_unpinned(c: &mut Criterion) {
+    let data = Arc::new(Mutex::new(0u64));
+    let mut group = c.benchmark_group("pinned_vs_unpinned");
+    for &threads in thread_counts() {
+        group.throughput(Throughput::Elements(threads as u64 * 10_000));
+        let unpinned = Scenario::new(threads, 10_000, 100);
+        for (name, scenario) in [
+            ("unpinned", unpinned),
+            ("pinned", unpinned.with_pinned_threads()),
+        ] {
+            group.bench_with_input(BenchmarkId::new(name, threads), &scenario, |b, scenario| {
+                b.iter(|| run_checked_scenario(scenario, &data));
+            });
+        }
+    }
+    group.finish();
+}
+#[cfg(not(feature = "pin"))]
+fn pinned_vs_unpinned(_: &mut Criterion) {}
+
+/// Multiples of the machine's available parallelism the oversubscription benchmarks run at.
+const OVERSUBSCRIPTION_FACTORS: [usize; 4] = [1, 2, 4, 8];
+
+/// Operations each thread performs per iteration in the oversubscription
+/// benchmarks, enough that threads are routinely preempted mid-run.
+const OVERSUBSCRIPTION_OPS: u64 = 1_000;
+
+/// Registers `name/{factor}x/{threads}` in `group` for every oversubscription
+/// factor: `threads` is that multiple of the available parallelism, and every
+/// thread performs `OVERSUBSCRIPTION_OPS` bare increments on `state`.
+fn bench_oversubscribed<S: scenario::SharedState>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    name: &str,
+    state: &S,
+) {
+    for factor in OVERSUBSCRIPTION_FACTORS {
+        let threads = available_threads() * factor;
+        group.throughput(Throughput::Elements(threads as u64 * OVERSUBSCRIPTION_OPS));
+        let scenario = Scenario::new(threads, OVERSUBSCRIPTION_OPS, 100);
+        let id = BenchmarkId::new(format!("{}/{}x", name, factor), threads);
+        group.bench_with_input(id, &scenario, |b, scenario| {
+            b.iter(|| run_checked_scenario(scenario, state));
         });
-    });
+    }
 }
 
-/// Benchmark for write-heavy workloads using Arc<RwLock>.
-/// This function measures the performance of multiple threads
-/// concurrently writing to an Arc-wrapped RwLock-protected integer,
-/// with a delay added for every even iteration.
-fn arc_rwlock_write_heavy(c: &mut Criterion) {
-    let data = Arc::new(RwLock::new(0));
-    c.bench_function("arc_rwlock_write_heavy", |b| {
+/// Benchmark for write-heavy lock workloads with more threads than cores.
+/// This function runs the write-heavy workload against std's Mutex and
+/// parking_lot's Mutex with 1, 2, 4, and 8 times as many threads as the
+/// machine has available parallelism, 1,000 increments each with no stall. The
+/// critical section is a single increment, so what slows the oversubscribed
+/// runs down is the OS preempting a thread while it holds the lock.
+fn oversubscription(c: &mut Criterion) {
+    let mut group = c.benchmark_group("oversubscription");
+    bench_oversubscribed(&mut group, "std_mutex", &Arc::new(Mutex::new(0u64)));
+    bench_oversubscribed(
+        &mut group,
+        "parking_lot_mutex",
+        &Arc::new(parking_lot::Mutex::new(0u64)),
+    );
+    group.finish();
+}
+
+/// Lock/increment/unlock cycles per iteration in the uncontended benchmarks.
+const UNCONTENDED_OPS: u64 = 1_000_000;
+
+/// Registers `name` in `group`: one thread increments a fresh `C` through
+/// `SharedCounter::increment` `UNCONTENDED_OPS` times, with `black_box` around
+/// the counter so the compiler cannot fold the loop, then checks the total.
+fn bench_uncontended<C: counter::SharedCounter + Default>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    name: &str,
+) {
+    let counter = C::default();
+    group.bench_function(name, |b| {
         b.iter(|| {
-            let mut handles = vec![];
-            for i in 0..10 {
-                let data_clone = Arc::clone(&data);
-                let handle = thread::spawn(move || {
-                    let mut num = data_clone.write().unwrap();
-                    *num += 1;
-                    even_iteration_delay(i);
-                });
-                handles.push(handle);
-            }
-            for handle in handles {
-                handle.join().unwrap();
+            let before = counter.get();
+            for _ in 0..UNCONTENDED_OPS {
+                black_box(&counter).increment();
             }
+            assert_counter_delta(before, counter.get(), UNCONTENDED_OPS);
         });
     });
 }
 
-/// Benchmark for mixed read/write workloads using Arc<Mutex>.
-/// This function measures the performance of multiple threads
-/// performing both reads and writes to an Arc-wrapped Mutex-protected integer,
-/// with a delay added for every even iteration.
-fn arc_mutex_mixed(c: &mut Criterion) {
-    let data = Arc::new(Mutex::new(0));
-    c.bench_function("arc_mutex_mixed", |b| {
+/// Registers `name` in `group`: one thread reads a fresh `C` through
+/// `SharedCounter::get` `UNCONTENDED_OPS` times, for the read side of the RwLocks.
+fn bench_uncontended_reads<C: counter::SharedCounter + Default>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    name: &str,
+) {
+    let counter = C::default();
+    group.bench_function(name, |b| {
         b.iter(|| {
-            let mut handles = vec![];
-            for i in 0..10 {
-                let data_clone = Arc::clone(&data);
-                let handle = if i % 2 == 0 {
-                    thread::spawn(move || {
-                        let _unused = data_clone.lock().unwrap();
-                        even_iteration_delay(i);
-                    })
-                } else {
-                    thread::spawn(move || {
-                        let mut num = data_clone.lock().unwrap();
-                        *num += 1;
-                        even_iteration_delay(i);
-                    })
-                };
-                handles.push(handle);
-            }
-            for handle in handles {
-                handle.join().unwrap();
+            for _ in 0..UNCONTENDED_OPS {
+                black_box(black_box(&counter).get());
             }
         });
     });
 }
 
-/// Benchmark for mixed read/write workloads using Arc<RwLock>.
-/// This function measures the performance of multiple threads
-/// performing both reads and writes to an Arc-wrapped RwLock-protected integer,
-/// with a delay added for every even iteration.
-fn arc_rwlock_mixed(c: &mut Criterion) {
-    let data = Arc::new(RwLock::new(0));
-    c.bench_function("arc_rwlock_mixed", |b| {
+/// Benchmark for the inherent cost of every primitive without contention.
+/// This function measures a single thread performing 1,000,000
+/// lock/increment/unlock cycles on std's Mutex and RwLock, parking_lot's Mutex,
+/// fair Mutex, and RwLock, and the library's ticket and MCS locks, and
+/// 1,000,000 `fetch_add`s on an AtomicU64, plus
+/// 1,000,000 read locks on each RwLock. Throughput counts every cycle, so the
+/// report shows the per-operation cost directly, and the difference from the
+/// contended benchmarks is the cost of contention.
+fn uncontended(c: &mut Criterion) {
+    let mut group = c.benchmark_group("uncontended");
+    group.throughput(Throughput::Elements(UNCONTENDED_OPS));
+    bench_uncontended::<Mutex<u64>>(&mut group, "std_mutex");
+    bench_uncontended::<RwLock<u64>>(&mut group, "std_rwlock_write");
+    bench_uncontended_reads::<RwLock<u64>>(&mut group, "std_rwlock_read");
+    bench_uncontended::<parking_lot::Mutex<u64>>(&mut group, "parking_lot_mutex");
+    bench_uncontended::<counter::FairMutex>(&mut group, "parking_lot_fair_mutex");
+    bench_uncontended::<parking_lot::RwLock<u64>>(&mut group, "parking_lot_rwlock_write");
+    bench_uncontended_reads::<parking_lot::RwLock<u64>>(&mut group, "parking_lot_rwlock_read");
+    bench_uncontended::<TicketLock<u64>>(&mut group, "ticket_lock");
+    bench_uncontended::<McsLock<u64>>(&mut group, "mcs_lock");
+    bench_uncontended::<AtomicU64>(&mut group, "atomic_u64");
+    group.finish();
+}
+
+/// Benchmark for write-heavy workloads using Mutex::try_lock with exponential backoff.
+/// This function measures the performance of multiple threads that retry
+/// try_lock on an Arc-wrapped Mutex-protected integer with crossbeam's Backoff
+/// until they succeed, with a delay added for every even iteration.
+/// Every failed try_lock is counted, and the average number of failures per
+/// iteration is printed after the run as a measure of contention intensity.
+fn arc_mutex_try_lock_backoff(c: &mut Criterion) {
+    let data = Arc::new(Mutex::new(0u64));
+    let failures = Arc::new(AtomicU64::new(0));
+    let mut iterations = 0u64;
+    bench_lock_with_stalls(c, "arc_mutex_try_lock_backoff", |b, threads, ops, stall| {
         b.iter(|| {
-            let mut handles = vec![];
-            for i in 0..10 {
-                let data_clone = Arc::clone(&data);
-                let handle = if i % 2 == 0 {
-                    thread::spawn(move || {
-                        let _unused = data_clone.read().unwrap();
-                        even_iteration_delay(i);
-                    })
-                } else {
-                    thread::spawn(move || {
-                        let mut num = data_clone.write().unwrap();
-                        *num += 1;
-                        even_iteration_delay(i);
-                    })
-                };
-                handles.push(handle);
-            }
-            for handle in handles {
-                handle.join().unwrap();
-            }
+            iterations += 1;
+            let before = *data.lock().unwrap();
+            let data_clone = Arc::clone(&data);
+            let failures_clone = Arc::clone(&failures);
+            spawn_and_join(threads, move |i| {
+                for op in 0..ops {
+                    let stall = stall.only_on_first(op);
+                    let backoff = Backoff::new();
+                    let mut num = loop {
+                        match data_clone.try_lock() {
+                            Ok(guard) => break guard,
+                            Err(_) => {
+                                failures_clone.fetch_add(1, Ordering::Relaxed);
+                                backoff.snooze();
+                            }
+                        }
+                    };
+                    *num += 1;
+                    stall.while_locked(i);
+                    drop(num);
+                    stall.after_unlock(i);
+                }
+            });
+            assert_counter_delta(before, *data.lock().unwrap(), threads as u64 * ops);
         });
     });
+    if iterations > 0 {
+        println!(
+            "arc_mutex_try_lock_backoff: {:.1} try_lock failures per iteration over {} iterations",
+            failures.load(Ordering::Relaxed) as f64 / iterations as f64,
+            iterations
+        );
+    }
 }
 
-/// Benchmark for read-heavy workloads using mpsc channels.
-/// This function measures the performance of multiple threads
-/// sending and receiving messages through mpsc channels,
-/// with a delay added for every even iteration.
-fn mpsc_read_heavy(c: &mut Criterion) {
-    c.bench_function("mpsc_read_heavy", |b| {
-        b.iter(|| {
-            let (tx, rx) = mpsc::channel();
-            let rx = Arc::new(Mutex::new(rx));
-            let mut handles = vec![];
-            for i in 0..10 {
-                let tx_clone = tx.clone();
-                let handle = thread::spawn(move || {
-                    tx_clone.send(i).unwrap();
-                    even_iteration_delay(i);
-                });
-                handles.push(handle);
-            }
-            for handle in handles {
-                handle.join().unwrap();
+/// Name of the thread `poison_recovery` panics on, so its panic hook can keep
+/// the deliberate panics quiet while still reporting any other panic.
+const POISONER_THREAD: &str = "poisoner";
+
+/// Benchmark for write-heavy workloads using an Arc<Mutex> that gets poisoned.
+/// This function measures threads each incrementing an Arc-wrapped
+/// Mutex-protected integer once, recovering the guard with
+/// `PoisonError::into_inner` instead of unwrapping. Under `poisoned`, thread 0
+/// panics while holding the lock, so every thread that locks after it takes
+/// the recovery path; `healthy` runs the same workload without the panic, so
+/// the difference is the cost of the panic and recovery. The poison is cleared
+/// after every iteration.
+fn poison_recovery(c: &mut Criterion) {
+    let data = Arc::new(Mutex::new(0u64));
+    let prev = Arc::new(panic::take_hook());
+    panic::set_hook(Box::new({
+        let prev = Arc::clone(&prev);
+        move |info| {
+            if thread::current().name() != Some(POISONER_THREAD) {
+                prev(info);
             }
-            let mut recv_handles = vec![];
-            for i in 0..10 {
-                let rx_clone = Arc::clone(&rx);
-                let handle = thread::spawn(move || {
-                    let _unused = rx_clone.lock().unwrap().recv().unwrap();
-                    even_iteration_delay(i);
+        }
+    }));
+    let mut group = c.benchmark_group("poison_recovery");
+    for &threads in thread_counts() {
+        group.throughput(Throughput::Elements(threads as u64));
+        for (label, poison) in [("healthy", false), ("poisoned", true)] {
+            group.bench_with_input(BenchmarkId::new(label, threads), &threads, |b, &threads| {
+                b.iter(|| {
+                    let before = *data.lock().unwrap_or_else(PoisonError::into_inner);
+                    let handles: Vec<_> = (0..threads)
+                        .map(|i| {
+                            let data_clone = Arc::clone(&data);
+                            let panics = poison && i == 0;
+                            let name = if panics { POISONER_THREAD } else { "worker" };
+                            thread::Builder::new()
+                                .name(name.to_string())
+                                .spawn(move || {
+                                    let mut num =
+                                        data_clone.lock().unwrap_or_else(PoisonError::into_inner);
+                                    *num += 1;
+                                    if panics {
+                                        panic!("poisoning the lock on purpose");
+                                    }
+                                })
+                                .unwrap()
+                        })
+                        .collect();
+                    let panicked = handles
+                        .into_iter()
+                        .map(|handle| handle.join().is_err())
+                        .filter(|&panicked| panicked)
+                        .count();
+                    assert_eq!(panicked, usize::from(poison));
+                    let after = *data.lock().unwrap_or_else(PoisonError::into_inner);
+                    assert_counter_delta(before, after, threads as u64);
+                    data.clear_poison();
                 });
-                recv_handles.push(handle);
-            }
-            for handle in recv_handles {
-                handle.join().unwrap();
-            }
-        });
-    });
+            });
+        }
+    }
+    group.finish();
+    // Dropping the filtering hook releases its handle on the previous one,
+    // which goes back in place as it was.
+    drop(panic::take_hook());
+    panic::set_hook(Arc::into_inner(prev).expect("the filtering hook was dropped"));
 }
 
-/// Benchmark for write-heavy workloads using mpsc channels.
+/// Benchmark for read-heavy workloads using Arc<ArcSwap>.
 /// This function measures the performance of multiple threads
-/// sending and receiving messages through mpsc channels,
+/// concurrently loading the current value from an Arc-wrapped ArcSwap,
 /// with a delay added for every even iteration.
-fn mpsc_write_heavy(c: &mut Criterion) {
-    c.bench_function("mpsc_write_heavy", |b| {
+#[cfg(feature = "arc-swap")]
+fn arcswap_read_heavy(c: &mut Criterion) {
+    let data = Arc::new(arc_swap::ArcSwap::from_pointee(READ_VALUE));
+    bench_ops_with_stalls(c, "arcswap_read_heavy", |b, threads, ops, stall| {
         b.iter(|| {
-            let (tx, rx) = mpsc::channel();
-            let rx = Arc::new(Mutex::new(rx));
-            let mut handles = vec![];
-            for i in 0..10 {
-                let tx_clone = tx.clone();
-                let handle = thread::spawn(move || {
-                    tx_clone.send(i).unwrap();
-                    even_iteration_delay(i);
-                });
-                handles.push(handle);
-            }
-            for handle in handles {
-                handle.join().unwrap();
-            }
-            let mut recv_handles = vec![];
-            for i in 0..10 {
-                let rx_clone = Arc::clone(&rx);
-                let handle = thread::spawn(move || {
-                    let _unused = rx_clone.lock().unwrap().recv().unwrap();
-                    even_iteration_delay(i);
-                });
-                recv_handles.push(handle);
-            }
-            for handle in recv_handles {
-                handle.join().unwrap();
-            }
+            let data_clone = Arc::clone(&data);
+            let sums = spawn_and_join(threads, move |i| {
+                let mut sum = 0;
+                for op in 0..ops {
+                    sum += black_box(**data_clone.load());
+                    stall.only_on_first(op).apply(i);
+                }
+                sum
+            });
+            assert_read_sums(&sums, ops);
         });
     });
 }
 
-/// Benchmark for mixed read/write workloads using mpsc channels.
+/// Benchmark for mixed read/write workloads using Arc<ArcSwap>.
 /// This function measures the performance of multiple threads
-/// performing both sending and receiving operations through mpsc channels,
+/// loading from and storing new values into an Arc-wrapped ArcSwap,
 /// with a delay added for every even iteration.
-fn mpsc_mixed(c: &mut Criterion) {
-    c.bench_function("mpsc_mixed", |b| {
+/// Writers publish an incremented value with `rcu`, so the value only grows
+/// and reads can be checked the same way as in the lock benchmarks.
+#[cfg(feature = "arc-swap")]
+fn arcswap_mixed(c: &mut Criterion) {
+    let data = Arc::new(arc_swap::ArcSwap::from_pointee(0u64));
+    bench_ops_with_stalls(c, "arcswap_mixed", |b, threads, ops, stall| {
+        let scenario = mixed_scenario(threads, ops);
+        let expected_writes = scenario.expected_writes();
         b.iter(|| {
-            let (tx, rx) = mpsc::channel();
-            let rx = Arc::new(Mutex::new(rx));
-            let mut handles = vec![];
-            for i in 0..10 {
-                let tx_clone = tx.clone();
-                let rx_clone = Arc::clone(&rx);
-                let handle = if i % 2 == 0 {
-                    thread::spawn(move || {
-                        let _unused = rx_clone.lock().unwrap().recv().unwrap();
-                        even_iteration_delay(i);
-                    })
-                } else {
-                    thread::spawn(move || {
-                        tx_clone.send(i).unwrap();
-                        even_iteration_delay(i);
-                    })
-                };
-                handles.push(handle);
-            }
-            for handle in handles {
-                handle.join().unwrap();
-            }
+            let before = **data.load();
+            let data_clone = Arc::clone(&data);
+            let sums = spawn_and_join(threads, move |i| {
+                let mut operations = scenario.operations(i);
+                let mut sum = 0;
+                for op in 0..ops {
+                    sum += if operations.next_is_write() {
+                        *data_clone.rcu(|current| **current + 1) + 1
+                    } else {
+                        black_box(**data_clone.load())
+                    };
+                    stall.only_on_first(op).apply(i);
+                }
+                sum
+            });
+            let after = **data.load();
+            assert_observed_sums(&sums, ops, before, after);
+            assert_counter_delta(before, after, expected_writes);
         });
     });
 }
 
-criterion_group!(
-    benches,
-    arc_mutex_read_heavy,
-    arc_mutex_write_heavy,
-    arc_rwlock_read_heavy,
-    arc_rwlock_write_heavy,
-    arc_mutex_mixed,
-    arc_rwlock_mixed,
-    mpsc_read_heavy,
-    mpsc_write_heavy,
-    mpsc_mixed
-);
-criterion_main!(benches);
+#[cfg(not(feature = "arc-swap"))]
+fn arcswap_read_heavy(_: &mut Criterion) {}
+#[cfg(not(feature = "arc-swap"))]
+fn arcswap_mixed(_: &mut Criterion) {}
+
+/// Operations each thread performs per iteration in the value size benchmarks.
+const VALUE_SIZE_OPS: u64 = 100;
+
+/// Bytes in the large value the value size benchmarks protect, one page.
+const PAGE_BYTES: usize = 4096;
+
+/// A value the value size benchmarks protect, from a bare integer up to a
+/// page that every write and read has to touch in full.
+trait ProtectedValue: Clone + Send + Sync + 'static {
+    /// The size label the value is reported under.
+    const NAME: &'static str;
+
+    /// A value with every byte set to `byte`.
+    fn filled(byte: u8) -> Self;
+
+    /// Overwrites every byte with `byte`.
+    fn fill(&mut self, byte: u8);
+
+    /// Whether every byte equals the first, as after a single `fill`. A read
+    /// that overlapped a write sees bytes from both and is not uniform.
+    fn is_uniform(&self) -> bool;
+}
+
+impl ProtectedValue for u64 {
+    const NAME: &'static str = "8_bytes";
+
+    fn filled(byte: u8) -> Self {
+        u64::from_ne_bytes([byte; 8])
+    }
+
+    fn fill(&mut self, byte: u8) {
+        *self = Self::filled(byte);
+    }
+
+    fn is_uniform(&self) -> bool {
+        let bytes = self.to_ne_bytes();
+        bytes.iter().all(|&byte| byte == bytes[0])
+    }
+}
+
+/// A 4 KiB buffer stored inline, so the lock protects the bytes themselves
+/// rather than a pointer to them.
+#[derive(Clone)]
+struct Page([u8; PAGE_BYTES]);
+
+impl ProtectedValue for Page {
+    const NAME: &'static str = "4096_bytes";
+
+    fn filled(byte: u8) -> Self {
+        Page([byte; PAGE_BYTES])
+    }
+
+    fn fill(&mut self, byte: u8) {
+        self.0.fill(byte);
+    }
+
+    fn is_uniform(&self) -> bool {
+        self.0.iter().all(|&byte| byte == self.0[0])
+    }
+}
+
+/// Runs `VALUE_SIZE_OPS` operations on each of `threads` threads, where even
+/// threads call `read` and assert that the value it checked was not torn,
+/// and odd threads call `write` with a byte to fill the value with.
+fn run_value_workload<T: ProtectedValue>(
+    threads: usize,
+    read: impl Fn() -> bool + Send + Sync + 'static,
+    write: impl Fn(u8) + Send + Sync + 'static,
+) {
+    spawn_and_join(threads, move |i| {
+        for op in 0..VALUE_SIZE_OPS {
+            if i.is_multiple_of(2) {
+                assert!(black_box(read()), "torn read of a {} value", T::NAME);
+            } else {
+                write(op as u8);
+            }
+        }
+    });
+}
+
+/// Registers the Mutex, RwLock, and, with the `arc-swap` feature,
+/// copy-on-write ArcSwap benchmarks for one value type at one thread count.
+fn bench_value_size<T: ProtectedValue>(group: &mut BenchmarkGroup<'_, WallTime>, threads: usize) {
+    let id = |primitive| BenchmarkId::new(format!("{}/{}", primitive, T::NAME), threads);
+    let mutex = Arc::new(Mutex::new(T::filled(0)));
+    group.bench_function(id("mutex"), |b| {
+        b.iter(|| {
+            let (reader, writer) = (Arc::clone(&mutex), Arc::clone(&mutex));
+            run_value_workload::<T>(
+                threads,
+                move || reader.lock().unwrap().is_uniform(),
+                move |byte| writer.lock().unwrap().fill(byte),
+            );
+        });
+    });
+    let rwlock = Arc::new(RwLock::new(T::filled(0)));
+    group.bench_function(id("rwlock"), |b| {
+        b.iter(|| {
+            let (reader, writer) = (Arc::clone(&rwlock), Arc::clone(&rwlock));
+            run_value_workload::<T>(
+                threads,
+                move || reader.read().unwrap().is_uniform(),
+                move |byte| writer.write().unwrap().fill(byte),
+            );
+        });
+    });
+    #[cfg(feature = "arc-swap")]
+    {
+        let swap = Arc::new(arc_swap::ArcSwap::from_pointee(T::filled(0)));
+        group.bench_function(id("arcswap_cow"), |b| {
+            b.iter(|| {
+                let (reader, writer) = (Arc::clone(&swap), Arc::clone(&swap));
+                run_value_workload::<T>(
+                    threads,
+                    move || reader.load().is_uniform(),
+                    move |byte| {
+                        writer.rcu(|current| {
+                            let mut next = T::clone(current);
+                            next.fill(byte);
+                            next
+                        });
+                    },
+                );
+            });
+        });
+    }
+}
+
+/// Benchmark for how the size of the protected value changes the comparison
+/// between locks and copy-on-write.
+/// This function measures threads performing 100 operations each, half of
+/// them reading and half writing, on an 8-byte u64 and on a 4 KiB buffer
+/// stored inline under a Mutex, an RwLock, and, with the `arc-swap` feature,
+/// an ArcSwap whose writers clone the current value, modify it, and publish
+/// the copy with `rcu`. Writers memset the whole value and readers check
+/// that every byte matches, so no read saw a half-written value. Ids such as
+/// `value_size/arcswap_cow/4096_bytes/8` name the primitive, value size, and
+/// thread count.
+fn value_size(c: &mut Criterion) {
+    let mut group = c.benchmark_group("value_size");
+    for &threads in thread_counts() {
+        group.throughput(Throughput::Elements(threads as u64 * VALUE_SIZE_OPS));
+        bench_value_size::<u64>(&mut group, threads);
+        bench_value_size::<Page>(&mut group, threads);
+    }
+    group.finish();
+}
+
+/// Operations each thread performs per iteration in the seqlock benchmarks.
+const SEQLOCK_OPS: u64 = 1_000;
+
+/// How often the one writer in the seqlock benchmarks writes instead of
+/// reading: once every this many operations.
+const SEQLOCK_WRITE_EVERY: u64 = 100;
+
+/// Runs `SEQLOCK_OPS` operations on each of `threads` threads, where thread
+/// 0 calls `write` once every `SEQLOCK_WRITE_EVERY` operations and every
+/// other operation calls `read`, asserting the two halves of the pair match.
+fn run_pair_workload(
+    threads: usize,
+    read: impl Fn() -> (u64, u64) + Send + Sync + 'static,
+    write: impl Fn(u64) + Send + Sync + 'static,
+) {
+    spawn_and_join(threads, move |i| {
+        for op in 0..SEQLOCK_OPS {
+            if i == 0 && op.is_multiple_of(SEQLOCK_WRITE_EVERY) {
+                write(op);
+            } else {
+                let (first, second) = black_box(read());
+                assert_eq!(first, second, "torn read of the pair");
+            }
+        }
+    });
+}
+
+/// Benchmark for read-mostly small data using a SeqLock.
+/// This function measures threads performing 1,000 operations each on a pair
+/// of u64s, where one thread writes both halves once every 100 operations and
+/// every other operation reads the pair, under the library's `SeqLock`, std's
+/// RwLock, and, with the `arc-swap` feature, an ArcSwap. Seqlock readers
+/// never write to shared state, so unlike RwLock readers they do not contend
+/// on the lock's cache line. Every read asserts that both halves match.
+fn seqlock_read_heavy(c: &mut Criterion) {
+    let mut group = c.benchmark_group("seqlock_read_heavy");
+    for &threads in thread_counts() {
+        group.throughput(Throughput::Elements(threads as u64 * SEQLOCK_OPS));
+        let seqlock = Arc::new(SeqLock::new((0u64, 0u64)));
+        group.bench_with_input(
+            BenchmarkId::new("seqlock", threads),
+            &threads,
+            |b, &threads| {
+                b.iter(|| {
+                    let (reader, writer) = (Arc::clone(&seqlock), Arc::clone(&seqlock));
+                    run_pair_workload(
+                        threads,
+                        move || reader.read(),
+                        move |value| writer.write((value, value)),
+                    );
+                });
+            },
+        );
+        let rwlock = Arc::new(RwLock::new((0u64, 0u64)));
+        group.bench_with_input(
+            BenchmarkId::new("rwlock", threads),
+            &threads,
+            |b, &threads| {
+                b.iter(|| {
+                    let (reader, writer) = (Arc::clone(&rwlock), Arc::clone(&rwlock));
+                    run_pair_workload(
+                        threads,
+                        move || *reader.read().unwrap(),
+                        move |value| *writer.write().unwrap() = (value, value),
+                    );
+                });
+            },
+        );
+        #[cfg(feature = "arc-swap")]
+        {
+            let swap = Arc::new(arc_swap::ArcSwap::from_pointee((0u64, 0u64)));
+            group.bench_with_input(
+                BenchmarkId::new("arcswap", threads),
+                &threads,
+                |b, &threads| {
+                    b.iter(|| {
+                        let (reader, writer) = (Arc::clone(&swap), Arc::clone(&swap));
+                        run_pair_workload(
+                            threads,
+                            move || **reader.load(),
+                            move |value| writer.store(Arc::new((value, value))),
+                        );
+                    });
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+/// Reader threads in the snapshot read benchmarks.
+const SNAPSHOT_READERS: usize = 8;
+
+/// Writer threads in the snapshot read benchmarks.
+const SNAPSHOT_WRITERS: usize = 2;
+
+/// Operations each snapshot reader and writer performs per iteration.
+const SNAPSHOT_OPS: u64 = 100;
+
+/// Elements in the Vec the snapshot writers replace and the readers iterate.
+const SNAPSHOT_LEN: usize = 1024;
+
+/// Sums a snapshot, asserting that it is one complete Vec from one writer.
+fn checked_snapshot_sum(snapshot: &[u64]) -> u64 {
+    let sum = snapshot.iter().sum();
+    assert_eq!(sum, snapshot[0] * SNAPSHOT_LEN as u64, "mixed snapshot");
+    black_box(sum)
+}
+
+/// Runs `SNAPSHOT_OPS` operations on each of `SNAPSHOT_READERS` reader and
+/// `SNAPSHOT_WRITERS` writer threads. Readers call `read`, which returns how
+/// long it held the lock, and writers call `write` with a value to fill a new
+/// Vec with. Returns the total time readers held the lock.
+fn run_snapshot_workload(
+    read: impl Fn() -> Duration + Send + Sync + 'static,
+    write: impl Fn(u64) + Send + Sync + 'static,
+) -> Duration {
+    let held = spawn_and_join(SNAPSHOT_READERS + SNAPSHOT_WRITERS, move |i| {
+        let mut held = Duration::ZERO;
+        for op in 0..SNAPSHOT_OPS {
+            if i < SNAPSHOT_READERS {
+                held += read();
+            } else {
+                write(i as u64 * SNAPSHOT_OPS + op);
+            }
+        }
+        held
+    });
+    held.into_iter().sum()
+}
+
+/// Registers `snapshot_read/{name}`, running `run_snapshot_workload` with
+/// `read` and `write` on every iteration. Once measured, the average time a
+/// reader held the lock per read is printed.
+fn bench_snapshot_read<R, W>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    name: &str,
+    read: R,
+    write: W,
+) where
+    R: Fn() -> Duration + Clone + Send + Sync + 'static,
+    W: Fn(u64) + Clone + Send + Sync + 'static,
+{
+    let mut held = Duration::ZERO;
+    let mut reads = 0u64;
+    group.bench_function(name, |b| {
+        b.iter(|| {
+            held += run_snapshot_workload(read.clone(), write.clone());
+            reads += SNAPSHOT_READERS as u64 * SNAPSHOT_OPS;
+        });
+    });
+    if reads > 0 {
+        println!(
+            "snapshot_read/{}: readers held the lock {:.0}ns per read on average",
+            name,
+            held.as_nanos() as f64 / reads as f64
+        );
+    }
+}
+
+/// Benchmark for snapshot reads, comparing a Mutex<Arc<Vec>> that readers
+/// clone and release with an RwLock<Vec> that readers hold while iterating.
+/// This function measures 8 readers and 2 writers performing 100 operations
+/// each on a 1,024-element Vec. Under `mutex_arc_clone`, readers lock the
+/// Mutex only long enough to clone the Arc and iterate outside the lock,
+/// while under `rwlock_guard` they iterate inside a read guard, so writers
+/// wait out every iteration in progress. Writers build the replacement Vec
+/// outside the lock in both variants, and readers assert that every snapshot
+/// is one complete Vec. The printed lock hold time per read shows how much
+/// the shorter critical section buys.
+fn snapshot_read(c: &mut Criterion) {
+    let mut group = c.benchmark_group("snapshot_read");
+    group.throughput(Throughput::Elements(
+        (SNAPSHOT_READERS + SNAPSHOT_WRITERS) as u64 * SNAPSHOT_OPS,
+    ));
+    let mutex = Arc::new(Mutex::new(Arc::new(vec![0u64; SNAPSHOT_LEN])));
+    let (reader, writer) = (Arc::clone(&mutex), mutex);
+    bench_snapshot_read(
+        &mut group,
+        "mutex_arc_clone",
+        move || {
+            let guard = reader.lock().unwrap();
+            let acquired = Instant::now();
+            let snapshot = Arc::clone(&guard);
+            drop(guard);
+            let held = acquired.elapsed();
+            checked_snapshot_sum(&snapshot);
+            held
+        },
+        move |value| {
+            let next = Arc::new(vec![value; SNAPSHOT_LEN]);
+            // The previous Vec is freed after the lock is released.
+            let _previous = std::mem::replace(&mut *writer.lock().unwrap(), next);
+        },
+    );
+    let rwlock = Arc::new(RwLock::new(vec![0u64; SNAPSHOT_LEN]));
+    let (reader, writer) = (Arc::clone(&rwlock), rwlock);
+    bench_snapshot_read(
+        &mut group,
+        "rwlock_guard",
+        move || {
+            let guard = reader.read().unwrap();
+            let acquired = Instant::now();
+            checked_snapshot_sum(&guard);
+            drop(guard);
+            acquired.elapsed()
+        },
+        move |value| {
+            let next = vec![value; SNAPSHOT_LEN];
+            let _previous = std::mem::replace(&mut *writer.write().unwrap(), next);
+        },
+    );
+    group.finish();
+}
+
+/// Benchmark for write-heavy workloads using Arc<SpinLock>.
+/// This function measures the performance of multiple threads
+/// concurrently writing to an Arc-wrapped spin-lock-protected integer,
+/// with a delay added for every even iteration while the lock is held.
+/// Waiters spin the whole time, so this shows why naive spinning is a bad idea.
+fn spin_lock_write_heavy(c: &mut Criterion) {
+    let data = Arc::new(SpinLock::new(0u64));
+    bench_lock_with_stalls(c, "spin_lock_write_heavy", |b, threads, ops, stall| {
+        b.iter(|| {
+            let before = *data.lock();
+            let data_clone = Arc::clone(&data);
+            spawn_and_join(threads, move |i| {
+                for op in 0..ops {
+                    let stall = stall.only_on_first(op);
+                    let mut num = data_clone.lock();
+                    *num += 1;
+                    stall.while_locked(i);
+                    drop(num);
+                    stall.after_unlock(i);
+                }
+            });
+            assert_counter_delta(before, *data.lock(), threads as u64 * ops);
+        });
+    });
+}
+
+/// Benchmark for write-heavy workloads using Arc<SpinLock> with a spin-then-yield fallback.
+/// This function measures the performance of multiple threads
+/// concurrently writing to an Arc-wrapped spin-lock-protected integer,
+/// where waiters spin about 100 times before yielding their core,
+/// with a delay added for every even iteration while the lock is held.
+fn spin_yield_lock_write_heavy(c: &mut Criterion) {
+    let data = Arc::new(SpinLock::new(0u64));
+    bench_lock_with_stalls(
+        c,
+        "spin_yield_lock_write_heavy",
+        |b, threads, ops, stall| {
+            b.iter(|| {
+                let before = *data.lock();
+                let data_clone = Arc::clone(&data);
+                spawn_and_join(threads, move |i| {
+                    for op in 0..ops {
+                        let stall = stall.only_on_first(op);
+                        let mut num = data_clone.lock_spin_then_yield();
+                        *num += 1;
+                        stall.while_locked(i);
+                        drop(num);
+                        stall.after_unlock(i);
+                    }
+                });
+                assert_counter_delta(before, *data.lock(), threads as u64 * ops);
+            });
+        },
+    );
+}
+
+// The spin locks implement `SharedCounter` so they can run through the
+// library's scenario runner, which measures every thread's CPU time.
+
+impl counter::SharedCounter for SpinLock<u64> {
+    fn increment(&self) {
+        *self.lock() += 1;
+    }
+
+    fn get(&self) -> u64 {
+        *self.lock()
+    }
+
+    fn increment_holding(&self, hold: impl FnOnce()) {
+        let mut guard = self.lock();
+        *guard += 1;
+        hold();
+    }
+
+    fn get_holding(&self, hold: impl FnOnce()) -> u64 {
+        let guard = self.lock();
+        let value = *guard;
+        hold();
+        value
+    }
+}
+
+/// A `SpinLock` acquired through `lock_spin_then_yield`.
+struct SpinYieldLock(SpinLock<u64>);
+
+impl counter::SharedCounter for SpinYieldLock {
+    fn increment(&self) {
+        *self.0.lock_spin_then_yield() += 1;
+    }
+
+    fn get(&self) -> u64 {
+        *self.0.lock_spin_then_yield()
+    }
+
+    fn increment_holding(&self, hold: impl FnOnce()) {
+        let mut guard = self.0.lock_spin_then_yield();
+        *guard += 1;
+        hold();
+    }
+
+    fn get_holding(&self, hold: impl FnOnce()) -> u64 {
+        let guard = self.0.lock_spin_then_yield();
+        let value = *guard;
+        hold();
+        value
+    }
+}
+
+/// Increments each thread performs per iteration in the spin-vs-park benchmarks.
+const SPIN_VS_PARK_OPS: u64 = 1_000;
+
+/// How long even-indexed threads hold the lock on their first increment in the
+/// spin-vs-park benchmarks, long enough that waiters either spin or park.
+const SPIN_VS_PARK_STALL: Duration = Duration::from_millis(1);
+
+/// Registers `name/{threads}` in `group` for every thread count: every thread
+/// performs `SPIN_VS_PARK_OPS` increments on `state`, with the
+/// `SPIN_VS_PARK_STALL` stall inside the critical section. Once a thread count
+/// is measured, the average efficiency, CPU time divided by wall time, is
+/// printed if CPU time was measured.
+fn bench_spin_vs_park<S: scenario::SharedState>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    name: &str,
+    state: &S,
+) {
+    for &threads in thread_counts() {
+        group.throughput(Throughput::Elements(threads as u64 * SPIN_VS_PARK_OPS));
+        let scenario = Scenario::new(threads, SPIN_VS_PARK_OPS, 100).with_stall(
+            StallPolicy::FixedEvenIterations(SPIN_VS_PARK_STALL),
+            StallPlacement::InCriticalSection,
+        );
+        let expected_writes = scenario.expected_writes();
+        let mut efficiency = Some(0.0);
+        let mut iterations = 0u32;
+        group.bench_with_input(BenchmarkId::new(name, threads), &scenario, |b, scenario| {
+            b.iter(|| {
+                let result = run_contention_scenario(scenario, state);
+                assert_counter_delta(result.initial_value, result.final_value, expected_writes);
+                efficiency = efficiency
+                    .zip(result.efficiency())
+                    .map(|(total, run)| total + run);
+                iterations += 1;
+            });
+        });
+        if let Some(efficiency) = efficiency.filter(|_| iterations > 0) {
+            println!(
+                "spin_vs_park/{}/{}: {:.2} cores busy on average (CPU time / wall time)",
+                name,
+                threads,
+                efficiency / iterations as f64
+            );
+        }
+    }
+}
+
+/// Benchmark for spinning waiters versus parking ones.
+/// This function runs the write-heavy workload through the scenario runner
+/// against the spin lock, the spin-then-yield lock, std's Mutex, and
+/// parking_lot's Mutex, with a 1ms stall held inside the critical section on
+/// every even thread's first increment. The wall times can be close, but with
+/// the `cpu-time` feature the printed efficiency shows the spin lock keeping
+/// a core busy for every waiting thread while the parking locks leave them idle.
+fn spin_vs_park(c: &mut Criterion) {
+    let mut group = c.benchmark_group("spin_vs_park");
+    bench_spin_vs_park(&mut group, "spin_lock", &Arc::new(SpinLock::new(0u64)));
+    bench_spin_vs_park(
+        &mut group,
+        "spin_yield_lock",
+        &Arc::new(SpinYieldLock(SpinLock::new(0))),
+    );
+    bench_spin_vs_park(&mut group, "std_mutex", &Arc::new(Mutex::new(0u64)));
+    bench_spin_vs_park(
+        &mut group,
+        "parking_lot_mutex",
+        &Arc::new(parking_lot::Mutex::new(0u64)),
+    );
+    group.finish();
+}
+
+/// Thread counts the queue lock benchmarks compare at, fixed rather than
+/// scaled to the machine so the contention levels match across hosts.
+const QUEUE_LOCK_THREADS: [usize; 3] = [4, 8, 16];
+
+/// Increments each thread performs per iteration in the queue lock benchmarks.
+const QUEUE_LOCK_OPS: u64 = 1_000;
+
+/// Increments per thread for a fair lock with more threads than cores, where
+/// every handover can wait a scheduler time slice for its thread to run.
+const OVERSUBSCRIBED_QUEUE_LOCK_OPS: u64 = 10;
+
+/// Samples criterion takes of a fair lock with more threads than cores, its
+/// minimum, so a point that runs at scheduler speed still finishes.
+const OVERSUBSCRIBED_QUEUE_LOCK_SAMPLES: usize = 10;
+
+/// Registers `name/{threads}` in `group` for every `QUEUE_LOCK_THREADS`
+/// count: every thread performs `QUEUE_LOCK_OPS` increments on `state` with
+/// no stall, so the critical section is a single add. A `fifo` lock hands
+/// itself to one particular waiter, and with more threads than cores that
+/// waiter is often not running, so every handover waits for the scheduler to
+/// get around to it. Those points still run, so the collapse shows up in the
+/// chart, but with `OVERSUBSCRIBED_QUEUE_LOCK_OPS` increments per thread and
+/// `OVERSUBSCRIBED_QUEUE_LOCK_SAMPLES` samples, and a note says so.
+fn bench_queue_lock<S: scenario::SharedState>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    name: &str,
+    state: &S,
+    fifo: bool,
+) {
+    for threads in QUEUE_LOCK_THREADS {
+        let oversubscribed = fifo && threads > available_threads();
+        let ops = if oversubscribed {
+            println!(
+                "queue_locks/{}/{}: more threads than the {} available, so every handover \
+                 can wait for the scheduler; running {} increments per thread and {} samples",
+                name,
+                threads,
+                available_threads(),
+                OVERSUBSCRIBED_QUEUE_LOCK_OPS,
+                OVERSUBSCRIBED_QUEUE_LOCK_SAMPLES
+            );
+            group.sample_size(OVERSUBSCRIBED_QUEUE_LOCK_SAMPLES);
+            OVERSUBSCRIBED_QUEUE_LOCK_OPS
+        } else {
+            QUEUE_LOCK_OPS
+        };
+        group.throughput(Throughput::Elements(threads as u64 * ops));
+        let scenario = Scenario::new(threads, ops, 100);
+        let expected_writes = scenario.expected_writes();
+        group.bench_with_input(BenchmarkId::new(name, threads), &scenario, |b, scenario| {
+            b.iter(|| {
+                let result = run_contention_scenario(scenario, state);
+                assert_counter_delta(result.initial_value, result.final_value, expected_writes);
+            });
+        });
+        if oversubscribed {
+            // Back to criterion's default for the next point.
+            group.sample_size(100);
+        }
+    }
+}
+
+/// Benchmark for queue-based spin locks against std's Mutex and the naive spin lock.
+/// This function measures 4, 8, and 16 threads each performing 1,000
+/// increments under std's Mutex, the test-and-set `SpinLock`, and the
+/// library's `TicketLock` and `McsLock`. The ticket and MCS locks hand the
+/// lock over in arrival order, so no thread starves as contention grows, and
+/// the MCS lock also spins on a per-thread node instead of one shared line;
+/// their single-threaded cost is in the `uncontended` group. At thread counts
+/// above the machine's cores they run shorter (see `bench_queue_lock`).
+fn queue_locks(c: &mut Criterion) {
+    let mut group = c.benchmark_group("queue_locks");
+    bench_queue_lock(&mut group, "std_mutex", &Arc::new(Mutex::new(0u64)), false);
+    bench_queue_lock(
+        &mut group,
+        "spin_lock",
+        &Arc::new(SpinLock::new(0u64)),
+        false,
+    );
+    bench_queue_lock(
+        &mut group,
+        "ticket_lock",
+        &Arc::new(TicketLock::new(0u64)),
+        true,
+    );
+    bench_queue_lock(&mut group, "mcs_lock", &Arc::new(McsLock::new(0u64)), true);
+    group.finish();
+}
+
+/// Benchmark for read-heavy workloads using Arc<AtomicU64>.
+/// This function measures the performance of multiple threads
+/// concurrently loading an Arc-wrapped atomic integer as a lock-free baseline,
+/// with a delay added for every even iteration.
+fn atomic_load_read_heavy(c: &mut Criterion) {
+    let data = Arc::new(AtomicU64::new(READ_VALUE));
+    bench_ops_with_stalls(c, "atomic_load_read_heavy", |b, threads, ops, stall| {
+        let scenario = Scenario::new(threads, ops, 0)
+            .with_stall(stall, StallPlacement::OutsideCriticalSection);
+        b.iter(|| run_checked_scenario(&scenario, &data));
+    });
+}
+
+/// Benchmark for write-heavy workloads using Arc<AtomicUsize>.
+/// This function measures the performance of multiple threads
+/// concurrently incrementing an Arc-wrapped atomic integer with fetch_add
+/// as a lock-free baseline, with a delay added for every even iteration.
+/// The counter is checked after every iteration so lost updates fail the bench.
+fn atomic_fetch_add_write_heavy(c: &mut Criterion) {
+    let data = Arc::new(AtomicU64::new(0));
+    bench_ops_with_stalls(
+        c,
+        "atomic_fetch_add_write_heavy",
+        |b, threads, ops, stall| {
+            let scenario = Scenario::new(threads, ops, 100)
+                .with_stall(stall, StallPlacement::OutsideCriticalSection);
+            b.iter(|| run_checked_scenario(&scenario, &data));
+        },
+    );
+}
+
+/// Benchmark for write-heavy workloads using a compare_exchange_weak loop.
+/// This function measures the performance of multiple threads
+/// incrementing an Arc-wrapped atomic integer by retrying compare_exchange_weak
+/// until it succeeds, with a delay added for every even iteration.
+/// The total number of failed exchanges is printed after the run.
+fn atomic_cas_loop_write_heavy(c: &mut Criterion) {
+    let data = Arc::new(AtomicUsize::new(0));
+    let retries = Arc::new(AtomicU64::new(0));
+    bench_ops_with_stalls(
+        c,
+        "atomic_cas_loop_write_heavy",
+        |b, threads, ops, stall| {
+            b.iter(|| {
+                let before = data.load(Ordering::Relaxed);
+                let data_clone = Arc::clone(&data);
+                let retries_clone = Arc::clone(&retries);
+                spawn_and_join(threads, move |i| {
+                    for op in 0..ops {
+                        let mut current = data_clone.load(Ordering::Relaxed);
+                        while let Err(actual) = data_clone.compare_exchange_weak(
+                            current,
+                            current + 1,
+                            Ordering::Relaxed,
+                            Ordering::Relaxed,
+                        ) {
+                            current = actual;
+                            retries_clone.fetch_add(1, Ordering::Relaxed);
+                        }
+                        stall.only_on_first(op).apply(i);
+                    }
+                });
+                assert_eq!(
+                    data.load(Ordering::Relaxed),
+                    before + threads * ops as usize
+                );
+            });
+        },
+    );
+    let increments = data.load(Ordering::Relaxed);
+    if increments > 0 {
+        println!(
+            "atomic_cas_loop_write_heavy: {} CAS retries across {} increments",
+            retries.load(Ordering::Relaxed),
+            increments
+        );
+    }
+}
+
+/// Threads in the memory ordering benchmarks.
+const ORDERING_THREADS: usize = 8;
+
+/// Increments each thread performs per iteration in the memory ordering benchmarks.
+const ORDERING_OPS: u64 = 10_000;
+
+/// Benchmark for the cost of memory ordering on an atomic counter.
+/// This function measures 8 threads incrementing a shared AtomicU64 10,000
+/// times each in a tight loop with `fetch_add` under `Relaxed`, `AcqRel`, and
+/// `SeqCst`, and with `Relaxed` followed by a `fence(SeqCst)`. The orderings
+/// cost about the same on x86, where every read-modify-write is already a full
+/// barrier, and diverge on weaker architectures such as ARM, so every id is
+/// parameterized by the target architecture, such as `seq_cst/x86_64`.
+fn atomic_ordering(c: &mut Criterion) {
+    let arch = env::consts::ARCH;
+    println!("atomic_ordering: target architecture {}", arch);
+    let data = Arc::new(AtomicU64::new(0));
+    let mut group = c.benchmark_group("atomic_ordering");
+    group.throughput(Throughput::Elements(ORDERING_THREADS as u64 * ORDERING_OPS));
+    for (name, ordering, fence) in [
+        ("relaxed", Ordering::Relaxed, false),
+        ("acq_rel", Ordering::AcqRel, false),
+        ("seq_cst", Ordering::SeqCst, false),
+        ("relaxed_fence_seq_cst", Ordering::Relaxed, true),
+    ] {
+        group.bench_function(BenchmarkId::new(name, arch), |b| {
+            b.iter(|| {
+                let before = data.load(Ordering::SeqCst);
+                let data_clone = Arc::clone(&data);
+                spawn_and_join(ORDERING_THREADS, move |_| {
+                    for _ in 0..ORDERING_OPS {
+                        data_clone.fetch_add(1, ordering);
+                        if fence {
+                            std::sync::atomic::fence(Ordering::SeqCst);
+                        }
+                    }
+                });
+                assert_counter_delta(
+                    before,
+                    data.load(Ordering::SeqCst),
+                    ORDERING_THREADS as u64 * ORDERING_OPS,
+                );
+            });
+        });
+    }
+    group.finish();
+}
+
+/// A message sent through the `bench_channel` benchmarks, stamped with the
+/// time it was handed to `send`, so its receiver can measure how long it took
+/// to arrive.
+#[derive(Clone, Copy)]
+struct Message {
+    value: u64,
+    sent_at: Instant,
+}
+
+impl Message {
+    /// Stamps `value` with the current time. Callers create the message before
+    /// calling `send`, so the timestamp never includes time spent in a lock.
+    fn new(value: u64) -> Self {
+        Message {
+            value,
+            sent_at: Instant::now(),
+        }
+    }
+}
+
+/// Minimal channel interface shared by the channel benchmarks, so each
+/// channel implementation runs exactly the same sender/receiver body.
+/// `Self` is the sending side of a channel of `T`; both sides are cloned into
+/// every thread.
+trait Channel<T>: Clone + Send + Sync + 'static {
+    type Receiver: ChannelReceiver<T>;
+
+    /// Creates an unbounded channel.
+    fn unbounded() -> (Self, Self::Receiver);
+
+    fn send(&self, message: T);
+}
+
+/// The receiving side of a `Channel`, shareable between receiver threads.
+trait ChannelReceiver<T>: Clone + Send + Sync + 'static {
+    /// Blocks until a message arrives, or returns `None` once every sender has
+    /// been dropped and the channel is empty.
+    fn recv_if_connected(&self) -> Option<T>;
+
+    /// Blocks until a message arrives.
+    fn recv(&self) -> T {
+        self.recv_if_connected().unwrap()
+    }
+}
+
+/// Like `Channel`, for channels created with a fixed capacity, where `send`
+/// blocks while the channel is full. std's bounded channel has its own sender
+/// type, `SyncSender`, which is why this is a trait of its own.
+trait BoundedChannel<T>: Clone + Send + Sync + 'static {
+    type Receiver: ChannelReceiver<T>;
+
+    fn bounded(capacity: usize) -> (Self, Self::Receiver);
+
+    fn send(&self, message: T);
+}
+
+/// Receives a message from `rx`, records how long ago it was sent into
+/// `latencies` in nanoseconds, and returns its value. The clock is read only
+/// after `recv` has released any lock around the receiver.
+fn recv_timed(rx: &impl ChannelReceiver<Message>, latencies: &mut Vec<u64>) -> u64 {
+    let message = rx.recv();
+    latencies.push(message.sent_at.elapsed().as_nanos() as u64);
+    message.value
+}
+
+/// std's receiver cannot be cloned, so it is shared behind a Mutex and every
+/// receiver thread takes turns locking it.
+impl<T: Send + 'static> Channel<T> for mpsc::Sender<T> {
+    type Receiver = Arc<Mutex<mpsc::Receiver<T>>>;
+
+    fn unbounded() -> (Self, Self::Receiver) {
+        let (tx, rx) = mpsc::channel();
+        (tx, Arc::new(Mutex::new(rx)))
+    }
+
+    fn send(&self, message: T) {
+        mpsc::Sender::send(self, message).unwrap();
+    }
+}
+
+impl<T: Send + 'static> ChannelReceiver<T> for Arc<Mutex<mpsc::Receiver<T>>> {
+    fn recv_if_connected(&self) -> Option<T> {
+        self.lock().unwrap().recv().ok()
+    }
+}
+
+impl<T: Send + 'static> BoundedChannel<T> for mpsc::SyncSender<T> {
+    type Receiver = Arc<Mutex<mpsc::Receiver<T>>>;
+
+    fn bounded(capacity: usize) -> (Self, Self::Receiver) {
+        let (tx, rx) = mpsc::sync_channel(capacity);
+        (tx, Arc::new(Mutex::new(rx)))
+    }
+
+    fn send(&self, message: T) {
+        mpsc::SyncSender::send(self, message).unwrap();
+    }
+}
+
+impl<T: Send + 'static> Channel<T> for crossbeam_channel::Sender<T> {
+    type Receiver = crossbeam_channel::Receiver<T>;
+
+    fn unbounded() -> (Self, Self::Receiver) {
+        crossbeam_channel::unbounded()
+    }
+
+    fn send(&self, message: T) {
+        crossbeam_channel::Sender::send(self, message).unwrap();
+    }
+}
+
+impl<T: Send + 'static> BoundedChannel<T> for crossbeam_channel::Sender<T> {
+    type Receiver = crossbeam_channel::Receiver<T>;
+
+    fn bounded(capacity: usize) -> (Self, Self::Receiver) {
+        crossbeam_channel::bounded(capacity)
+    }
+
+    fn send(&self, message: T) {
+        crossbeam_channel::Sender::send(self, message).unwrap();
+    }
+}
+
+impl<T: Send + 'static> ChannelReceiver<T> for crossbeam_channel::Receiver<T> {
+    fn recv_if_connected(&self) -> Option<T> {
+        crossbeam_channel::Receiver::recv(self).ok()
+    }
+}
+
+#[cfg(feature = "flume")]
+impl<T: Send + 'static> Channel<T> for flume::Sender<T> {
+    type Receiver = flume::Receiver<T>;
+
+    fn unbounded() -> (Self, Self::Receiver) {
+        flume::unbounded()
+    }
+
+    fn send(&self, message: T) {
+        flume::Sender::send(self, message).unwrap();
+    }
+}
+
+#[cfg(feature = "flume")]
+impl<T: Send + 'static> BoundedChannel<T> for flume::Sender<T> {
+    type Receiver = flume::Receiver<T>;
+
+    fn bounded(capacity: usize) -> (Self, Self::Receiver) {
+        flume::bounded(capacity)
+    }
+
+    fn send(&self, message: T) {
+        flume::Sender::send(self, message).unwrap();
+    }
+}
+
+#[cfg(feature = "flume")]
+impl<T: Send + 'static> ChannelReceiver<T> for flume::Receiver<T> {
+    fn recv_if_connected(&self) -> Option<T> {
+        flume::Receiver::recv(self).ok()
+    }
+}
+
+/// Messages each thread sends or receives per iteration of the read-heavy and
+/// write-heavy channel benchmarks: nine in the workload's direction and one in
+/// the other.
+const CHANNEL_OPS_PER_THREAD: usize = 10;
+
+/// Messages a read-heavy or write-heavy iteration sends, and must receive, in
+/// total. Every message is a 1, so summing what was received counts it.
+fn channel_messages(threads: usize) -> u64 {
+    (threads * (CHANNEL_OPS_PER_THREAD - 1)) as u64
+}
+
+/// Most send-to-receive latencies `bench_channel` keeps per benchmark, so a
+/// long measurement cannot grow the sample without bound.
+const MAX_LATENCY_SAMPLES: usize = 1_000_000;
+
+/// Returns the `quantile` of `sorted` nanosecond samples as a Duration.
+fn percentile(sorted: &[u64], quantile: f64) -> Duration {
+    let index = ((sorted.len() - 1) as f64 * quantile).round() as usize;
+    Duration::from_nanos(sorted[index])
+}
+
+/// Benchmark for `workload` on an unbounded channel of type `C`.
+/// This function measures the performance of multiple threads sending and
+/// receiving messages, with a delay added for every even iteration:
+/// write-heavy has every thread send nine messages and receive one, with the
+/// main thread draining the rest afterwards; read-heavy has every thread send
+/// one message and receive nine, with the main thread sending the missing
+/// messages up front; and mixed splits the threads between sending and receiving.
+/// Both balance sends and receives so the channel drains fully, and check that
+/// every message sent was received.
+/// Every receiver also records how long each message took from send to
+/// receive, in a Vec of its own so recording never contends, and the p50 and
+/// p99 latencies are printed after each thread count is measured.
+fn bench_channel<C: Channel<Message>>(c: &mut Criterion, name: &str, workload: Workload) {
+    let ops_per_thread = match workload {
+        Workload::Mixed => 1,
+        _ => CHANNEL_OPS_PER_THREAD,
+    };
+    for (name, stall) in stall_variants(name) {
+        bench_over_threads_with_elements(
+            c,
+            &name,
+            |threads| (threads * ops_per_thread) as u64,
+            |b, threads| {
+                let mut latencies = Vec::new();
+                b.iter(|| {
+                    let (tx, rx) = C::unbounded();
+                    let receivers = rx.clone();
+                    let received = match workload {
+                        Workload::ReadHeavy => {
+                            for _ in 0..threads * (CHANNEL_OPS_PER_THREAD - 2) {
+                                tx.send(Message::new(1));
+                            }
+                            let received = spawn_and_join(threads, move |i| {
+                                tx.send(Message::new(1));
+                                stall.apply(i);
+                                let mut latencies = Vec::with_capacity(CHANNEL_OPS_PER_THREAD);
+                                let sum = (1..CHANNEL_OPS_PER_THREAD)
+                                    .map(|_| recv_timed(&receivers, &mut latencies))
+                                    .sum::<u64>();
+                                (sum, latencies)
+                            });
+                            let sum: u64 = received.iter().map(|(sum, _)| sum).sum();
+                            assert_eq!(sum, channel_messages(threads));
+                            received
+                        }
+                        Workload::WriteHeavy => {
+                            let mut received = spawn_and_join(threads, move |i| {
+                                for _ in 1..CHANNEL_OPS_PER_THREAD {
+                                    tx.send(Message::new(1));
+                                }
+                                stall.apply(i);
+                                let mut latencies = Vec::with_capacity(1);
+                                (recv_timed(&receivers, &mut latencies), latencies)
+                            });
+                            let mut drained_latencies = Vec::new();
+                            let drained: u64 = (0..threads * (CHANNEL_OPS_PER_THREAD - 2))
+                                .map(|_| recv_timed(&rx, &mut drained_latencies))
+                                .sum();
+                            received.push((drained, drained_latencies));
+                            let sum: u64 = received.iter().map(|(sum, _)| sum).sum();
+                            assert_eq!(sum, channel_messages(threads));
+                            received
+                        }
+                        Workload::Mixed => spawn_and_join(threads, move |i| {
+                            let mut latencies = Vec::new();
+                            if is_channel_receiver(i, threads) {
+                                black_box(recv_timed(&receivers, &mut latencies));
+                            } else {
+                                tx.send(Message::new(i as u64));
+                            }
+                            stall.apply(i);
+                            (0, latencies)
+                        }),
+                    };
+                    for (_, thread_latencies) in received {
+                        if latencies.len() < MAX_LATENCY_SAMPLES {
+                            latencies.extend(thread_latencies);
+                        }
+                    }
+                });
+                if !latencies.is_empty() {
+                    latencies.sort_unstable();
+                    println!(
+                        "{}/threads/{}: message latency p50 {:?}, p99 {:?} over {} messages",
+                        name,
+                        threads,
+                        percentile(&latencies, 0.5),
+                        percentile(&latencies, 0.99),
+                        latencies.len()
+                    );
+                }
+            },
+        );
+    }
+}
+
+/// Registers the read-heavy, write-heavy, and mixed benchmarks for every
+/// channel benchmarked with `bench_channel`.
+fn channel_benches(c: &mut Criterion) {
+    let matrix = ScenarioMatrix::new(&Workload::ALL)
+        .primitive("mpsc", bench_channel::<mpsc::Sender<Message>>)
+        .primitive(
+            "crossbeam",
+            bench_channel::<crossbeam_channel::Sender<Message>>,
+        );
+    #[cfg(feature = "flume")]
+    let matrix = matrix.primitive("flume", bench_channel::<flume::Sender<Message>>);
+    matrix.register(c);
+}
+
+/// A message type the payload benchmarks send, from a bare integer up to a
+/// heap buffer that a channel moves by pointer.
+trait Payload: Send + 'static {
+    /// The benchmark function id the payload is reported under.
+    const NAME: &'static str;
+
+    fn new() -> Self;
+
+    /// Bytes of data the payload carries.
+    fn bytes(&self) -> usize;
+}
+
+impl Payload for u64 {
+    const NAME: &'static str = "u64";
+
+    fn new() -> Self {
+        1
+    }
+
+    fn bytes(&self) -> usize {
+        std::mem::size_of::<u64>()
+    }
+}
+
+impl Payload for [u8; 64] {
+    const NAME: &'static str = "array_64b";
+
+    fn new() -> Self {
+        [1; 64]
+    }
+
+    fn bytes(&self) -> usize {
+        self.len()
+    }
+}
+
+impl Payload for Vec<u8> {
+    const NAME: &'static str = "vec_4kib";
+
+    fn new() -> Self {
+        vec![1; 4096]
+    }
+
+    fn bytes(&self) -> usize {
+        self.len()
+    }
+}
+
+/// Producer threads in the payload benchmarks, all draining into the main thread.
+const PAYLOAD_PRODUCERS: usize = 4;
+
+/// Messages each producer sends per iteration of the payload benchmarks.
+const MESSAGES_PER_PRODUCER: [usize; 3] = [10, 1_000, 100_000];
+
+/// Capacity of the payload benchmarks' channels. Producers easily outrun the
+/// single consumer, and unbounded channels would queue up to 1.6 GB of 4 KiB
+/// buffers at 100,000 messages per producer; bounded ones keep memory flat
+/// and measure sustained throughput instead.
+const PAYLOAD_CHANNEL_CAPACITY: usize = 1024;
+
+/// Registers one benchmark per entry of `MESSAGES_PER_PRODUCER` in `group`:
+/// `PAYLOAD_PRODUCERS` threads each send that many `T`s over a channel of
+/// type `C`, bounded to `PAYLOAD_CHANNEL_CAPACITY`, while the main thread
+/// receives them all, and the bytes received must add up to the bytes sent.
+fn bench_payload<C: BoundedChannel<T>, T: Payload>(group: &mut BenchmarkGroup<'_, WallTime>) {
+    for messages in MESSAGES_PER_PRODUCER {
+        let total = PAYLOAD_PRODUCERS * messages;
+        group.throughput(Throughput::Bytes((total * T::new().bytes()) as u64));
+        group.bench_with_input(
+            BenchmarkId::new(T::NAME, messages),
+            &messages,
+            |b, &messages| {
+                b.iter(|| {
+                    let (tx, rx) = C::bounded(PAYLOAD_CHANNEL_CAPACITY);
+                    let producers: Vec<_> = (0..PAYLOAD_PRODUCERS)
+                        .map(|_| {
+                            let tx = tx.clone();
+                            thread::spawn(move || {
+                                let mut sent = 0;
+                                for _ in 0..messages {
+                                    let payload = T::new();
+                                    sent += payload.bytes();
+                                    tx.send(payload);
+                                }
+                                sent
+                            })
+                        })
+                        .collect();
+                    let received: usize = (0..total).map(|_| rx.recv().bytes()).sum();
+                    let sent: usize = producers
+                        .into_iter()
+                        .map(|producer| producer.join().unwrap())
+                        .sum();
+                    assert_eq!(received, sent);
+                });
+            },
+        );
+    }
+}
+
+/// Registers the payload benchmarks for one channel as the group
+/// `{name}_payloads`, with one line per payload type over the message count.
+fn bench_channel_payloads<A, B, V>(c: &mut Criterion, name: &str)
+where
+    A: BoundedChannel<u64>,
+    B: BoundedChannel<[u8; 64]>,
+    V: BoundedChannel<Vec<u8>>,
+{
+    let mut group = c.benchmark_group(format!("{}_payloads", name));
+    bench_payload::<A, _>(&mut group);
+    bench_payload::<B, _>(&mut group);
+    bench_payload::<V, _>(&mut group);
+    group.finish();
+}
+
+/// Benchmarks for channel throughput by message count and payload size.
+/// These functions measure 4 producer threads each sending 10, 1,000, or
+/// 100,000 messages to the main thread over each bounded channel, with
+/// `u64`, `[u8; 64]`, and 4 KiB `Vec<u8>` payloads, and report bytes per second.
+/// The Vec payload is moved by pointer, so it separates channels that move
+/// messages from any that copy them. The sweep is not part of
+/// `channel_benches`, whose workloads have every thread both send and receive
+/// on an unbounded channel; the README explains the split.
+fn channel_payloads(c: &mut Criterion) {
+    bench_channel_payloads::<mpsc::SyncSender<_>, mpsc::SyncSender<_>, mpsc::SyncSender<_>>(
+        c, "mpsc",
+    );
+    bench_channel_payloads::<
+        crossbeam_channel::Sender<_>,
+        crossbeam_channel::Sender<_>,
+        crossbeam_channel::Sender<_>,
+    >(c, "crossbeam");
+    #[cfg(feature = "flume")]
+    bench_channel_payloads::<flume::Sender<_>, flume::Sender<_>, flume::Sender<_>>(c, "flume");
+}
+
+/// Benchmark for handing messages out to worker threads over a channel.
+/// This function measures the main thread sending nine messages per worker to
+/// `threads` workers that each receive nine, three ways:
+/// `mpsc_shared_receiver_antipattern` shares the mpsc Receiver behind an
+/// Arc<Mutex>, which serializes the workers on the Mutex like `bench_channel`
+/// does; `mpsc_dedicated_consumer` has one consumer thread own the Receiver and
+/// forward messages round-robin to a channel per worker, the idiomatic std
+/// pattern; and `crossbeam` clones its MPMC Receiver into every worker.
+/// Every worker must receive exactly its nine messages.
+fn channel_fan_out(c: &mut Criterion) {
+    const MESSAGES_PER_WORKER: u64 = (CHANNEL_OPS_PER_THREAD - 1) as u64;
+    let mut group = c.benchmark_group("channel_fan_out");
+    for &threads in thread_counts() {
+        let messages = threads as u64 * MESSAGES_PER_WORKER;
+        group.throughput(Throughput::Elements(messages));
+        group.bench_with_input(
+            BenchmarkId::new("mpsc_shared_receiver_antipattern", threads),
+            &threads,
+            |b, &threads| {
+                b.iter(|| {
+                    let (tx, rx) = mpsc::channel();
+                    let rx = Arc::new(Mutex::new(rx));
+                    let workers = thread::spawn(move || {
+                        spawn_and_join(threads, move |_| {
+                            (0..MESSAGES_PER_WORKER)
+                                .map(|_| rx.lock().unwrap().recv().unwrap())
+                                .sum::<u64>()
+                        })
+                    });
+                    for _ in 0..messages {
+                        tx.send(1u64).unwrap();
+                    }
+                    for received in workers.join().unwrap() {
+                        assert_eq!(received, MESSAGES_PER_WORKER);
+                    }
+                });
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("mpsc_dedicated_consumer", threads),
+            &threads,
+            |b, &threads| {
+                b.iter(|| {
+                    let (tx, rx) = mpsc::channel::<u64>();
+                    let (worker_txs, worker_rxs): (Vec<_>, Vec<_>) =
+                        (0..threads).map(|_| mpsc::channel::<u64>()).unzip();
+                    let consumer = thread::spawn(move || {
+                        for (value, worker) in rx.iter().zip(worker_txs.iter().cycle()) {
+                            worker.send(value).unwrap();
+                        }
+                    });
+                    let workers: Vec<_> = worker_rxs
+                        .into_iter()
+                        .map(|worker_rx| {
+                            thread::spawn(move || {
+                                (0..MESSAGES_PER_WORKER)
+                                    .map(|_| worker_rx.recv().unwrap())
+                                    .sum::<u64>()
+                            })
+                        })
+                        .collect();
+                    for _ in 0..messages {
+                        tx.send(1).unwrap();
+                    }
+                    drop(tx);
+                    for worker in workers {
+                        assert_eq!(worker.join().unwrap(), MESSAGES_PER_WORKER);
+                    }
+                    consumer.join().unwrap();
+                });
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("crossbeam", threads),
+            &threads,
+            |b, &threads| {
+                b.iter(|| {
+                    let (tx, rx) = crossbeam_channel::unbounded();
+                    let workers = thread::spawn(move || {
+                        spawn_and_join(threads, move |_| {
+                            (0..MESSAGES_PER_WORKER)
+                                .map(|_| rx.recv().unwrap())
+                                .sum::<u64>()
+                        })
+                    });
+                    for _ in 0..messages {
+                        tx.send(1u64).unwrap();
+                    }
+                    for received in workers.join().unwrap() {
+                        assert_eq!(received, MESSAGES_PER_WORKER);
+                    }
+                });
+            },
+        );
+    }
+    group.finish();
+}
+
+/// Benchmark for producer/consumer workloads using bounded mpsc sync channels.
+/// This function measures the performance of multiple threads
+/// sending and receiving messages through `mpsc::sync_channel(n)` for several bounds,
+/// with a delay added for every even iteration.
+/// For each thread count, that many messages are sent, each by a producer
+/// thread of its own to a consumer thread of its own. Producer and consumer
+/// threads are spawned interleaved, because with a small bound the producers
+/// would block forever if no consumer were running yet.
+fn mpsc_sync_channel(c: &mut Criterion) {
+    for (name, stall) in stall_variants("mpsc_sync_channel") {
+        let mut group = c.benchmark_group(name);
+        for bound in [1, 4, 16] {
+            for &threads in thread_counts() {
+                group.throughput(Throughput::Elements(threads as u64));
+                let id = BenchmarkId::new(format!("bound_{}", bound), threads);
+                group.bench_with_input(id, &threads, |b, &threads| {
+                    b.iter(|| {
+                        let (tx, rx) = mpsc::sync_channel(bound);
+                        let rx = Arc::new(Mutex::new(rx));
+                        let mut handles = vec![];
+                        for i in 0..threads {
+                            let tx_clone = tx.clone();
+                            let handle = thread::spawn(move || {
+                                tx_clone.send(i).unwrap();
+                                stall.apply(i);
+                            });
+                            handles.push(handle);
+                            let rx_clone = Arc::clone(&rx);
+                            let handle = thread::spawn(move || {
+                                let _unused = rx_clone.lock().unwrap().recv().unwrap();
+                                stall.apply(i);
+                            });
+                            handles.push(handle);
+                        }
+                        for handle in handles {
+                            handle.join().unwrap();
+                        }
+                    });
+                });
+            }
+        }
+        group.finish();
+    }
+}
+
+/// Benchmark for mixed read/write workloads using crossbeam bounded channels.
+/// This function measures the performance of multiple threads
+/// performing both sending and receiving operations through a crossbeam
+/// bounded channel for several capacities, with a delay added for every even iteration.
+/// For each thread count, that many messages are sent, each by a sender thread
+/// of its own to a receiver thread of its own. Receiver threads are spawned
+/// before the senders so that the rendezvous channel (capacity 0) always has a
+/// receiver ready and never deadlocks.
+fn crossbeam_bounded(c: &mut Criterion) {
+    for (name, stall) in stall_variants("crossbeam_bounded") {
+        let mut group = c.benchmark_group(name);
+        for cap in [0, 1, 8, 64] {
+            for &threads in thread_counts() {
+                group.throughput(Throughput::Elements(threads as u64));
+                let id = BenchmarkId::new(format!("capacity_{}", cap), threads);
+                group.bench_with_input(id, &threads, |b, &threads| {
+                    b.iter(|| {
+                        let (tx, rx) = crossbeam_channel::bounded(cap);
+                        let mut handles = vec![];
+                        for i in 0..threads {
+                            let rx_clone = rx.clone();
+                            let handle = thread::spawn(move || {
+                                let _unused = rx_clone.recv().unwrap();
+                                stall.apply(i);
+                            });
+                            handles.push(handle);
+                        }
+                        for i in 0..threads {
+                            let tx_clone = tx.clone();
+                            let handle = thread::spawn(move || {
+                                tx_clone.send(i).unwrap();
+                                stall.apply(i);
+                            });
+                            handles.push(handle);
+                        }
+                        for handle in handles {
+                            handle.join().unwrap();
+                        }
+                    });
+                });
+            }
+        }
+        group.finish();
+    }
+}
+
+/// Producer threads in the backpressure benchmarks.
+const BACKPRESSURE_PRODUCERS: usize = 4;
+
+/// Messages each backpressure producer sends per iteration.
+const BACKPRESSURE_MESSAGES: usize = 1_000;
+
+/// Consumer threads in the backpressure benchmarks, which split the messages evenly.
+const BACKPRESSURE_CONSUMERS: usize = 2;
+
+/// How long a backpressure consumer works on every message it receives.
+const BACKPRESSURE_CONSUMER_DELAY: Duration = Duration::from_micros(100);
+
+/// Registers `name` in `group`: `BACKPRESSURE_PRODUCERS` threads send
+/// `BACKPRESSURE_MESSAGES` each, as fast as they can, into a channel of type
+/// `C` with capacity 8, while `BACKPRESSURE_CONSUMERS` threads sleep
+/// `BACKPRESSURE_CONSUMER_DELAY` per message, so the channel stays full and
+/// sends block. Every send is timed, and after the run each producer's longest
+/// blocked send and its average blocked time per iteration are printed.
+fn bench_backpressure<C: BoundedChannel<u64>>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    name: &str,
+) {
+    let mut longest = [Duration::ZERO; BACKPRESSURE_PRODUCERS];
+    let mut blocked = [Duration::ZERO; BACKPRESSURE_PRODUCERS];
+    let mut iterations = 0u32;
+    group.bench_function(name, |b| {
+        b.iter(|| {
+            let (tx, rx) = C::bounded(8);
+            let consumers: Vec<_> = (0..BACKPRESSURE_CONSUMERS)
+                .map(|_| {
+                    let rx = rx.clone();
+                    thread::spawn(move || {
+                        for _ in 0..BACKPRESSURE_PRODUCERS * BACKPRESSURE_MESSAGES
+                            / BACKPRESSURE_CONSUMERS
+                        {
+                            black_box(rx.recv());
+                            thread::sleep(BACKPRESSURE_CONSUMER_DELAY);
+                        }
+                    })
+                })
+                .collect();
+            let producers: Vec<_> = (0..BACKPRESSURE_PRODUCERS)
+                .map(|i| {
+                    let tx = tx.clone();
+                    thread::spawn(move || {
+                        let mut longest = Duration::ZERO;
+                        let mut blocked = Duration::ZERO;
+                        for _ in 0..BACKPRESSURE_MESSAGES {
+                            let start = Instant::now();
+                            tx.send(i as u64);
+                            let waited = start.elapsed();
+                            longest = longest.max(waited);
+                            blocked += waited;
+                        }
+                        (longest, blocked)
+                    })
+                })
+                .collect();
+            for (i, producer) in producers.into_iter().enumerate() {
+                let (producer_longest, producer_blocked) = producer.join().unwrap();
+                longest[i] = longest[i].max(producer_longest);
+                blocked[i] += producer_blocked;
+            }
+            for consumer in consumers {
+                consumer.join().unwrap();
+            }
+            iterations += 1;
+        });
+    });
+    if iterations > 0 {
+        for i in 0..BACKPRESSURE_PRODUCERS {
+            println!(
+                "bounded_backpressure/{}: producer {} blocked up to {:?} in one send, {:?} per iteration on average",
+                name,
+                i,
+                longest[i],
+                blocked[i] / iterations
+            );
+        }
+    }
+}
+
+/// Benchmark for producers outrunning slow consumers on bounded channels.
+/// This function measures 4 producers sending 1,000 messages each into
+/// `mpsc::sync_channel(8)` and `crossbeam_channel::bounded(8)` while 2
+/// consumers sleep 100µs per message, which exercises the path where a send
+/// blocks on a full channel. Each iteration takes at least 200ms, so the group
+/// takes criterion's minimum of 10 samples.
+fn bounded_backpressure(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bounded_backpressure");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(
+        (BACKPRESSURE_PRODUCERS * BACKPRESSURE_MESSAGES) as u64,
+    ));
+    bench_backpressure::<mpsc::SyncSender<u64>>(&mut group, "mpsc_sync_channel");
+    bench_backpressure::<crossbeam_channel::Sender<u64>>(&mut group, "crossbeam_bounded");
+    group.finish();
+}
+
+/// How a channel producer spaces out its sends.
+#[derive(Clone, Copy, Debug)]
+enum ArrivalPattern {
+    /// Sends back to back, as fast as the channel takes them.
+    Steady,
+    /// Sends `size` messages back to back, then idles for `gap`, and repeats.
+    Burst { size: usize, gap: Duration },
+}
+
+impl ArrivalPattern {
+    /// The name benchmark ids use for the pattern.
+    fn name(self) -> &'static str {
+        match self {
+            ArrivalPattern::Steady => "steady",
+            ArrivalPattern::Burst { .. } => "burst",
+        }
+    }
+
+    /// Calls `send` `messages` times, spaced out by the pattern. No gap
+    /// follows the last burst, so a producer finishes as soon as it has sent.
+    fn produce(self, messages: usize, mut send: impl FnMut()) {
+        for sent in 1..=messages {
+            send();
+            if let ArrivalPattern::Burst { size, gap } = self {
+                if sent.is_multiple_of(size) && sent < messages {
+                    thread::sleep(gap);
+                }
+            }
+        }
+    }
+}
+
+/// Producer threads in the burst benchmarks.
+const BURST_PRODUCERS: usize = 4;
+
+/// Messages each burst producer sends per iteration, in bursts of `BURST_SIZE`.
+const BURST_MESSAGES: usize = 1_024;
+
+/// Messages in one burst.
+const BURST_SIZE: usize = 256;
+
+/// How long a burst producer idles between bursts.
+const BURST_GAP: Duration = Duration::from_millis(5);
+
+/// Capacity of the channels in the burst benchmarks, a quarter of one burst,
+/// so every burst fills the channel and queues up behind it.
+const BURST_CAPACITY: usize = 64;
+
+/// Registers `name/{pattern}` in `group`: `BURST_PRODUCERS` threads send
+/// `BURST_MESSAGES` each into a channel of type `C` with `BURST_CAPACITY`
+/// slots, spaced out by `pattern`, while one consumer receives them all. Every
+/// message is stamped before its send, so its latency covers the time its
+/// producer spent blocked on a full channel as well as the time it sat in
+/// the queue, and the p50 and p99 are printed once the benchmark is measured.
+fn bench_arrivals<C: BoundedChannel<Message>>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    name: &str,
+    pattern: ArrivalPattern,
+) {
+    let total = BURST_PRODUCERS * BURST_MESSAGES;
+    let mut latencies = Vec::new();
+    group.bench_function(BenchmarkId::new(name, pattern.name()), |b| {
+        b.iter(|| {
+            let (tx, rx) = C::bounded(BURST_CAPACITY);
+            let consumer = thread::spawn(move || {
+                let mut latencies = Vec::with_capacity(total);
+                let sum: u64 = (0..total).map(|_| recv_timed(&rx, &mut latencies)).sum();
+                (sum, latencies)
+            });
+            let producers: Vec<_> = (0..BURST_PRODUCERS)
+                .map(|_| {
+                    let tx = tx.clone();
+                    thread::spawn(move || {
+                        pattern.produce(BURST_MESSAGES, || tx.send(Message::new(1)))
+                    })
+                })
+                .collect();
+            for producer in producers {
+                producer.join().unwrap();
+            }
+            let (sum, consumer_latencies) = consumer.join().unwrap();
+            assert_eq!(sum, total as u64);
+            if latencies.len() < MAX_LATENCY_SAMPLES {
+                latencies.extend(consumer_latencies);
+            }
+        });
+    });
+    if !latencies.is_empty() {
+        latencies.sort_unstable();
+        println!(
+            "channel_bursts/{}/{}: message latency p50 {:?}, p99 {:?} over {} messages",
+            name,
+            pattern.name(),
+            percentile(&latencies, 0.5),
+            percentile(&latencies, 0.99),
+            latencies.len()
+        );
+    }
+}
+
+/// Benchmark for bursty traffic on bounded channels.
+/// This function measures 4 producers sending 1,024 messages each into
+/// `mpsc::sync_channel(64)`, `crossbeam_channel::bounded(64)`, and, with the
+/// `flume` feature, `flume::bounded(64)`, either steadily or in bursts of 256
+/// separated by 5ms of idling. Criterion times whole iterations, and the
+/// printed p99 message latency shows how far the queue backs up while a
+/// burst drains, which the steady run next to it does not.
+fn channel_bursts(c: &mut Criterion) {
+    let mut group = c.benchmark_group("channel_bursts");
+    group.throughput(Throughput::Elements(
+        (BURST_PRODUCERS * BURST_MESSAGES) as u64,
+    ));
+    let patterns = [
+        ArrivalPattern::Steady,
+        ArrivalPattern::Burst {
+            size: BURST_SIZE,
+            gap: BURST_GAP,
+        },
+    ];
+    for pattern in patterns {
+        bench_arrivals::<mpsc::SyncSender<_>>(&mut group, "mpsc_sync_channel", pattern);
+        bench_arrivals::<crossbeam_channel::Sender<_>>(&mut group, "crossbeam_bounded", pattern);
+        #[cfg(feature = "flume")]
+        bench_arrivals::<flume::Sender<_>>(&mut group, "flume_bounded", pattern);
+    }
+    group.finish();
+}
+
+/// Producer threads in the select benchmarks, each with its own channel.
+const SELECT_PRODUCERS: usize = 4;
+
+/// Messages each select producer sends per iteration.
+const SELECT_MESSAGES: usize = 250;
+
+/// Spawns a producer that sends `SELECT_MESSAGES` copies of `id` and then
+/// drops `tx`, disconnecting the channel if it held the last sender.
+fn spawn_select_producer(id: usize, tx: crossbeam_channel::Sender<u64>) -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        for _ in 0..SELECT_MESSAGES {
+            tx.send(id as u64).unwrap();
+        }
+    })
+}
+
+/// Drains `receivers` with `select!` until all of them have disconnected and
+/// returns how many messages arrived on each. A disconnected receiver is
+/// always ready, so it is swapped for `never()` to stop select picking it again.
+fn select_drain(
+    mut receivers: [crossbeam_channel::Receiver<u64>; SELECT_PRODUCERS],
+) -> [usize; SELECT_PRODUCERS] {
+    let mut received = [0; SELECT_PRODUCERS];
+    let mut open = SELECT_PRODUCERS;
+    while open > 0 {
+        let (i, msg) = crossbeam_channel::select! {
+            recv(receivers[0]) -> msg => (0, msg),
+            recv(receivers[1]) -> msg => (1, msg),
+            recv(receivers[2]) -> msg => (2, msg),
+            recv(receivers[3]) -> msg => (3, msg),
+        };
+        match msg {
+            Ok(value) => {
+                black_box(value);
+                received[i] += 1;
+            }
+            Err(_) => {
+                receivers[i] = crossbeam_channel::never();
+                open -= 1;
+            }
+        }
+    }
+    received
+}
+
+/// Benchmark for one consumer selecting over several crossbeam receivers.
+/// This function measures the time to drain 4 producers sending 250 messages
+/// each, either through their own channels with the consumer running
+/// `crossbeam_channel::select!` over all 4 receivers, or funnelled into a
+/// single shared channel as the baseline. The staggered variant lets two
+/// producers finish and drop their senders before the other two start, so the
+/// consumer keeps selecting after some channels have disconnected. Every
+/// iteration asserts that exactly 1,000 messages arrived, 250 from each producer.
+fn crossbeam_select(c: &mut Criterion) {
+    let mut group = c.benchmark_group("crossbeam_select");
+    group.throughput(Throughput::Elements(
+        (SELECT_PRODUCERS * SELECT_MESSAGES) as u64,
+    ));
+    for staggered in [false, true] {
+        let name = if staggered {
+            "select_staggered_disconnects"
+        } else {
+            "select_4_receivers"
+        };
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let (senders, receivers): (Vec<_>, Vec<_>) = (0..SELECT_PRODUCERS)
+                    .map(|_| crossbeam_channel::unbounded())
+                    .unzip();
+                let receivers = receivers.try_into().unwrap();
+                let consumer = thread::spawn(move || select_drain(receivers));
+                let mut producers: Vec<thread::JoinHandle<()>> = Vec::new();
+                for (id, tx) in senders.into_iter().enumerate() {
+                    if staggered && id == SELECT_PRODUCERS / 2 {
+                        for producer in producers.drain(..) {
+                            producer.join().unwrap();
+                        }
+                    }
+                    producers.push(spawn_select_producer(id, tx));
+                }
+                for producer in producers {
+                    producer.join().unwrap();
+                }
+                let received = consumer.join().unwrap();
+                assert_eq!(received, [SELECT_MESSAGES; SELECT_PRODUCERS]);
+            });
+        });
+    }
+    group.bench_function("single_channel", |b| {
+        b.iter(|| {
+            let (tx, rx) = crossbeam_channel::unbounded();
+            let consumer = thread::spawn(move || {
+                let mut received = [0; SELECT_PRODUCERS];
+                for value in rx {
+                    received[black_box(value) as usize] += 1;
+                }
+                received
+            });
+            let producers: Vec<_> = (0..SELECT_PRODUCERS)
+                .map(|id| spawn_select_producer(id, tx.clone()))
+                .collect();
+            drop(tx);
+            for producer in producers {
+                producer.join().unwrap();
+            }
+            let received = consumer.join().unwrap();
+            assert_eq!(received, [SELECT_MESSAGES; SELECT_PRODUCERS]);
+        });
+    });
+    group.finish();
+}
+
+/// Messages the producer sends in the polling benchmarks.
+const POLLING_MESSAGES: usize = 10_000;
+
+/// How long the polling producer sleeps between messages.
+const POLLING_GAP: Duration = Duration::from_micros(10);
+
+/// `try_recv` attempts the spin-then-block consumer makes before blocking.
+const POLLING_SPINS: u32 = 100;
+
+/// How the consumer in the polling benchmarks waits for the next message.
+#[derive(Clone, Copy)]
+enum Polling {
+    /// Blocks in `recv` until a message arrives.
+    Blocking,
+    /// Spins on `try_recv`, calling `spin_loop` between attempts.
+    BusyPoll,
+    /// Spins on `try_recv` up to the given number of times, then blocks in `recv`.
+    SpinThenBlock(u32),
+}
+
+impl Polling {
+    /// Receives the next message, or `None` once the producer has hung up.
+    fn recv(self, rx: &mpsc::Receiver<u64>) -> Option<u64> {
+        match self {
+            Polling::Blocking => rx.recv().ok(),
+            Polling::BusyPoll => loop {
+                match rx.try_recv() {
+                    Err(mpsc::TryRecvError::Empty) => std::hint::spin_loop(),
+                    other => return other.ok(),
+                }
+            },
+            Polling::SpinThenBlock(spins) => {
+                for _ in 0..spins {
+                    match rx.try_recv() {
+                        Err(mpsc::TryRecvError::Empty) => std::hint::spin_loop(),
+                        other => return other.ok(),
+                    }
+                }
+                rx.recv().ok()
+            }
+        }
+    }
+}
+
+/// Benchmark for busy-polling a channel versus blocking on it.
+/// This function measures one producer sending 10,000 messages over
+/// `mpsc::channel` with a 10µs sleep between them, while the consumer either
+/// blocks in `recv`, spins on `try_recv`, or spins 100 times before falling
+/// back to `recv`. Criterion reports the total time; with the `cpu-time`
+/// feature on Linux or macOS, the consumer also reads its thread CPU clock and the
+/// average CPU time per iteration is printed, which is where spinning pays.
+fn spsc_polling(c: &mut Criterion) {
+    let mut group = c.benchmark_group("spsc_polling");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(POLLING_MESSAGES as u64));
+    for (name, polling) in [
+        ("blocking_recv", Polling::Blocking),
+        ("busy_poll_try_recv", Polling::BusyPoll),
+        ("spin_then_block", Polling::SpinThenBlock(POLLING_SPINS)),
+    ] {
+        let mut cpu_time = Some(Duration::ZERO);
+        let mut iterations = 0u32;
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let (tx, rx) = mpsc::channel();
+                let consumer = thread::spawn(move || {
+                    let start = thread_cpu_time();
+                    let mut received = 0;
+                    while let Some(value) = polling.recv(&rx) {
+                        black_box(value);
+                        received += 1;
+                    }
+                    let used = start.zip(thread_cpu_time()).map(|(start, end)| end - start);
+                    (received, used)
+                });
+                for i in 0..POLLING_MESSAGES {
+                    tx.send(i as u64).unwrap();
+                    thread::sleep(POLLING_GAP);
+                }
+                drop(tx);
+                let (received, used) = consumer.join().unwrap();
+                assert_eq!(received, POLLING_MESSAGES);
+                cpu_time = cpu_time.zip(used).map(|(total, used)| total + used);
+                iterations += 1;
+            });
+        });
+        if let Some(cpu_time) = cpu_time.filter(|_| iterations > 0) {
+            println!(
+                "spsc_polling/{}: consumer used {:?} of CPU time per iteration on average",
+                name,
+                cpu_time / iterations
+            );
+        }
+    }
+    group.finish();
+}
+
+/// Increments each actor client performs per iteration.
+const ACTOR_INCREMENTS: usize = 1_000;
+
+/// In the read variants, clients read the counter after every this many increments.
+const ACTOR_READ_EVERY: usize = 10;
+
+/// A channel used as an actor's mailbox: one owner thread holds the receiver
+/// and drains it until every sender has been dropped. Unlike `Channel`, the
+/// std receiver is not wrapped in a Mutex, because only the owner touches it.
+trait Mailbox<T>: Clone + Send + 'static {
+    type Receiver: IntoIterator<Item = T> + Send + 'static;
+
+    fn unbounded() -> (Self, Self::Receiver);
+
+    fn send(&self, message: T);
+}
+
+impl<T: Send + 'static> Mailbox<T> for mpsc::Sender<T> {
+    type Receiver = mpsc::Receiver<T>;
+
+    fn unbounded() -> (Self, Self::Receiver) {
+        mpsc::channel()
+    }
+
+    fn send(&self, message: T) {
+        mpsc::Sender::send(self, message).unwrap();
+    }
+}
+
+impl<T: Send + 'static> Mailbox<T> for crossbeam_channel::Sender<T> {
+    type Receiver = crossbeam_channel::Receiver<T>;
+
+    fn unbounded() -> (Self, Self::Receiver) {
+        crossbeam_channel::unbounded()
+    }
+
+    fn send(&self, message: T) {
+        crossbeam_channel::Sender::send(self, message).unwrap();
+    }
+}
+
+/// A command sent to the counter's owner thread. `Get` carries a fresh
+/// single-use channel the owner answers on.
+enum Command<M> {
+    Increment,
+    Get(M),
+}
+
+/// Registers `{name}/increment/{clients}` and `{name}/increment_get/{clients}`
+/// in `group`: an owner thread holds the counter and serves `Command`s from a
+/// mailbox of type `C`, while `clients` threads each send `ACTOR_INCREMENTS` increments. In
+/// `increment_get` every client also sends a `Get` after every
+/// `ACTOR_READ_EVERY` increments and waits for the reply on a mailbox of type
+/// `R`, which must count at least its own increments so far. The owner returns
+/// the counter once its mailbox disconnects, and it must equal the total number
+/// of increments.
+fn bench_actor<C: Mailbox<Command<R>>, R: Mailbox<u64>>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    name: &str,
+    clients: usize,
+) {
+    for (variant, reads) in [("increment", false), ("increment_get", true)] {
+        let id = BenchmarkId::new(format!("{}/{}", name, variant), clients);
+        group.bench_function(id, |b| {
+            b.iter(|| {
+                let (mailbox, commands) = C::unbounded();
+                let owner = thread::spawn(move || {
+                    let mut count = 0u64;
+                    for command in commands {
+                        match command {
+                            Command::Increment => count += 1,
+                            Command::Get(reply) => reply.send(count),
+                        }
+                    }
+                    count
+                });
+                let handles: Vec<_> = (0..clients)
+                    .map(|_| {
+                        let mailbox = mailbox.clone();
+                        thread::spawn(move || {
+                            for i in 1..=ACTOR_INCREMENTS {
+                                mailbox.send(Command::Increment);
+                                if reads && i.is_multiple_of(ACTOR_READ_EVERY) {
+                                    let (reply, answer) = R::unbounded();
+                                    mailbox.send(Command::Get(reply));
+                                    let count = answer.into_iter().next().unwrap();
+                                    assert!(black_box(count) >= i as u64);
+                                }
+                            }
+                        })
+                    })
+                    .collect();
+                drop(mailbox);
+                for handle in handles {
+                    handle.join().unwrap();
+                }
+                let count = owner.join().unwrap();
+                assert_eq!(count, (clients * ACTOR_INCREMENTS) as u64);
+            });
+        });
+    }
+}
+
+/// Benchmark for giving state to one owner thread versus sharing it behind a Mutex.
+/// This function measures client threads performing 1,000 increments each,
+/// either by sending `Increment` commands to an actor thread that owns the
+/// counter, over std mpsc and over crossbeam, or by locking a shared
+/// `Arc<Mutex<u64>>` directly. The `increment_get` variants also read the
+/// counter after every 10th increment: actor clients send a `Get` with a reply
+/// channel and wait for the answer, while Mutex clients lock and read. Both
+/// designs must end with the counter at exactly 1,000 per client.
+fn actor_vs_mutex(c: &mut Criterion) {
+    let mut group = c.benchmark_group("actor_vs_mutex");
+    for &clients in thread_counts() {
+        group.throughput(Throughput::Elements((clients * ACTOR_INCREMENTS) as u64));
+        bench_actor::<mpsc::Sender<_>, mpsc::Sender<_>>(&mut group, "mpsc_actor", clients);
+        bench_actor::<crossbeam_channel::Sender<_>, crossbeam_channel::Sender<_>>(
+            &mut group,
+            "crossbeam_actor",
+            clients,
+        );
+        for (variant, reads) in [("increment", false), ("increment_get", true)] {
+            let id = BenchmarkId::new(format!("arc_mutex/{}", variant), clients);
+            group.bench_function(id, |b| {
+                b.iter(|| {
+                    let counter = Arc::new(Mutex::new(0u64));
+                    let handles: Vec<_> = (0..clients)
+                        .map(|_| {
+                            let counter = Arc::clone(&counter);
+                            thread::spawn(move || {
+                                for i in 1..=ACTOR_INCREMENTS {
+                                    *counter.lock().unwrap() += 1;
+                                    if reads && i.is_multiple_of(ACTOR_READ_EVERY) {
+                                        let count = *counter.lock().unwrap();
+                                        assert!(black_box(count) >= i as u64);
+                                    }
+                                }
+                            })
+                        })
+                        .collect();
+                    for handle in handles {
+                        handle.join().unwrap();
+                    }
+                    assert_eq!(
+                        *counter.lock().unwrap(),
+                        (clients * ACTOR_INCREMENTS) as u64
+                    );
+                });
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Worker threads in the aggregation benchmarks.
+const AGGREGATION_THREADS: usize = 8;
+
+/// Results the aggregation workers produce between them per iteration.
+const AGGREGATION_RESULTS: usize = 100_000;
+
+/// The result a worker computes for input `i`, scrambled so that sorted order
+/// has nothing to do with the order the workers produce results in.
+fn aggregation_result(i: usize) -> u64 {
+    (i as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+}
+
+/// Spawns `AGGREGATION_THREADS` workers, each computing its contiguous share
+/// of `AGGREGATION_RESULTS` and handing every result to `emit`, and returns
+/// their handles. Each worker returns whatever `finish` builds from its
+/// emitter once its share is done.
+fn spawn_aggregation_workers<E, R>(
+    emitter: impl Fn() -> E,
+    finish: fn(E) -> R,
+    emit: fn(&mut E, u64),
+) -> Vec<thread::JoinHandle<R>>
+where
+    E: Send + 'static,
+    R: Send + 'static,
+{
+    let share = AGGREGATION_RESULTS / AGGREGATION_THREADS;
+    (0..AGGREGATION_THREADS)
+        .map(|t| {
+            let mut emitter = emitter();
+            thread::spawn(move || {
+                for i in t * share..(t + 1) * share {
+                    emit(&mut emitter, black_box(aggregation_result(i)));
+                }
+                finish(emitter)
+            })
+        })
+        .collect()
+}
+
+/// Collects every result by having each worker push into one shared Vec.
+fn aggregate_shared_vec() -> Vec<u64> {
+    let results = Arc::new(Mutex::new(Vec::with_capacity(AGGREGATION_RESULTS)));
+    let workers = spawn_aggregation_workers(
+        || Arc::clone(&results),
+        drop,
+        |results, value| results.lock().unwrap().push(value),
+    );
+    for worker in workers {
+        worker.join().unwrap();
+    }
+    Arc::try_unwrap(results).unwrap().into_inner().unwrap()
+}
+
+/// Collects every result by having each worker send it over a channel that
+/// the calling thread drains while the workers run.
+fn aggregate_channel() -> Vec<u64> {
+    let (tx, rx) = mpsc::channel();
+    let workers =
+        spawn_aggregation_workers(|| tx.clone(), drop, |tx, value| tx.send(value).unwrap());
+    drop(tx);
+    let mut results = Vec::with_capacity(AGGREGATION_RESULTS);
+    results.extend(rx);
+    for worker in workers {
+        worker.join().unwrap();
+    }
+    results
+}
+
+/// Collects every result by having each worker fill a Vec of its own, which
+/// the calling thread appends together after joining them.
+fn aggregate_local_merge() -> Vec<u64> {
+    let workers = spawn_aggregation_workers(
+        || Vec::with_capacity(AGGREGATION_RESULTS / AGGREGATION_THREADS),
+        |local| local,
+        Vec::push,
+    );
+    let mut results = Vec::with_capacity(AGGREGATION_RESULTS);
+    for worker in workers {
+        results.append(&mut worker.join().unwrap());
+    }
+    results
+}
+
+/// Benchmark for gathering results from worker threads.
+/// This function measures 8 threads producing 100,000 u64 results between
+/// them, collected three ways: `shared_mutex_vec` pushes every result into an
+/// `Arc<Mutex<Vec>>`, `channel` sends every result over an mpsc channel that
+/// the main thread drains, and `local_merge` fills a Vec per thread that the
+/// main thread merges after joining. Before measuring, the sorted output of
+/// every strategy is checked to be the same multiset; every iteration then
+/// checks the length and sum.
+fn result_aggregation(c: &mut Criterion) {
+    let strategies = [
+        ("shared_mutex_vec", aggregate_shared_vec as fn() -> Vec<u64>),
+        ("channel", aggregate_channel),
+        ("local_merge", aggregate_local_merge),
+    ];
+    let sorted = |mut results: Vec<u64>| {
+        results.sort_unstable();
+        results
+    };
+    let expected = sorted((0..AGGREGATION_RESULTS).map(aggregation_result).collect());
+    let expected_sum = expected.iter().fold(0u64, |sum, &v| sum.wrapping_add(v));
+    for (name, aggregate) in strategies {
+        assert!(
+            sorted(aggregate()) == expected,
+            "{} lost or duplicated results",
+            name
+        );
+    }
+
+    let mut group = c.benchmark_group("result_aggregation");
+    group.throughput(Throughput::Elements(AGGREGATION_RESULTS as u64));
+    for (name, aggregate) in strategies {
+        group.bench_function(name, |b| {
+            b.iter(|| {
+                let results = aggregate();
+                assert_eq!(results.len(), AGGREGATION_RESULTS);
+                let sum = results.iter().fold(0u64, |sum, &v| sum.wrapping_add(v));
+                assert_eq!(sum, expected_sum);
+            });
+        });
+    }
+    group.finish();
+}
+
+/// Items pushed through the pipeline benchmarks per iteration.
+const PIPELINE_ITEMS: usize = 10_000;
+
+/// Capacity of the channels between pipeline stages.
+const PIPELINE_CAPACITY: usize = 64;
+
+/// The work the transform stage does on every item.
+fn pipeline_transform(value: u64) -> u64 {
+    black_box(value.wrapping_mul(3).wrapping_add(1))
+}
+
+/// Registers `name/stage_threads/{1,2}` in `group`: a generate, transform, and
+/// sink stage, each run by that many threads and connected by bounded
+/// channels of type `C`, push `PIPELINE_ITEMS` items through as
+/// `(index, value)` pairs. Every stage runs until its upstream channel
+/// disconnects. With one thread per stage the sink must see every index in
+/// order; with two, the indices may arrive in any order but must all arrive
+/// exactly once. Every value must have been transformed.
+fn bench_pipeline<C: BoundedChannel<(u64, u64)>>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    name: &str,
+) {
+    for stage_threads in [1, 2] {
+        group.bench_with_input(
+            BenchmarkId::new(format!("{}/stage_threads", name), stage_threads),
+            &stage_threads,
+            |b, &stage_threads| {
+                b.iter(|| {
+                    let (generated, to_transform) = C::bounded(PIPELINE_CAPACITY);
+                    let (transformed, to_sink) = C::bounded(PIPELINE_CAPACITY);
+                    let share = PIPELINE_ITEMS / stage_threads;
+                    let generators: Vec<_> = (0..stage_threads)
+                        .map(|t| {
+                            let tx = generated.clone();
+                            thread::spawn(move || {
+                                for i in t * share..(t + 1) * share {
+                                    tx.send((i as u64, i as u64));
+                                }
+                            })
+                        })
+                        .collect();
+                    let transformers: Vec<_> = (0..stage_threads)
+                        .map(|_| {
+                            let (rx, tx) = (to_transform.clone(), transformed.clone());
+                            thread::spawn(move || {
+                                while let Some((i, value)) = rx.recv_if_connected() {
+                                    tx.send((i, pipeline_transform(value)));
+                                }
+                            })
+                        })
+                        .collect();
+                    let sinks: Vec<_> = (0..stage_threads)
+                        .map(|_| {
+                            let rx = to_sink.clone();
+                            thread::spawn(move || {
+                                let mut received = Vec::with_capacity(share);
+                                while let Some(item) = rx.recv_if_connected() {
+                                    received.push(item);
+                                }
+                                received
+                            })
+                        })
+                        .collect();
+                    drop((generated, to_transform, transformed, to_sink));
+                    for handle in generators.into_iter().chain(transformers) {
+                        handle.join().unwrap();
+                    }
+                    let mut received = Vec::with_capacity(PIPELINE_ITEMS);
+                    for sink in sinks {
+                        received.append(&mut sink.join().unwrap());
+                    }
+                    if stage_threads > 1 {
+                        received.sort_unstable();
+                    }
+                    assert_eq!(received.len(), PIPELINE_ITEMS);
+                    for (expected, &(i, value)) in received.iter().enumerate() {
+                        assert_eq!(i, expected as u64);
+                        assert_eq!(value, pipeline_transform(i));
+                    }
+                });
+            },
+        );
+    }
+}
+
+/// Benchmark for a three-stage pipeline of threads connected by channels.
+/// This function measures the end-to-end time of pushing 10,000 items through
+/// generate, transform, and sink stages, with one or two threads per stage,
+/// over `mpsc::sync_channel`, `crossbeam_channel::bounded`, and, with the
+/// `flume` feature, `flume::bounded`, all with capacity 64. As in the other
+/// bounded benchmarks, the mpsc Receiver is shared behind an Arc<Mutex>, which
+/// is also what lets two threads run a stage.
+fn channel_pipeline(c: &mut Criterion) {
+    let mut group = c.benchmark_group("channel_pipeline");
+    group.throughput(Throughput::Elements(PIPELINE_ITEMS as u64));
+    bench_pipeline::<mpsc::SyncSender<_>>(&mut group, "mpsc");
+    bench_pipeline::<crossbeam_channel::Sender<_>>(&mut group, "crossbeam");
+    #[cfg(feature = "flume")]
+    bench_pipeline::<flume::Sender<_>>(&mut group, "flume");
+    group.finish();
+}
+
+/// Messages sent per iteration in the channel allocation benchmarks.
+#[cfg(feature = "count-allocations")]
+const ALLOCATION_MESSAGES: u64 = 10_000;
+
+/// Capacity of the bounded channels in the channel allocation benchmarks.
+#[cfg(feature = "count-allocations")]
+const ALLOCATION_CAPACITY: usize = 64;
+
+/// Registers `name` in `group`: one producer sends `ALLOCATION_MESSAGES`
+/// messages over a channel made by `channel` to one consumer, and only the
+/// sends, the receives, and joining the consumer run with allocation counting
+/// on, so creating the channel and spawning the consumer never count. Once
+/// measured, the allocations and bytes per message are printed.
+#[cfg(feature = "count-allocations")]
+fn bench_channel_allocations<S, R>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    name: &str,
+    channel: impl Fn() -> (S, R),
+    send: impl Fn(&S, u64),
+) where
+    R: ChannelReceiver<u64>,
+{
+    use basic_rust_concurrency_benchmarks::allocations::{count_allocations, AllocationCounts};
+
+    let mut total = AllocationCounts::default();
+    let mut messages = 0u64;
+    group.bench_function(name, |b| {
+        b.iter(|| {
+            let (tx, rx) = channel();
+            let consumer = thread::spawn(move || {
+                let mut received = 0u64;
+                while let Some(value) = rx.recv_if_connected() {
+                    black_box(value);
+                    received += 1;
+                }
+                received
+            });
+            let (received, counts) = count_allocations(|| {
+                for i in 0..ALLOCATION_MESSAGES {
+                    send(&tx, i);
+                }
+                drop(tx);
+                consumer.join().unwrap()
+            });
+            assert_eq!(received, ALLOCATION_MESSAGES);
+            total += counts;
+            messages += ALLOCATION_MESSAGES;
+        });
+    });
+    if messages > 0 {
+        println!(
+            "channel_allocations/{}: {:.4} allocations and {:.2} bytes per message",
+            name,
+            total.allocations as f64 / messages as f64,
+            total.bytes as f64 / messages as f64
+        );
+    }
+}
+
+/// Registers `name` in `group` for an unbounded channel of type `C`.
+#[cfg(feature = "count-allocations")]
+fn bench_unbounded_allocations<C: Channel<u64>>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    name: &str,
+) {
+    bench_channel_allocations(group, name, C::unbounded, C::send);
+}
+
+/// Registers `name` in `group` for a bounded channel of type `C` with
+/// capacity `ALLOCATION_CAPACITY`.
+#[cfg(feature = "count-allocations")]
+fn bench_bounded_allocations<C: BoundedChannel<u64>>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    name: &str,
+) {
+    bench_channel_allocations(group, name, || C::bounded(ALLOCATION_CAPACITY), C::send);
+}
+
+/// Benchmark for heap allocations per message across channel implementations.
+/// This function sends 10,000 messages from one producer to one consumer over
+/// unbounded and capacity-64 bounded channels from std's mpsc, crossbeam, and,
+/// with the `flume` feature, flume, counting every allocation made while the
+/// messages are in flight. Unbounded channels grow their buffer as messages
+/// arrive, while bounded channels allocate their buffer once up front and at
+/// most some bookkeeping for blocked senders, which the printed allocations
+/// per message show.
+#[cfg(feature = "count-allocations")]
+fn channel_allocations(c: &mut Criterion) {
+    let mut group = c.benchmark_group("channel_allocations");
+    group.throughput(Throughput::Elements(ALLOCATION_MESSAGES));
+    bench_unbounded_allocations::<mpsc::Sender<_>>(&mut group, "mpsc_unbounded");
+    bench_bounded_allocations::<mpsc::SyncSender<_>>(&mut group, "mpsc_bounded");
+    bench_unbounded_allocations::<crossbeam_channel::Sender<_>>(&mut group, "crossbeam_unbounded");
+    bench_bounded_allocations::<crossbeam_channel::Sender<_>>(&mut group, "crossbeam_bounded");
+    #[cfg(feature = "flume")]
+    bench_unbounded_allocations::<flume::Sender<_>>(&mut group, "flume_unbounded");
+    #[cfg(feature = "flume")]
+    bench_bounded_allocations::<flume::Sender<_>>(&mut group, "flume_bounded");
+    group.finish();
+}
+#[cfg(not(feature = "count-allocations"))]
+fn channel_allocations(_: &mut Criterion) {}
+
+/// Benchmarks for read-heavy, write-heavy, and mixed workloads using Arc<tokio::sync::Mutex>.
+/// These functions measure the performance of multiple tokio tasks on a
+/// multi-threaded runtime locking an Arc-wrapped async Mutex-protected integer,
+/// with an async sleep added for every even iteration.
+fn async_tokio_mutex(c: &mut Criterion) {
+    let runtime = tokio_runtime();
+    let data = Arc::new(tokio::sync::Mutex::new(0u64));
+    for (name, stall) in lock_stall_variants("async_tokio_mutex") {
+        let mut group = c.benchmark_group(name);
+        for ops in OPS_PER_THREAD {
+            for &threads in thread_counts() {
+                group.throughput(Throughput::Elements(threads as u64 * ops));
+                group.bench_with_input(
+                    BenchmarkId::new(ops_function_name("read_heavy", ops), threads),
+                    &threads,
+                    |b, &threads| {
+                        b.to_async(&runtime).iter(|| async {
+                            let before = *data.lock().await;
+                            let sums = spawn_tasks_and_join(threads, |i| {
+                                let data_clone = Arc::clone(&data);
+                                async move {
+                                    let mut sum = 0;
+                                    for op in 0..ops {
+                                        let stall = stall.only_on_first(op);
+                                        let guard = data_clone.lock().await;
+                                        sum += black_box(*guard);
+                                        stall.while_locked_async(i).await;
+                                        drop(guard);
+                                        stall.after_unlock_async(i).await;
+                                    }
+                                    sum
+                                }
+                            })
+                            .await;
+                            assert_observed_sums(&sums, ops, before, *data.lock().await);
+                        });
+                    },
+                );
+                group.bench_with_input(
+                    BenchmarkId::new(ops_function_name("write_heavy", ops), threads),
+                    &threads,
+                    |b, &threads| {
+                        b.to_async(&runtime).iter(|| async {
+                            let before = *data.lock().await;
+                            spawn_tasks_and_join(threads, |i| {
+                                let data_clone = Arc::clone(&data);
+                                async move {
+                                    for op in 0..ops {
+                                        let stall = stall.only_on_first(op);
+                                        let mut num = data_clone.lock().await;
+                                        *num += 1;
+                                        stall.while_locked_async(i).await;
+                                        drop(num);
+                                        stall.after_unlock_async(i).await;
+                                    }
+                                }
+                            })
+                            .await;
+                            let after = *data.lock().await;
+                            assert_counter_delta(before, after, threads as u64 * ops);
+                        });
+                    },
+                );
+                group.bench_with_input(
+                    BenchmarkId::new(ops_function_name("mixed", ops), threads),
+                    &threads,
+                    |b, &threads| {
+                        let scenario = mixed_scenario(threads, ops);
+                        let expected_writes = scenario.expected_writes();
+                        b.to_async(&runtime).iter(|| async {
+                            let before = *data.lock().await;
+                            let sums = spawn_tasks_and_join(threads, |i| {
+                                let data_clone = Arc::clone(&data);
+                                let mut operations = scenario.operations(i);
+                                async move {
+                                    let mut sum = 0;
+                                    for op in 0..ops {
+                                        let stall = stall.only_on_first(op);
+                                        let write = operations.next_is_write();
+                                        let mut num = data_clone.lock().await;
+                                        if write {
+                                            *num += 1;
+                                        }
+                                        sum += black_box(*num);
+                                        stall.while_locked_async(i).await;
+                                        drop(num);
+                                        stall.after_unlock_async(i).await;
+                                    }
+                                    sum
+                                }
+                            })
+                            .await;
+                            let after = *data.lock().await;
+                            assert_observed_sums(&sums, ops, before, after);
+                            assert_counter_delta(before, after, expected_writes);
+                        });
+                    },
+                );
+            }
+        }
+        group.finish();
+    }
+}
+
+/// Benchmark for mixed read/write workloads using Arc<tokio::sync::RwLock>.
+/// This function measures the performance of multiple tokio tasks
+/// performing both reads and writes to an Arc-wrapped async RwLock-protected integer,
+/// with an async sleep added for every even iteration.
+fn async_tokio_rwlock_mixed(c: &mut Criterion) {
+    let runtime = tokio_runtime();
+    let data = Arc::new(tokio::sync::RwLock::new(0u64));
+    bench_lock_with_stalls(c, "async_tokio_rwlock_mixed", |b, threads, ops, stall| {
+        let scenario = mixed_scenario(threads, ops);
+        let expected_writes = scenario.expected_writes();
+        b.to_async(&runtime).iter(|| async {
+            let before = *data.read().await;
+            let sums = spawn_tasks_and_join(threads, |i| {
+                let data_clone = Arc::clone(&data);
+                let mut operations = scenario.operations(i);
+                async move {
+                    let mut sum = 0;
+                    for op in 0..ops {
+                        let stall = stall.only_on_first(op);
+                        if operations.next_is_write() {
+                            let mut num = data_clone.write().await;
+                            *num += 1;
+                            sum += *num;
+                            stall.while_locked_async(i).await;
+                            drop(num);
+                            stall.after_unlock_async(i).await;
+                        } else {
+                            let guard = data_clone.read().await;
+                            sum += black_box(*guard);
+                            stall.while_locked_async(i).await;
+                            drop(guard);
+                            stall.after_unlock_async(i).await;
+                        }
+                    }
+                    sum
+                }
+            })
+            .await;
+            let after = *data.read().await;
+            assert_observed_sums(&sums, ops, before, after);
+            assert_counter_delta(before, after, expected_writes);
+        });
+    });
+}
+
+/// Benchmark for mixed read/write workloads using a std RwLock inside spawn_blocking.
+/// This function measures the performance of multiple tokio tasks that each
+/// hand off to the blocking pool and take an Arc-wrapped std RwLock there,
+/// with a delay added for every even iteration.
+fn async_spawn_blocking_std_rwlock_mixed(c: &mut Criterion) {
+    let runtime = tokio_runtime();
+    let data = Arc::new(RwLock::new(0u64));
+    bench_lock_with_stalls(
+        c,
+        "async_spawn_blocking_std_rwlock_mixed",
+        |b, threads, ops, stall| {
+            let scenario = mixed_scenario(threads, ops);
+            let expected_writes = scenario.expected_writes();
+            b.to_async(&runtime).iter(|| async {
+                let before = *data.read().unwrap();
+                let sums = spawn_tasks_and_join(threads, |i| {
+                    let data_clone = Arc::clone(&data);
+                    async move {
+                        tokio::task::spawn_blocking(move || {
+                            let mut operations = scenario.operations(i);
+                            let mut sum = 0;
+                            for op in 0..ops {
+                                let stall = stall.only_on_first(op);
+                                if operations.next_is_write() {
+                                    let mut num = data_clone.write().unwrap();
+                                    *num += 1;
+                                    sum += *num;
+                                    stall.while_locked(i);
+                                    drop(num);
+                                    stall.after_unlock(i);
+                                } else {
+                                    let guard = data_clone.read().unwrap();
+                                    sum += black_box(*guard);
+                                    stall.while_locked(i);
+                                    drop(guard);
+                                    stall.after_unlock(i);
+                                }
+                            }
+                            sum
+                        })
+                        .await
+                        .unwrap()
+                    }
+                })
+                .await;
+                let after = *data.read().unwrap();
+                assert_observed_sums(&sums, ops, before, after);
+                assert_counter_delta(before, after, expected_writes);
+            });
+        },
+    );
+}
+
+/// Benchmark for `workload` on a bounded tokio mpsc channel.
+/// This function splits sends and receives nine to one like `bench_channel`:
+/// write-heavy has every task send nine messages and receive one, and
+/// read-heavy has every task send one and receive nine, with the tokio Mutex
+/// around the receiver taken for each. One extra task receives, or sends, the
+/// difference, and every task runs at once, since the channel holds 32
+/// messages and would otherwise fill with nobody receiving. Every message is a
+/// 1, so the sum received must equal the messages sent.
+fn bench_tokio_mpsc(c: &mut Criterion, name: &str, workload: Workload) {
+    let runtime = tokio_runtime();
+    let (sends, receives) = match workload {
+        Workload::ReadHeavy => (1, CHANNEL_OPS_PER_THREAD - 1),
+        Workload::WriteHeavy => (CHANNEL_OPS_PER_THREAD - 1, 1),
+        Workload::Mixed => unreachable!("tokio_mpsc_mixed splits its tasks instead"),
+    };
+    for (name, stall) in stall_variants(name) {
+        bench_over_threads_with_elements(
+            c,
+            &name,
+            |threads| (threads * CHANNEL_OPS_PER_THREAD) as u64,
+            |b, threads| {
+                b.to_async(&runtime).iter(|| async {
+                    let (tx, rx) = tokio::sync::mpsc::channel(32);
+                    let rx = Arc::new(tokio::sync::Mutex::new(rx));
+                    let received = spawn_tasks_and_join(threads + 1, |i| {
+                        let tx_clone = tx.clone();
+                        let rx_clone = Arc::clone(&rx);
+                        let (sends, receives) = if i < threads {
+                            (sends, receives)
+                        } else {
+                            (
+                                threads * receives.saturating_sub(sends),
+                                threads * sends.saturating_sub(receives),
+                            )
+                        };
+                        async move {
+                            for _ in 0..sends {
+                                tx_clone.send(1).await.unwrap();
+                            }
+                            if i < threads {
+                                stall.apply_async(i).await;
+                            }
+                            let mut sum = 0u64;
+                            for _ in 0..receives {
+                                sum += rx_clone.lock().await.recv().await.unwrap();
+                            }
+                            sum
+                        }
+                    })
+                    .await;
+                    assert_eq!(received.iter().sum::<u64>(), channel_messages(threads));
+                });
+            },
+        );
+    }
+}
+
+/// Benchmark for read-heavy workloads using tokio mpsc channels.
+/// This function runs `bench_tokio_mpsc` with every task receiving nine
+/// messages for each one it sends, with an async sleep added for every even
+/// iteration.
+fn tokio_mpsc_read_heavy(c: &mut Criterion) {
+    bench_tokio_mpsc(c, "tokio_mpsc_read_heavy", Workload::ReadHeavy);
+}
+
+/// Benchmark for write-heavy workloads using tokio mpsc channels.
+/// This function runs `bench_tokio_mpsc` with every task sending nine
+/// messages for each one it receives, with an async sleep added for every even
+/// iteration.
+fn tokio_mpsc_write_heavy(c: &mut Criterion) {
+    bench_tokio_mpsc(c, "tokio_mpsc_write_heavy", Workload::WriteHeavy);
+}
+
+/// Benchmark for mixed read/write workloads using tokio mpsc channels.
+/// This function measures the performance of multiple tokio tasks
+/// performing both sending and receiving operations through a bounded tokio mpsc channel,
+/// with an async sleep added for every even iteration.
+fn tokio_mpsc_mixed(c: &mut Criterion) {
+    let runtime = tokio_runtime();
+    bench_with_stalls(c, "tokio_mpsc_mixed", |b, threads, stall| {
+        b.to_async(&runtime).iter(|| async {
+            let (tx, rx) = tokio::sync::mpsc::channel(32);
+            let rx = Arc::new(tokio::sync::Mutex::new(rx));
+            spawn_tasks_and_join(threads, |i| {
+                let tx_clone = tx.clone();
+                let rx_clone = Arc::clone(&rx);
+                async move {
+                    if is_channel_receiver(i, threads) {
+                        let _unused = rx_clone.lock().await.recv().await.unwrap();
+                    } else {
+                        tx_clone.send(i).await.unwrap();
+                    }
+                    stall.apply_async(i).await;
+                }
+            })
+            .await;
+        });
+    });
+}
+
+/// Benchmark for one-to-many fan-out using a tokio broadcast channel.
+/// This function measures the performance of one producer task sending
+/// 100 values to one subscriber task per thread count that each receive every value,
+/// showing the cost of cloning each message to every subscriber.
+/// A lagging subscriber fails the benchmark rather than skewing the result.
+fn tokio_broadcast_fanout(c: &mut Criterion) {
+    let runtime = tokio_runtime();
+    let deliveries = |subscribers: usize| subscribers as u64 * 100;
+    bench_over_threads_with_elements(c, "tokio_broadcast_fanout", deliveries, |b, threads| {
+        b.to_async(&runtime).iter(|| async {
+            let (tx, _) = tokio::sync::broadcast::channel(128);
+            let mut handles = vec![];
+            for _ in 0..threads {
+                let mut rx = tx.subscribe();
+                handles.push(tokio::spawn(async move {
+                    for expected in 0..100u64 {
+                        match rx.recv().await {
+                            Ok(value) => assert_eq!(value, expected),
+                            Err(tokio::sync::broadcast::error::RecvError::Lagged(skipped)) => {
+                                panic!("subscriber lagged by {} messages", skipped)
+                            }
+                            Err(tokio::sync::broadcast::error::RecvError::Closed) => {
+                                panic!("broadcast channel closed early")
+                            }
+                        }
+                    }
+                }));
+            }
+            let producer = tokio::spawn(async move {
+                for value in 0..100u64 {
+                    tx.send(value).unwrap();
+                }
+            });
+            producer.await.unwrap();
+            for handle in handles {
+                handle.await.unwrap();
+            }
+        });
+    });
+}
+
+/// Benchmark for read-mostly shared state using a tokio watch channel.
+/// This function measures the performance of one writer task publishing
+/// 10 updates, with an async sleep added for every even iteration, while
+/// multiple reader tasks borrow the current value until they observe the final one.
+fn tokio_watch_read_heavy(c: &mut Criterion) {
+    let runtime = tokio_runtime();
+    bench_with_stalls(c, "tokio_watch_read_heavy", |b, threads, stall| {
+        b.to_async(&runtime).iter(|| async {
+            let (tx, rx) = tokio::sync::watch::channel(0u64);
+            let mut handles = vec![];
+            for _ in 0..threads {
+                let mut rx_clone = rx.clone();
+                handles.push(tokio::spawn(async move {
+                    while *rx_clone.borrow_and_update() != 10 {
+                        rx_clone.changed().await.unwrap();
+                    }
+                }));
+            }
+            let writer = tokio::spawn(async move {
+                for i in 0..10 {
+                    tx.send(i as u64 + 1).unwrap();
+                    stall.apply_async(i).await;
+                }
+            });
+            writer.await.unwrap();
+            for handle in handles {
+                handle.await.unwrap();
+            }
+        });
+    });
+}
+
+/// Benchmark for request/response workloads using std mpsc reply channels.
+/// This function measures the time for the main thread to send one job per
+/// worker thread, each carrying its own reply Sender, to workers sharing one job
+/// channel, and to collect every response.
+fn mpsc_request_response(c: &mut Criterion) {
+    bench_over_threads(c, "mpsc_request_response", |b, threads| {
+        b.iter(|| {
+            let (job_tx, job_rx) = mpsc::channel::<(u64, mpsc::Sender<u64>)>();
+            let job_rx = Arc::new(Mutex::new(job_rx));
+            let mut handles = vec![];
+            for _ in 0..threads {
+                let job_rx_clone = Arc::clone(&job_rx);
+                let handle = thread::spawn(move || {
+                    let (value, reply_tx) = job_rx_clone.lock().unwrap().recv().unwrap();
+                    reply_tx.send(value * 2).unwrap();
+                });
+                handles.push(handle);
+            }
+            let mut replies = vec![];
+            for value in 0..threads as u64 {
+                let (reply_tx, reply_rx) = mpsc::channel();
+                job_tx.send((value, reply_tx)).unwrap();
+                replies.push((value, reply_rx));
+            }
+            for (value, reply_rx) in replies {
+                assert_eq!(reply_rx.recv().unwrap(), value * 2);
+            }
+            for handle in handles {
+                handle.join().unwrap();
+            }
+        });
+    });
+}
+
+/// Benchmark for request/response workloads using tokio oneshot reply channels.
+/// This function measures the time for the main task to send one job per
+/// worker task, each carrying its own oneshot Sender, to workers sharing one job
+/// channel, and to await every response.
+fn tokio_oneshot_request_response(c: &mut Criterion) {
+    let runtime = tokio_runtime();
+    bench_over_threads(c, "tokio_oneshot_request_response", |b, threads| {
+        b.to_async(&runtime).iter(|| async {
+            let (job_tx, job_rx) =
+                tokio::sync::mpsc::channel::<(u64, tokio::sync::oneshot::Sender<u64>)>(32);
+            let job_rx = Arc::new(tokio::sync::Mutex::new(job_rx));
+            let mut handles = vec![];
+            for _ in 0..threads {
+                let job_rx_clone = Arc::clone(&job_rx);
+                handles.push(tokio::spawn(async move {
+                    let (value, reply_tx) = job_rx_clone.lock().await.recv().await.unwrap();
+                    reply_tx.send(value * 2).unwrap();
+                }));
+            }
+            let mut replies = vec![];
+            for value in 0..threads as u64 {
+                let (reply_tx, reply_rx) = tokio::sync::oneshot::channel();
+                job_tx.send((value, reply_tx)).await.unwrap();
+                replies.push((value, reply_rx));
+            }
+            for (value, reply_rx) in replies {
+                assert_eq!(reply_rx.await.unwrap(), value * 2);
+            }
+            for handle in handles {
+                handle.await.unwrap();
+            }
+        });
+    });
+}
+
+/// Builds the rayon thread pool used by the rayon benchmarks.
+/// A pool is created once per thread count, outside the measured loop,
+/// with one thread per iteration index so pool construction is not measured.
+fn rayon_pool(threads: usize) -> rayon::ThreadPool {
+    rayon::ThreadPoolBuilder::new()
+        .num_threads(threads)
+        .build()
+        .unwrap()
+}
+
+/// Benchmark for read-heavy workloads using rayon parallel iteration over Arc<Mutex>.
+/// This function measures the performance of a rayon pool concurrently
+/// reading from an Arc-wrapped Mutex-protected integer via `into_par_iter`,
+/// with a delay added for every even iteration.
+fn rayon_read_heavy(c: &mut Criterion) {
+    let data = Arc::new(Mutex::new(READ_VALUE));
+    bench_lock_with_stalls(c, "rayon_read_heavy", |b, threads, ops, stall| {
+        let pool = rayon_pool(threads);
+        b.iter(|| {
+            let sums: Vec<u64> = pool.install(|| {
+                (0..threads)
+                    .into_par_iter()
+                    .map(|i| {
+                        let mut sum = 0;
+                        for op in 0..ops {
+                            let stall = stall.only_on_first(op);
+                            let guard = data.lock().unwrap();
+                            sum += black_box(*guard);
+                            stall.while_locked(i);
+                            drop(guard);
+                            stall.after_unlock(i);
+                        }
+                        sum
+                    })
+                    .collect()
+            });
+            assert_read_sums(&sums, ops);
+        });
+    });
+}
+
+/// Benchmark for write-heavy workloads using rayon parallel iteration over Arc<Mutex>.
+/// This function measures the performance of a rayon pool concurrently
+/// writing to an Arc-wrapped Mutex-protected integer via `into_par_iter`,
+/// with a delay added for every even iteration.
+fn rayon_write_heavy(c: &mut Criterion) {
+    let data = Arc::new(Mutex::new(0u64));
+    bench_lock_with_stalls(c, "rayon_write_heavy", |b, threads, ops, stall| {
+        let pool = rayon_pool(threads);
+        b.iter(|| {
+            let before = *data.lock().unwrap();
+            pool.install(|| {
+                (0..threads).into_par_iter().for_each(|i| {
+                    for op in 0..ops {
+                        let stall = stall.only_on_first(op);
+                        let mut num = data.lock().unwrap();
+                        *num += 1;
+                        stall.while_locked(i);
+                        drop(num);
+                        stall.after_unlock(i);
+                    }
+                });
+            });
+            assert_counter_delta(before, *data.lock().unwrap(), threads as u64 * ops);
+        });
+    });
+}
+
+/// Benchmark for write-heavy workloads using rayon parallel iteration over Arc<AtomicUsize>.
+/// This function measures the performance of a rayon pool concurrently
+/// incrementing an Arc-wrapped atomic integer via `into_par_iter`,
+/// with a delay added for every even iteration.
+fn rayon_atomic_write_heavy(c: &mut Criterion) {
+    let data = Arc::new(AtomicUsize::new(0));
+    bench_ops_with_stalls(c, "rayon_atomic_write_heavy", |b, threads, ops, stall| {
+        let pool = rayon_pool(threads);
+        b.iter(|| {
+            let before = data.load(Ordering::Relaxed);
+            pool.install(|| {
+                (0..threads).into_par_iter().for_each(|i| {
+                    for op in 0..ops {
+                        data.fetch_add(1, Ordering::Relaxed);
+                        stall.only_on_first(op).apply(i);
+                    }
+                });
+            });
+            assert_eq!(
+                data.load(Ordering::Relaxed),
+                before + threads * ops as usize
+            );
+        });
+    });
+}
+
+/// Benchmark for write-heavy workloads using Arc<Mutex> with scoped threads.
+/// This function measures the performance of multiple scoped threads
+/// concurrently writing to an Arc-wrapped Mutex-protected integer that they
+/// borrow instead of cloning the Arc, with a delay added for every even iteration.
+fn scoped_arc_mutex_write_heavy(c: &mut Criterion) {
+    let data = Arc::new(Mutex::new(0u64));
+    bench_lock_with_stalls(
+        c,
+        "scoped_arc_mutex_write_heavy",
+        |b, threads, ops, stall| {
+            b.iter(|| {
+                let before = *data.lock().unwrap();
+                thread::scope(|s| {
+                    for i in 0..threads {
+                        let data = &data;
+                        s.spawn(move || {
+                            for op in 0..ops {
+                                let stall = stall.only_on_first(op);
+                                let mut num = data.lock().unwrap();
+                                *num += 1;
+                                stall.while_locked(i);
+                                drop(num);
+                                stall.after_unlock(i);
+                            }
+                        });
+                    }
+                });
+                assert_counter_delta(before, *data.lock().unwrap(), threads as u64 * ops);
+            });
+        },
+    );
+}
+
+/// Benchmark for mixed read/write workloads using Arc<RwLock> with scoped threads.
+/// This function measures the performance of multiple scoped threads
+/// performing both reads and writes to an Arc-wrapped RwLock-protected integer
+/// that they borrow instead of cloning the Arc, with a delay added for every even iteration.
+fn scoped_arc_rwlock_mixed(c: &mut Criterion) {
+    let data = Arc::new(RwLock::new(0u64));
+    bench_lock_with_stalls(c, "scoped_arc_rwlock_mixed", |b, threads, ops, stall| {
+        let scenario = mixed_scenario(threads, ops);
+        let expected_writes = scenario.expected_writes();
+        b.iter(|| {
+            let before = *data.read().unwrap();
+            let sums: Vec<u64> = thread::scope(|s| {
+                let mut handles = vec![];
+                for i in 0..threads {
+                    let data = &data;
+                    let mut operations = scenario.operations(i);
+                    handles.push(s.spawn(move || {
+                        let mut sum = 0;
+                        for op in 0..ops {
+                            let stall = stall.only_on_first(op);
+                            if operations.next_is_write() {
+                                let mut num = data.write().unwrap();
+                                *num += 1;
+                                sum += *num;
+                                stall.while_locked(i);
+                                drop(num);
+                                stall.after_unlock(i);
+                            } else {
+                                let guard = data.read().unwrap();
+                                sum += black_box(*guard);
+                                stall.while_locked(i);
+                                drop(guard);
+                                stall.after_unlock(i);
+                            }
+                        }
+                        sum
+                    }));
+                }
+                handles
+                    .into_iter()
+                    .map(|handle| handle.join().unwrap())
+                    .collect()
+            });
+            let after = *data.read().unwrap();
+            assert_observed_sums(&sums, ops, before, after);
+            assert_counter_delta(before, after, expected_writes);
+        });
+    });
+}
+
+/// Benchmark for read-heavy workloads using Arc<Mutex> and a pre-spawned worker pool.
+/// This function measures the performance of long-lived worker threads
+/// concurrently reading from an Arc-wrapped Mutex-protected integer,
+/// with a delay added for every even iteration and no thread spawning in the measured loop.
+fn arc_mutex_read_heavy_pooled(c: &mut Criterion) {
+    let data = Arc::new(Mutex::new(READ_VALUE));
+    bench_lock_with_stalls(
+        c,
+        "arc_mutex_read_heavy_pooled",
+        |b, threads, ops, stall| {
+            let data = Arc::clone(&data);
+            let pool = WorkerPool::new(threads, move |i| {
+                let mut sum = 0;
+                for op in 0..ops {
+                    let stall = stall.only_on_first(op);
+                    let guard = data.lock().unwrap();
+                    sum += black_box(*guard);
+                    stall.while_locked(i);
+                    drop(guard);
+                    stall.after_unlock(i);
+                }
+                sum
+            });
+            b.iter(|| assert_read_sums(&pool.run(), ops));
+        },
+    );
+}
+
+/// Benchmark for write-heavy workloads using Arc<Mutex> and a pre-spawned worker pool.
+/// This function measures the performance of long-lived worker threads
+/// concurrently writing to an Arc-wrapped Mutex-protected integer,
+/// with a delay added for every even iteration and no thread spawning in the measured loop.
+fn arc_mutex_write_heavy_pooled(c: &mut Criterion) {
+    let data = Arc::new(Mutex::new(0u64));
+    bench_lock_with_stalls(
+        c,
+        "arc_mutex_write_heavy_pooled",
+        |b, threads, ops, stall| {
+            let shared = Arc::clone(&data);
+            let pool = WorkerPool::new(threads, move |i| {
+                for op in 0..ops {
+                    let stall = stall.only_on_first(op);
+                    let mut num = shared.lock().unwrap();
+                    *num += 1;
+                    stall.while_locked(i);
+                    drop(num);
+                    stall.after_unlock(i);
+                }
+            });
+            b.iter(|| {
+                let before = *data.lock().unwrap();
+                pool.run();
+                assert_counter_delta(before, *data.lock().unwrap(), threads as u64 * ops);
+            });
+        },
+    );
+}
+
+/// Benchmark for mixed read/write workloads using Arc<Mutex> and a pre-spawned worker pool.
+/// This function measures the performance of long-lived worker threads
+/// performing both reads and writes to an Arc-wrapped Mutex-protected integer,
+/// with a delay added for every even iteration and no thread spawning in the measured loop.
+fn arc_mutex_mixed_pooled(c: &mut Criterion) {
+    let data = Arc::new(Mutex::new(0u64));
+    bench_lock_with_stalls(c, "arc_mutex_mixed_pooled", |b, threads, ops, stall| {
+        let scenario = mixed_scenario(threads, ops);
+        let expected_writes = scenario.expected_writes();
+        let shared = Arc::clone(&data);
+        let pool = WorkerPool::new(threads, move |i| {
+            let mut operations = scenario.operations(i);
+            let mut sum = 0;
+            for op in 0..ops {
+                let stall = stall.only_on_first(op);
+                if operations.next_is_write() {
+                    let mut num = shared.lock().unwrap();
+                    *num += 1;
+                    sum += *num;
+                    stall.while_locked(i);
+                    drop(num);
+                    stall.after_unlock(i);
+                } else {
+                    let guard = shared.lock().unwrap();
+                    sum += black_box(*guard);
+                    stall.while_locked(i);
+                    drop(guard);
+                    stall.after_unlock(i);
+                }
+            }
+            sum
+        });
+        b.iter(|| {
+            let before = *data.lock().unwrap();
+            let sums = pool.run();
+            let after = *data.lock().unwrap();
+            assert_observed_sums(&sums, ops, before, after);
+            assert_counter_delta(before, after, expected_writes);
+        });
+    });
+}
+
+/// Benchmark for read-heavy workloads using Arc<RwLock> and a pre-spawned worker pool.
+/// This function measures the performance of long-lived worker threads
+/// concurrently reading from an Arc-wrapped RwLock-protected integer,
+/// with a delay added for every even iteration and no thread spawning in the measured loop.
+fn arc_rwlock_read_heavy_pooled(c: &mut Criterion) {
+    let data = Arc::new(RwLock::new(READ_VALUE));
+    bench_lock_with_stalls(
+        c,
+        "arc_rwlock_read_heavy_pooled",
+        |b, threads, ops, stall| {
+            let data = Arc::clone(&data);
+            let pool = WorkerPool::new(threads, move |i| {
+                let mut sum = 0;
+                for op in 0..ops {
+                    let stall = stall.only_on_first(op);
+                    let guard = data.read().unwrap();
+                    sum += black_box(*guard);
+                    stall.while_locked(i);
+                    drop(guard);
+                    stall.after_unlock(i);
+                }
+                sum
+            });
+            b.iter(|| assert_read_sums(&pool.run(), ops));
+        },
+    );
+}
+
+/// Benchmark for write-heavy workloads using Arc<RwLock> and a pre-spawned worker pool.
+/// This function measures the performance of long-lived worker threads
+/// concurrently writing to an Arc-wrapped RwLock-protected integer,
+/// with a delay added for every even iteration and no thread spawning in the measured loop.
+fn arc_rwlock_write_heavy_pooled(c: &mut Criterion) {
+    let data = Arc::new(RwLock::new(0u64));
+    bench_lock_with_stalls(
+        c,
+        "arc_rwlock_write_heavy_pooled",
+        |b, threads, ops, stall| {
+            let shared = Arc::clone(&data);
+            let pool = WorkerPool::new(threads, move |i| {
+                for op in 0..ops {
+                    let stall = stall.only_on_first(op);
+                    let mut num = shared.write().unwrap();
+                    *num += 1;
+                    stall.while_locked(i);
+                    drop(num);
+                    stall.after_unlock(i);
+                }
+            });
+            b.iter(|| {
+                let before = *data.read().unwrap();
+                pool.run();
+                assert_counter_delta(before, *data.read().unwrap(), threads as u64 * ops);
+            });
+        },
+    );
+}
+
+/// Benchmark for mixed read/write workloads using Arc<RwLock> and a pre-spawned worker pool.
+/// This function measures the performance of long-lived worker threads
+/// performing both reads and writes to an Arc-wrapped RwLock-protected integer,
+/// with a delay added for every even iteration and no thread spawning in the measured loop.
+fn arc_rwlock_mixed_pooled(c: &mut Criterion) {
+    let data = Arc::new(RwLock::new(0u64));
+    bench_lock_with_stalls(c, "arc_rwlock_mixed_pooled", |b, threads, ops, stall| {
+        let scenario = mixed_scenario(threads, ops);
+        let expected_writes = scenario.expected_writes();
+        let shared = Arc::clone(&data);
+        let pool = WorkerPool::new(threads, move |i| {
+            let mut operations = scenario.operations(i);
+            let mut sum = 0;
+            for op in 0..ops {
+                let stall = stall.only_on_first(op);
+                if operations.next_is_write() {
+                    let mut num = shared.write().unwrap();
+                    *num += 1;
+                    sum += *num;
+                    stall.while_locked(i);
+                    drop(num);
+                    stall.after_unlock(i);
+                } else {
+                    let guard = shared.read().unwrap();
+                    sum += black_box(*guard);
+                    stall.while_locked(i);
+                    drop(guard);
+                    stall.after_unlock(i);
+                }
+            }
+            sum
+        });
+        b.iter(|| {
+            let before = *data.read().unwrap();
+            let sums = pool.run();
+            let after = *data.read().unwrap();
+            assert_observed_sums(&sums, ops, before, after);
+            assert_counter_delta(before, after, expected_writes);
+        });
+    });
+}
+
+/// Queue state shared by the Condvar producer/consumer benchmark.
+/// `closed` is set once every producer has finished so that a consumer
+/// which somehow missed a notification wakes up and exits instead of hanging.
+struct CondvarQueue {
+    items: VecDeque<u64>,
+    closed: bool,
+}
+
+/// Benchmark for producer/consumer workloads using a Mutex<VecDeque> and a Condvar.
+/// This function measures the performance of producer threads pushing
+/// one item each and calling notify_one, while as many consumer threads wait on
+/// the Condvar until an item is available, with a delay added for every even iteration.
+/// Every consumer must receive exactly one item, so a lost wakeup fails the benchmark.
+fn condvar_producer_consumer(c: &mut Criterion) {
+    bench_with_stalls(c, "condvar_producer_consumer", |b, threads, stall| {
+        b.iter(|| {
+            let state = Arc::new((
+                Mutex::new(CondvarQueue {
+                    items: VecDeque::new(),
+                    closed: false,
+                }),
+                Condvar::new(),
+            ));
+            let mut consumers = vec![];
+            for i in 0..threads {
+                let state_clone = Arc::clone(&state);
+                let handle = thread::spawn(move || {
+                    let (queue, ready) = &*state_clone;
+                    let mut guard = ready
+                        .wait_while(queue.lock().unwrap(), |q| q.items.is_empty() && !q.closed)
+                        .unwrap();
+                    let item = guard.items.pop_front();
+                    drop(guard);
+                    stall.apply(i);
+                    item.is_some()
+                });
+                consumers.push(handle);
+            }
+            let mut producers = vec![];
+            for i in 0..threads {
+                let state_clone = Arc::clone(&state);
+                let handle = thread::spawn(move || {
+                    let (queue, ready) = &*state_clone;
+                    queue.lock().unwrap().items.push_back(i as u64);
+                    ready.notify_one();
+                    stall.apply(i);
+                });
+                producers.push(handle);
+            }
+            for handle in producers {
+                handle.join().unwrap();
+            }
+            {
+                let (queue, ready) = &*state;
+                queue.lock().unwrap().closed = true;
+                ready.notify_all();
+            }
+            let received = consumers
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .filter(|&got_item| got_item)
+                .count();
+            assert_eq!(received, threads);
+        });
+    });
+}
+
+/// Consumer threads waiting on the Condvar in the notify benchmarks.
+const NOTIFY_WAITERS: usize = 16;
+
+/// Items the producer pushes one at a time per iteration in the notify benchmarks.
+const NOTIFY_ITEMS: u64 = 1_000;
+
+/// Registers `condvar_notify/{name}`: `NOTIFY_WAITERS` consumers wait on a
+/// Condvar guarding a `CondvarQueue` while the benchmark thread pushes
+/// `NOTIFY_ITEMS` items one at a time, calling `notify` after each. Consumers
+/// count every wakeup that finds the queue empty before waiting again, and the
+/// average of those spurious wakeups per iteration is printed once measured.
+fn bench_condvar_notify(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    name: &str,
+    notify: fn(&Condvar),
+) {
+    let spurious = Arc::new(AtomicU64::new(0));
+    let mut iterations = 0u64;
+    group.bench_function(name, |b| {
+        b.iter(|| {
+            let state = Arc::new((
+                Mutex::new(CondvarQueue {
+                    items: VecDeque::new(),
+                    closed: false,
+                }),
+                Condvar::new(),
+            ));
+            let consumers: Vec<_> = (0..NOTIFY_WAITERS)
+                .map(|_| {
+                    let state = Arc::clone(&state);
+                    let spurious = Arc::clone(&spurious);
+                    thread::spawn(move || {
+                        let (queue, ready) = &*state;
+                        let mut received = 0u64;
+                        let mut guard = queue.lock().unwrap();
+                        loop {
+                            if guard.items.pop_front().is_some() {
+                                received += 1;
+                            } else if guard.closed {
+                                return received;
+                            } else {
+                                guard = ready.wait(guard).unwrap();
+                                if guard.items.is_empty() && !guard.closed {
+                                    spurious.fetch_add(1, Ordering::Relaxed);
+                                }
+                            }
+                        }
+                    })
+                })
+                .collect();
+            let (queue, ready) = &*state;
+            for item in 0..NOTIFY_ITEMS {
+                queue.lock().unwrap().items.push_back(item);
+                notify(ready);
+            }
+            queue.lock().unwrap().closed = true;
+            ready.notify_all();
+            let received: u64 = consumers
+                .into_iter()
+                .map(|handle| handle.join().unwrap())
+                .sum();
+            assert_eq!(received, NOTIFY_ITEMS);
+            iterations += 1;
+        });
+    });
+    if iterations > 0 {
+        println!(
+            "condvar_notify/{}: {:.1} spurious wakeups per iteration on average",
+            name,
+            spurious.load(Ordering::Relaxed) as f64 / iterations as f64
+        );
+    }
+}
+
+/// Benchmark for the thundering herd of waking every waiter for one item.
+/// This function measures 16 consumer threads waiting on a Condvar that
+/// guards a Mutex<VecDeque> while one producer pushes 1,000 items one at a
+/// time, notifying with `notify_one` or `notify_all` after each push. Besides
+/// the time to drain every item, it prints how many wakeups found the queue
+/// already empty, which `notify_all` multiplies since every waiter but one
+/// wakes for nothing. Consumers must receive all 1,000 items between them.
+fn condvar_notify(c: &mut Criterion) {
+    let mut group = c.benchmark_group("condvar_notify");
+    group.throughput(Throughput::Elements(NOTIFY_ITEMS));
+    bench_condvar_notify(&mut group, "notify_one", Condvar::notify_one);
+    bench_condvar_notify(&mut group, "notify_all", Condvar::notify_all);
+    group.finish();
+}
+
+/// Benchmark for phased workloads using std::sync::Barrier.
+/// This function measures the total time for a pre-spawned set of threads
+/// to pass through a shared Barrier 100 times, for 2, 4, 8, and 16 threads,
+/// so criterion plots how barrier wait cost scales with the thread count.
+fn barrier_wait(c: &mut Criterion) {
+    let mut group = c.benchmark_group("barrier_wait");
+    for threads in [2, 4, 8, 16] {
+        let barrier = Arc::new(Barrier::new(threads));
+        let pool = WorkerPool::new(threads, move |_| {
+            for _ in 0..100 {
+                barrier.wait();
+            }
+        });
+        group.throughput(Throughput::Elements(threads as u64 * 100));
+        group.bench_with_input(BenchmarkId::from_parameter(threads), &threads, |b, _| {
+            b.iter(|| pool.run());
+        });
+    }
+    group.finish();
+}
+
+/// Round trips the two threads of the handoff benchmarks make per iteration.
+const HANDOFF_ROUND_TRIPS: u64 = 100_000;
+
+/// Registers `handoff/{name}` with two pooled threads running `work` with
+/// their index, 0 or 1, every iteration. Each iteration is
+/// `HANDOFF_ROUND_TRIPS` context switches both ways, so the group takes
+/// criterion's minimum of 10 samples.
+fn bench_handoff(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    name: &str,
+    work: impl Fn(usize) + Send + Sync + 'static,
+) {
+    let pool = WorkerPool::new(2, work);
+    group.bench_function(name, |b| b.iter(|| pool.run()));
+}
+
+/// Benchmark for the raw cost of handing control from one thread to another.
+/// This function measures two pre-spawned threads passing a turn back and
+/// forth 100,000 times, by setting a turn flag and calling `unpark` on the
+/// other thread (`park_unpark`), by a rendezvous through a pair of
+/// zero-capacity `sync_channel`s (`sync_channel_rendezvous`), and by a turn
+/// under a Mutex with a Condvar (`condvar`). Throughput counts round trips,
+/// so the report reads as round trips per second, the floor for any blocking
+/// signal between threads on this machine.
+fn handoff(c: &mut Criterion) {
+    let mut group = c.benchmark_group("handoff");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(HANDOFF_ROUND_TRIPS));
+
+    // Whose turn it is, and each worker's Thread handle, which the workers
+    // record on their first run so the other one can unpark them.
+    let turn = AtomicUsize::new(0);
+    let threads: [OnceLock<thread::Thread>; 2] = [OnceLock::new(), OnceLock::new()];
+    bench_handoff(&mut group, "park_unpark", move |me| {
+        let _ = threads[me].set(thread::current());
+        let other = loop {
+            match threads[1 - me].get() {
+                Some(other) => break other,
+                None => thread::yield_now(),
+            }
+        };
+        for _ in 0..HANDOFF_ROUND_TRIPS {
+            // `park` can return spuriously, so the flag decides.
+            while turn.load(Ordering::Acquire) != me {
+                thread::park();
+            }
+            turn.store(1 - me, Ordering::Release);
+            other.unpark();
+        }
+    });
+
+    let (ping_tx, ping_rx) = mpsc::sync_channel(0);
+    let (pong_tx, pong_rx) = mpsc::sync_channel(0);
+    // Receivers are not Sync, so each sits behind a Mutex only its thread locks.
+    let (ping_rx, pong_rx) = (Mutex::new(ping_rx), Mutex::new(pong_rx));
+    bench_handoff(&mut group, "sync_channel_rendezvous", move |me| {
+        if me == 0 {
+            let pong_rx = pong_rx.lock().unwrap();
+            for _ in 0..HANDOFF_ROUND_TRIPS {
+                ping_tx.send(()).unwrap();
+                pong_rx.recv().unwrap();
+            }
+        } else {
+            let ping_rx = ping_rx.lock().unwrap();
+            for _ in 0..HANDOFF_ROUND_TRIPS {
+                ping_rx.recv().unwrap();
+                pong_tx.send(()).unwrap();
+            }
+        }
+    });
+
+    let turn = Mutex::new(0);
+    let turn_changed = Condvar::new();
+    bench_handoff(&mut group, "condvar", move |me| {
+        for _ in 0..HANDOFF_ROUND_TRIPS {
+            let mut turn = turn_changed
+                .wait_while(turn.lock().unwrap(), |turn| *turn != me)
+                .unwrap();
+            *turn = 1 - me;
+            turn_changed.notify_one();
+        }
+    });
+    group.finish();
+}
+
+/// Threads spawned per iteration in the completion benchmarks.
+const COMPLETION_THREADS: usize = 10;
+
+/// Increments each thread performs per iteration in the completion benchmarks.
+const COMPLETION_OPS: u64 = 1_000;
+
+/// Counts outstanding threads down to zero and wakes whoever is waiting once
+/// the last one finishes.
+struct Countdown {
+    remaining: AtomicUsize,
+    lock: Mutex<()>,
+    zero: Condvar,
+}
+
+impl Countdown {
+    fn new(count: usize) -> Self {
+        Countdown {
+            remaining: AtomicUsize::new(count),
+            lock: Mutex::new(()),
+            zero: Condvar::new(),
+        }
+    }
+
+    fn count_down(&self) {
+        if self.remaining.fetch_sub(1, Ordering::AcqRel) == 1 {
+            // Taking the lock before notifying means a waiter is either still
+            // before its check or already waiting, so the wakeup is not lost.
+            let _guard = self.lock.lock().unwrap();
+            self.zero.notify_all();
+        }
+    }
+
+    fn wait(&self) {
+        let mut guard = self.lock.lock().unwrap();
+        while self.remaining.load(Ordering::Acquire) != 0 {
+            guard = self.zero.wait(guard).unwrap();
+        }
+    }
+}
+
+/// Spawns `COMPLETION_THREADS` detached threads that each perform
+/// `COMPLETION_OPS` increments of `counter` and then run the completion
+/// callback `done_for_thread` made for them.
+fn spawn_detached_increments<D>(counter: &Arc<Mutex<u64>>, done_for_thread: impl Fn() -> D)
+where
+    D: FnOnce() + Send + 'static,
+{
+    for _ in 0..COMPLETION_THREADS {
+        let counter = Arc::clone(counter);
+        let done = done_for_thread();
+        thread::spawn(move || {
+            for _ in 0..COMPLETION_OPS {
+                *counter.lock().unwrap() += 1;
+            }
+            done();
+        });
+    }
+}
+
+/// Benchmark for how the spawning thread learns that its workers are done.
+/// This function measures 10 freshly spawned threads each performing 1,000
+/// increments of an Arc-wrapped Mutex-protected integer, finishing with one
+/// of four completion mechanisms: joining every JoinHandle in turn
+/// (`join_handles`), waiting on a crossbeam WaitGroup cloned into each thread
+/// (`wait_group`), waiting on a Barrier sized for the workers plus the
+/// spawning thread (`barrier`), or waiting on a Condvar that the thread
+/// bringing an AtomicUsize countdown to zero notifies (`countdown_condvar`).
+/// Each asserts the counter grew by every increment once the wait returns.
+/// Outside `join_handles` the threads are detached, so their exit can overlap
+/// the next iteration instead of being part of the measured wait.
+fn completion(c: &mut Criterion) {
+    let data = Arc::new(Mutex::new(0u64));
+    let expected = COMPLETION_THREADS as u64 * COMPLETION_OPS;
+    let mut group = c.benchmark_group("completion");
+    group.throughput(Throughput::Elements(expected));
+    group.bench_function("join_handles", |b| {
+        b.iter(|| {
+            let before = *data.lock().unwrap();
+            let handles: Vec<_> = (0..COMPLETION_THREADS)
+                .map(|_| {
+                    let counter = Arc::clone(&data);
+                    thread::spawn(move || {
+                        for _ in 0..COMPLETION_OPS {
+                            *counter.lock().unwrap() += 1;
+                        }
+                    })
+                })
+                .collect();
+            for handle in handles {
+                handle.join().unwrap();
+            }
+            assert_counter_delta(before, *data.lock().unwrap(), expected);
+        });
+    });
+    group.bench_function("wait_group", |b| {
+        b.iter(|| {
+            let before = *data.lock().unwrap();
+            let wait_group = WaitGroup::new();
+            spawn_detached_increments(&data, || {
+                let worker = wait_group.clone();
+                move || drop(worker)
+            });
+            wait_group.wait();
+            assert_counter_delta(before, *data.lock().unwrap(), expected);
+        });
+    });
+    group.bench_function("barrier", |b| {
+        b.iter(|| {
+            let before = *data.lock().unwrap();
+            let barrier = Arc::new(Barrier::new(COMPLETION_THREADS + 1));
+            spawn_detached_increments(&data, || {
+                let worker = Arc::clone(&barrier);
+                move || {
+                    worker.wait();
+                }
+            });
+            barrier.wait();
+            assert_counter_delta(before, *data.lock().unwrap(), expected);
+        });
+    });
+    group.bench_function("countdown_condvar", |b| {
+        b.iter(|| {
+            let before = *data.lock().unwrap();
+            let countdown = Arc::new(Countdown::new(COMPLETION_THREADS));
+            spawn_detached_increments(&data, || {
+                let worker = Arc::clone(&countdown);
+                move || worker.count_down()
+            });
+            countdown.wait();
+            assert_counter_delta(before, *data.lock().unwrap(), expected);
+        });
+    });
+    group.finish();
+}
+
+/// Simulates an expensive one-time setup step taking roughly 1ms.
+/// Used by the lazy-initialization benchmarks as the initializer every thread races to run.
+fn expensive_init() -> Vec<u64> {
+    thread::sleep(Duration::from_millis(1));
+    (0..1000).collect()
+}
+
+/// Benchmark for lazy initialization races using Arc<OnceLock>.
+/// This function measures how long it takes until multiple threads racing to call
+/// get_or_init on a fresh OnceLock all have the value, where the initializer
+/// takes roughly 1ms to simulate expensive setup.
+fn oncelock_init_race(c: &mut Criterion) {
+    bench_over_threads(c, "oncelock_init_race", |b, threads| {
+        b.iter(|| {
+            let cell = Arc::new(OnceLock::new());
+            spawn_and_join(threads, move |_| {
+                let value = cell.get_or_init(expensive_init);
+                assert_eq!(value.len(), 1000);
+            });
+        });
+    });
+}
+
+/// Benchmark for lazy initialization races using a double-checked Mutex<Option>.
+/// This function measures how long it takes until multiple threads racing to
+/// initialize a fresh Mutex<Option> all have the value, using an AtomicBool
+/// fast-path check before taking the lock and re-checking under it.
+fn mutex_option_init_race(c: &mut Criterion) {
+    bench_over_threads(c, "mutex_option_init_race", |b, threads| {
+        b.iter(|| {
+            let state = Arc::new((AtomicBool::new(false), Mutex::new(None)));
+            spawn_and_join(threads, move |_| {
+                let (ready, slot) = &*state;
+                if !ready.load(Ordering::Acquire) {
+                    let mut guard = slot.lock().unwrap();
+                    if guard.is_none() {
+                        *guard = Some(Arc::new(expensive_init()));
+                        ready.store(true, Ordering::Release);
+                    }
+                }
+                let value = Arc::clone(slot.lock().unwrap().as_ref().unwrap());
+                assert_eq!(value.len(), 1000);
+            });
+        });
+    });
+}
+
+/// Minimal map interface shared by the map benchmarks, so each map
+/// implementation runs exactly the same workload body.
+trait SharedMap: Send + Sync + 'static {
+    fn get(&self, key: u64) -> Option<u64>;
+    fn insert(&self, key: u64, value: u64);
+}
+
+impl SharedMap for Mutex<HashMap<u64, u64>> {
+    fn get(&self, key: u64) -> Option<u64> {
+        self.lock().unwrap().get(&key).copied()
+    }
+
+    fn insert(&self, key: u64, value: u64) {
+        self.lock().unwrap().insert(key, value);
+    }
+}
+
+impl SharedMap for RwLock<HashMap<u64, u64>> {
+    fn get(&self, key: u64) -> Option<u64> {
+        self.read().unwrap().get(&key).copied()
+    }
+
+    fn insert(&self, key: u64, value: u64) {
+        self.write().unwrap().insert(key, value);
+    }
+}
+
+impl SharedMap for DashMap<u64, u64> {
+    fn get(&self, key: u64) -> Option<u64> {
+        DashMap::get(self, &key).map(|entry| *entry)
+    }
+
+    fn insert(&self, key: u64, value: u64) {
+        DashMap::insert(self, key, value);
+    }
+}
+
+/// Number of keys every map is pre-populated with before the benchmark runs.
+const MAP_KEYS: u64 = 1000;
+
+/// Base seed for the per-thread RNGs in the map benchmarks.
+/// Each thread derives its RNG from this seed plus its index, so every map
+/// implementation sees exactly the same key and operation sequence.
+const MAP_SEED: u64 = 0x5eed;
+
+/// How the map benchmarks pick the key for each operation.
+#[derive(Clone, Copy, Debug)]
+enum AccessDistribution {
+    /// Every key is equally likely.
+    Uniform,
+    /// Key `k` is drawn with probability proportional to `1 / (k + 1)^s`, so
+    /// a handful of low keys take most of the traffic, like a real cache.
+    Zipfian { s: f64 },
+    /// Key 0 takes `fraction` of the operations and the rest are uniform.
+    SingleHotKey { fraction: f64 },
+}
+
+impl AccessDistribution {
+    /// Suffix for benchmark ids. Uniform has none, so its ids match the ones
+    /// from before distributions existed.
+    fn id_suffix(self) -> String {
+        match self {
+            AccessDistribution::Uniform => String::new(),
+            AccessDistribution::Zipfian { s } => format!("_zipfian_{:.1}", s),
+            AccessDistribution::SingleHotKey { fraction } => {
+                format!("_hot_key_{}pct", (fraction * 100.0).round())
+            }
+        }
+    }
+
+    /// The distribution of keys in `0..MAP_KEYS`.
+    fn keys(self) -> MapKeys {
+        match self {
+            AccessDistribution::Uniform => MapKeys::Uniform,
+            AccessDistribution::Zipfian { s } => MapKeys::Zipfian(
+                Zipf::new(MAP_KEYS as f64, s).expect("the Zipf exponent is non-negative"),
+            ),
+            AccessDistribution::SingleHotKey { fraction } => MapKeys::SingleHotKey(fraction),
+        }
+    }
+}
+
+/// A ready-to-sample `AccessDistribution`.
+enum MapKeys {
+    Uniform,
+    Zipfian(Zipf<f64>),
+    SingleHotKey(f64),
+}
+
+impl Distribution<u64> for MapKeys {
+    fn sample<R: Rng + ?Sized>(&self, rng: &mut R) -> u64 {
+        match self {
+            MapKeys::Uniform => rng.random_range(0..MAP_KEYS),
+            // Zipf ranks run from 1 to MAP_KEYS, with rank 1 the most frequent.
+            MapKeys::Zipfian(zipf) => zipf.sample(rng) as u64 - 1,
+            MapKeys::SingleHotKey(fraction) => {
+                if rng.random_bool(*fraction) {
+                    0
+                } else {
+                    rng.random_range(0..MAP_KEYS)
+                }
+            }
+        }
+    }
+}
+
+/// The key distributions every map workload runs under.
+const MAP_DISTRIBUTIONS: [AccessDistribution; 3] = [
+    AccessDistribution::Uniform,
+    AccessDistribution::Zipfian { s: 1.0 },
+    AccessDistribution::SingleHotKey { fraction: 0.9 },
+];
+
+/// Runs `threads` threads that each perform 100 operations against `map`, where
+/// `write_percent` of the operations are inserts and the rest are gets, on keys
+/// drawn from `distribution`.
+fn run_map_workload<M: SharedMap>(
+    map: &Arc<M>,
+    threads: usize,
+    write_percent: u32,
+    distribution: AccessDistribution,
+) {
+    let map = Arc::clone(map);
+    spawn_and_join(threads, move |i| {
+        let mut rng = StdRng::seed_from_u64(MAP_SEED + i as u64);
+        let keys = distribution.keys();
+        for _ in 0..100 {
+            let key = keys.sample(&mut rng);
+            if rng.random_range(0..100) < write_percent {
+                map.insert(key, i as u64);
+            } else {
+                black_box(map.get(key));
+            }
+        }
+    });
+}
+
+/// Benchmark for shared-map workloads comparing Mutex<HashMap>, RwLock<HashMap>, and DashMap.
+/// This function measures the performance of threads performing 100
+/// operations each against a map of 1,000 pre-populated keys, for read-heavy
+/// (90% get), write-heavy (90% insert), and mixed (50% insert) workloads,
+/// under uniform, Zipfian (s = 1.0), and single-hot-key (90%) key access,
+/// registered as ids such as `dashmap/mixed/8` and `dashmap/mixed_zipfian_1.0/8`.
+/// Hot keys concentrate DashMap's traffic on a few shards, which is where its
+/// sharding stops helping.
+fn shared_map(c: &mut Criterion) {
+    let prepopulated = || (0..MAP_KEYS).map(|key| (key, key));
+    let mutex_map = Arc::new(Mutex::new(prepopulated().collect::<HashMap<_, _>>()));
+    let rwlock_map = Arc::new(RwLock::new(prepopulated().collect::<HashMap<_, _>>()));
+    let dash_map = Arc::new(prepopulated().collect::<DashMap<_, _>>());
+    let mut group = c.benchmark_group("shared_map");
+    for (workload, write_percent) in [("read_heavy", 10), ("write_heavy", 90), ("mixed", 50)] {
+        for distribution in MAP_DISTRIBUTIONS {
+            let workload = format!("{}{}", workload, distribution.id_suffix());
+            for &threads in thread_counts() {
+                group.throughput(Throughput::Elements(threads as u64 * 100));
+                let id = |map| BenchmarkId::new(format!("{}/{}", map, workload), threads);
+                group.bench_with_input(id("mutex_hashmap"), &threads, |b, &threads| {
+                    b.iter(|| run_map_workload(&mutex_map, threads, write_percent, distribution))
+                });
+                group.bench_with_input(id("rwlock_hashmap"), &threads, |b, &threads| {
+                    b.iter(|| run_map_workload(&rwlock_map, threads, write_percent, distribution))
+                });
+                group.bench_with_input(id("dashmap"), &threads, |b, &threads| {
+                    b.iter(|| run_map_workload(&dash_map, threads, write_percent, distribution))
+                });
+            }
+        }
+    }
+    group.finish();
+}
+
+/// Benchmark for counter workloads comparing Arc<Mutex<u64>> with a sharded counter.
+/// This function measures threads performing 1,000 operations each, either
+/// all increments (write-heavy) or one increment per ten operations with the
+/// rest reading the total (read-heavy), registered as ids such as
+/// `sharded/read_heavy/8`, and asserts the final total matches the number of
+/// increments after every iteration. The sharded counter has one shard per thread.
+fn sharded_counter(c: &mut Criterion) {
+    let mut group = c.benchmark_group("sharded_counter");
+    for (workload, write_every) in [("write_heavy", 1), ("read_heavy", 10)] {
+        for &threads in thread_counts() {
+            group.throughput(Throughput::Elements(threads as u64 * 1000));
+            let expected = threads as u64 * 1000 / write_every;
+            let id = |counter| BenchmarkId::new(format!("{}/{}", counter, workload), threads);
+            let mutex_counter = Arc::new(Mutex::new(0u64));
+            group.bench_function(id("mutex"), |b| {
+                b.iter(|| {
+                    let before = *mutex_counter.lock().unwrap();
+                    let counter = Arc::clone(&mutex_counter);
+                    spawn_and_join(threads, move |_| {
+                        for op in 0..1000u64 {
+                            if op.is_multiple_of(write_every) {
+                                *counter.lock().unwrap() += 1;
+                            } else {
+                                black_box(*counter.lock().unwrap());
+                            }
+                        }
+                    });
+                    assert_eq!(*mutex_counter.lock().unwrap(), before + expected);
+                });
+            });
+            let sharded = Arc::new(ShardedCounter::new(threads));
+            group.bench_function(id("sharded"), |b| {
+                b.iter(|| {
+                    let before = sharded.sum();
+                    let counter = Arc::clone(&sharded);
+                    spawn_and_join(threads, move |i| {
+                        for op in 0..1000u64 {
+                            if op.is_multiple_of(write_every) {
+                                counter.increment(i);
+                            } else {
+                                black_box(counter.sum());
+                            }
+                        }
+                    });
+                    assert_eq!(sharded.sum(), before + expected);
+                });
+            });
+        }
+    }
+    group.finish();
+}
+
+/// Threads in the lock striping benchmark.
+const STRIPING_THREADS: usize = 16;
+
+/// Keyed increments each striping thread performs per iteration.
+const STRIPING_OPS: u64 = 1000;
+
+/// Distinct keys the striping threads increment.
+const STRIPING_KEYS: u64 = 1024;
+
+/// Stripe counts compared, from one big lock to far more stripes than threads.
+const STRIPE_COUNTS: [usize; 5] = [1, 4, 16, 64, 256];
+
+/// Has `STRIPING_THREADS` threads each increment `STRIPING_OPS` counters at
+/// keys drawn uniformly from `STRIPING_KEYS` with a per-thread seed.
+fn keyed_increments(increment: impl Fn(u64) + Send + Sync + 'static) {
+    let increment = Arc::new(increment);
+    spawn_and_join(STRIPING_THREADS, move |i| {
+        let mut rng = StdRng::seed_from_u64(MAP_SEED + i as u64);
+        for _ in 0..STRIPING_OPS {
+            increment(rng.random_range(0..STRIPING_KEYS));
+        }
+    });
+}
+
+/// Benchmark for lock striping with a configurable number of stripes.
+/// This function measures 16 threads each performing 1,000 increments of
+/// per-key counters, with keys spread over 1,024 values, in a StripedMutex of
+/// HashMaps at 1, 4, 16, 64, and 256 stripes and in a DashMap with the same
+/// number of shards. Ids such as `striped_mutex/64` carry the stripe count as
+/// the parameter, so each variant's scaling curve lands in one chart; DashMap
+/// needs at least two shards, so it has no point at one stripe. Every
+/// iteration asserts that the counters sum to the number of increments.
+fn lock_striping(c: &mut Criterion) {
+    let expected = STRIPING_THREADS as u64 * STRIPING_OPS;
+    let mut group = c.benchmark_group("lock_striping");
+    group.throughput(Throughput::Elements(expected));
+    for stripes in STRIPE_COUNTS {
+        let striped = Arc::new(StripedMutex::<HashMap<u64, u64>>::new(stripes));
+        let sum = |striped: &StripedMutex<HashMap<u64, u64>>| {
+            striped.fold(0, |sum, stripe| sum + stripe.values().sum::<u64>())
+        };
+        group.bench_with_input(
+            BenchmarkId::new("striped_mutex", stripes),
+            &stripes,
+            |b, _| {
+                b.iter(|| {
+                    let before = sum(&striped);
+                    let map = Arc::clone(&striped);
+                    keyed_increments(move |key| *map.lock(&key).entry(key).or_insert(0) += 1);
+                    assert_eq!(sum(&striped), before + expected);
+                });
+            },
+        );
+        if stripes < 2 {
+            continue;
+        }
+        let dash_map = Arc::new(DashMap::<u64, u64>::with_shard_amount(stripes));
+        group.bench_with_input(BenchmarkId::new("dashmap", stripes), &stripes, |b, _| {
+            b.iter(|| {
+                let before: u64 = dash_map.iter().map(|entry| *entry.value()).sum();
+                let map = Arc::clone(&dash_map);
+                keyed_increments(move |key| *map.entry(key).or_insert(0) += 1);
+                let after: u64 = dash_map.iter().map(|entry| *entry.value()).sum();
+                assert_eq!(after, before + expected);
+            });
+        });
+    }
+    group.finish();
+}
+
+/// Has one scoped thread per counter increment its own counter 100,000 times
+/// in a tight loop, then returns the total across all counters.
+fn increment_own_counters(counters: &[&AtomicU64]) -> u64 {
+    thread::scope(|s| {
+        for &counter in counters {
+            s.spawn(move || {
+                for _ in 0..100_000 {
+                    counter.fetch_add(1, Ordering::Relaxed);
+                }
+            });
+        }
+    });
+    counters
+        .iter()
+        .map(|counter| counter.load(Ordering::Relaxed))
+        .sum()
+}
+
+/// Benchmark for cache-line effects using per-thread atomic counters.
+/// This function measures 8 threads each incrementing their own AtomicU64,
+/// once with the counters packed contiguously in a Vec so they share cache
+/// lines and once with each counter wrapped in CachePadded. The difference
+/// between the two is the false-sharing penalty, and both must produce the same total.
+fn false_sharing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("false_sharing");
+    group.throughput(Throughput::Elements(8 * 100_000));
+    group.bench_function("packed", |b| {
+        b.iter(|| {
+            let counters: Vec<AtomicU64> = (0..8).map(|_| AtomicU64::new(0)).collect();
+            let refs: Vec<&AtomicU64> = counters.iter().collect();
+            assert_eq!(increment_own_counters(&refs), 8 * 100_000);
+        });
+    });
+    group.bench_function("padded", |b| {
+        b.iter(|| {
+            let counters: Vec<CachePadded<AtomicU64>> = (0..8)
+                .map(|_| CachePadded::new(AtomicU64::new(0)))
+                .collect();
+            let refs: Vec<&AtomicU64> = counters.iter().map(|counter| &**counter).collect();
+            assert_eq!(increment_own_counters(&refs), 8 * 100_000);
+        });
+    });
+    group.finish();
+}
+
+/// Threads in the Arc reference count benchmarks.
+const REFCOUNT_THREADS: usize = 8;
+
+/// Clones each refcount thread makes and drops per iteration.
+const REFCOUNT_CLONES: u64 = 1_000_000;
+
+/// Has one scoped thread per Arc clone and drop its Arc `REFCOUNT_CLONES`
+/// times, then asserts every Arc is back to its one original reference.
+fn clone_and_drop_arcs(arcs: &[&Arc<CachePadded<u64>>]) {
+    thread::scope(|s| {
+        for &arc in arcs {
+            s.spawn(move || {
+                for _ in 0..REFCOUNT_CLONES {
+                    drop(black_box(Arc::clone(arc)));
+                }
+            });
+        }
+    });
+    for arc in arcs {
+        assert_eq!(Arc::strong_count(arc), 1);
+    }
+}
+
+/// Benchmark for Arc reference count contention with no lock involved.
+/// This function measures 8 threads each cloning and dropping an Arc
+/// 1,000,000 times, either all the same Arc (`shared`), so every clone and
+/// drop contends for one reference count, or each its own (`private`). The
+/// value is CachePadded so the private Arcs' counts never share a cache
+/// line. The difference is the refcount traffic hidden in every Arc::clone
+/// the other benchmarks make, and both assert only the original references
+/// remain afterwards. Each iteration makes 8 million clones, so the group
+/// takes criterion's minimum of 10 samples.
+fn arc_refcount(c: &mut Criterion) {
+    let mut group = c.benchmark_group("arc_refcount");
+    group.sample_size(10);
+    group.throughput(Throughput::Elements(
+        REFCOUNT_THREADS as u64 * REFCOUNT_CLONES,
+    ));
+    let shared = Arc::new(CachePadded::new(0u64));
+    group.bench_function("shared", |b| {
+        let arcs: Vec<_> = (0..REFCOUNT_THREADS).map(|_| &shared).collect();
+        b.iter(|| clone_and_drop_arcs(&arcs));
+    });
+    let private: Vec<_> = (0..REFCOUNT_THREADS)
+        .map(|_| Arc::new(CachePadded::new(0u64)))
+        .collect();
+    group.bench_function("private", |b| {
+        let arcs: Vec<_> = private.iter().collect();
+        b.iter(|| clone_and_drop_arcs(&arcs));
+    });
+    group.finish();
+}
+
+/// Minimal queue interface shared by the queue benchmarks, so each queue
+/// implementation runs exactly the same producer/consumer body.
+trait SharedQueue: Sync {
+    fn push(&self, value: u64);
+    fn pop(&self) -> Option<u64>;
+}
+
+impl SharedQueue for Mutex<VecDeque<u64>> {
+    fn push(&self, value: u64) {
+        self.lock().unwrap().push_back(value);
+    }
+
+    fn pop(&self) -> Option<u64> {
+        self.lock().unwrap().pop_front()
+    }
+}
+
+impl SharedQueue for SegQueue<u64> {
+    fn push(&self, value: u64) {
+        SegQueue::push(self, value);
+    }
+
+    fn pop(&self) -> Option<u64> {
+        SegQueue::pop(self)
+    }
+}
+
+impl SharedQueue for ArrayQueue<u64> {
+    /// Spins until there is room, since a bounded queue rejects pushes when full.
+    fn push(&self, value: u64) {
+        let mut value = value;
+        while let Err(rejected) = ArrayQueue::push(self, value) {
+            value = rejected;
+            std::hint::spin_loop();
+        }
+    }
+
+    fn pop(&self) -> Option<u64> {
+        ArrayQueue::pop(self)
+    }
+}
+
+/// Runs 5 producer threads pushing 1,000 items each and 5 consumer threads
+/// popping until all 5,000 items have been drained, then checks that no item
+/// was lost or duplicated.
+fn run_queue_workload<Q: SharedQueue>(queue: &Q) {
+    let popped = AtomicUsize::new(0);
+    let popped_sum = AtomicU64::new(0);
+    thread::scope(|s| {
+        for producer in 0..5u64 {
+            s.spawn(move || {
+                for item in 0..1000 {
+                    queue.push(producer * 1000 + item);
+                }
+            });
+        }
+        for _ in 0..5 {
+            s.spawn(|| {
+                while popped.load(Ordering::Relaxed) < 5000 {
+                    match queue.pop() {
+                        Some(item) => {
+                            popped.fetch_add(1, Ordering::Relaxed);
+                            popped_sum.fetch_add(item, Ordering::Relaxed);
+                        }
+                        None => std::hint::spin_loop(),
+                    }
+                }
+            });
+        }
+    });
+    assert_eq!(popped.load(Ordering::Relaxed), 5000);
+    assert_eq!(popped_sum.load(Ordering::Relaxed), (0..5000).sum::<u64>());
+    assert!(queue.pop().is_none());
+}
+
+/// Benchmark for producer/consumer queues comparing Mutex<VecDeque>, SegQueue, and ArrayQueue.
+/// This function measures 5 producer threads pushing 1,000 items each while
+/// 5 consumer threads pop until everything is drained, and fails the benchmark
+/// if the popped-item count or sum does not match what was pushed.
+fn lock_free_queues(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lock_free_queues");
+    group.throughput(Throughput::Elements(5 * 1000));
+    let mutex_queue = Mutex::new(VecDeque::new());
+    group.bench_function("mutex_vecdeque", |b| {
+        b.iter(|| run_queue_workload(&mutex_queue));
+    });
+    let seg_queue = SegQueue::new();
+    group.bench_function("seg_queue", |b| {
+        b.iter(|| run_queue_workload(&seg_queue));
+    });
+    let array_queue = ArrayQueue::new(1024);
+    group.bench_function("array_queue", |b| {
+        b.iter(|| run_queue_workload(&array_queue));
+    });
+    group.finish();
+}
+
+/// A small unit of work for the work-distribution benchmarks.
+/// Executing it records completion so every task can be checked to have run exactly once.
+struct Task(u64);
+
+impl Task {
+    fn execute(self, completed: &AtomicUsize, completed_sum: &AtomicU64) {
+        completed.fetch_add(1, Ordering::Relaxed);
+        completed_sum.fetch_add(criterion::black_box(self.0), Ordering::Relaxed);
+    }
+}
+
+/// Benchmark for work distribution comparing crossbeam-deque work stealing with a shared Mutex<Vec>.
+/// This function measures the time for 8 worker threads to drain 10,000 small
+/// tasks pushed by one owner thread, either by stealing batches from a
+/// crossbeam_deque::Worker or by popping from a single shared Mutex<Vec<Task>>,
+/// and asserts every task was executed exactly once.
+fn work_stealing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("work_stealing");
+    group.throughput(Throughput::Elements(10_000));
+    group.bench_function("crossbeam_deque", |b| {
+        b.iter(|| {
+            let completed = AtomicUsize::new(0);
+            let completed_sum = AtomicU64::new(0);
+            let owner = Worker::new_fifo();
+            let stealer = owner.stealer();
+            thread::scope(|s| {
+                s.spawn(move || {
+                    for id in 0..10_000 {
+                        owner.push(Task(id));
+                    }
+                });
+                for _ in 0..8 {
+                    let stealer = stealer.clone();
+                    let completed = &completed;
+                    let completed_sum = &completed_sum;
+                    s.spawn(move || {
+                        let local = Worker::new_fifo();
+                        while completed.load(Ordering::Relaxed) < 10_000 {
+                            match stealer.steal_batch_and_pop(&local) {
+                                Steal::Success(task) => {
+                                    task.execute(completed, completed_sum);
+                                    while let Some(task) = local.pop() {
+                                        task.execute(completed, completed_sum);
+                                    }
+                                }
+                                Steal::Empty | Steal::Retry => std::hint::spin_loop(),
+                            }
+                        }
+                    });
+                }
+            });
+            assert_eq!(completed.load(Ordering::Relaxed), 10_000);
+            assert_eq!(
+                completed_sum.load(Ordering::Relaxed),
+                (0..10_000).sum::<u64>()
+            );
+        });
+    });
+    group.bench_function("mutex_vec", |b| {
+        b.iter(|| {
+            let completed = AtomicUsize::new(0);
+            let completed_sum = AtomicU64::new(0);
+            let tasks = Mutex::new(Vec::new());
+            thread::scope(|s| {
+                s.spawn(|| {
+                    for id in 0..10_000 {
+                        tasks.lock().unwrap().push(Task(id));
+                    }
+                });
+                for _ in 0..8 {
+                    s.spawn(|| {
+                        while completed.load(Ordering::Relaxed) < 10_000 {
+                            let task = tasks.lock().unwrap().pop();
+                            match task {
+                                Some(task) => task.execute(&completed, &completed_sum),
+                                None => std::hint::spin_loop(),
+                            }
+                        }
+                    });
+                }
+            });
+            assert_eq!(completed.load(Ordering::Relaxed), 10_000);
+            assert_eq!(
+                completed_sum.load(Ordering::Relaxed),
+                (0..10_000).sum::<u64>()
+            );
+        });
+    });
+    group.finish();
+}
+
+/// Minimal stack interface shared by the stack benchmarks, so each stack
+/// implementation runs exactly the same push/pop body.
+trait SharedStack: Sync {
+    fn push(&self, value: u64);
+    fn pop(&self) -> Option<u64>;
+}
+
+impl SharedStack for Mutex<Vec<u64>> {
+    fn push(&self, value: u64) {
+        self.lock().unwrap().push(value);
+    }
+
+    fn pop(&self) -> Option<u64> {
+        self.lock().unwrap().pop()
+    }
+}
+
+impl SharedStack for TreiberStack<u64> {
+    fn push(&self, value: u64) {
+        TreiberStack::push(self, value);
+    }
+
+    fn pop(&self) -> Option<u64> {
+        TreiberStack::pop(self)
+    }
+}
+
+/// Runs `threads` threads that each interleave 1,000 pushes and pops on `stack`,
+/// then drains what is left and checks that pushes minus successful pops
+/// equals the remaining depth, so lost or duplicated nodes fail the benchmark.
+fn run_stack_workload<S: SharedStack>(stack: &S, threads: u64) {
+    let pushes = AtomicUsize::new(0);
+    let pops = AtomicUsize::new(0);
+    thread::scope(|s| {
+        for i in 0..threads {
+            let (pushes, pops) = (&pushes, &pops);
+            s.spawn(move || {
+                for op in 0..1000u64 {
+                    if op.is_multiple_of(2) {
+                        stack.push(i * 1000 + op);
+                        pushes.fetch_add(1, Ordering::Relaxed);
+                    } else if stack.pop().is_some() {
+                        pops.fetch_add(1, Ordering::Relaxed);
+                    }
+                }
+            });
+        }
+    });
+    let mut remaining = 0;
+    while stack.pop().is_some() {
+        remaining += 1;
+    }
+    assert_eq!(
+        pushes.load(Ordering::Relaxed) - pops.load(Ordering::Relaxed),
+        remaining
+    );
+}
+
+/// Benchmark for lock-free stacks comparing a Treiber stack with Arc<Mutex<Vec>>.
+/// This function measures threads doing interleaved push/pop of 1,000
+/// elements each on an epoch-reclaimed Treiber stack and on a Mutex-protected Vec,
+/// registered as ids such as `treiber/8`, verifying afterwards that no element
+/// was lost or duplicated.
+fn treiber_stack(c: &mut Criterion) {
+    let mut group = c.benchmark_group("treiber_stack");
+    let treiber = Arc::new(TreiberStack::new());
+    let mutex_vec = Arc::new(Mutex::new(Vec::new()));
+    for &threads in thread_counts() {
+        let threads = threads as u64;
+        group.throughput(Throughput::Elements(threads * 1000));
+        group.bench_with_input(
+            BenchmarkId::new("treiber", threads),
+            &threads,
+            |b, &threads| {
+                b.iter(|| run_stack_workload(&*treiber, threads));
+            },
+        );
+        group.bench_with_input(
+            BenchmarkId::new("mutex_vec", threads),
+            &threads,
+            |b, &threads| {
+                b.iter(|| run_stack_workload(&*mutex_vec, threads));
+            },
+        );
+    }
+    group.finish();
+}
+
+/// Runs 8 reader threads taking `read` in a tight loop for a 50ms window while
+/// 2 writer threads each try to take `write` once, recording how long each
+/// writer waited for its first acquisition into `waits`.
+/// Writers only start requesting once every reader is inside its loop.
+fn run_writer_starvation<R, W>(read: R, write: W, waits: &Mutex<Vec<Duration>>)
+where
+    R: Fn() + Sync,
+    W: Fn() + Sync,
+{
+    let readers_running = AtomicUsize::new(0);
+    let window = Duration::from_millis(50);
+    thread::scope(|s| {
+        for _ in 0..8 {
+            s.spawn(|| {
+                let started = Instant::now();
+                read();
+                readers_running.fetch_add(1, Ordering::Release);
+                while started.elapsed() < window {
+                    read();
+                }
+            });
+        }
+        for _ in 0..2 {
+            s.spawn(|| {
+                while readers_running.load(Ordering::Acquire) < 8 {
+                    std::hint::spin_loop();
+                }
+                let requested = Instant::now();
+                write();
+                waits.lock().unwrap().push(requested.elapsed());
+            });
+        }
+    });
+}
+
+/// Prints the mean and maximum writer wait collected by `run_writer_starvation`.
+fn report_writer_waits(name: &str, waits: &Mutex<Vec<Duration>>) {
+    let waits = waits.lock().unwrap();
+    if let Some(max) = waits.iter().max() {
+        let mean = waits.iter().sum::<Duration>() / waits.len() as u32;
+        println!(
+            "{}: writer wait for first acquisition mean {:?}, max {:?} over {} writers",
+            name,
+            mean,
+            max,
+            waits.len()
+        );
+    }
+}
+
+/// Threads in the parking_lot downgrade and upgradable read benchmarks.
+const UPGRADE_THREADS: usize = 8;
+
+/// Operations each thread performs per iteration in the downgrade and
+/// upgradable read benchmarks.
+const UPGRADE_OPS: u64 = 1_000;
+
+/// Whether the check in the upgradable read benchmarks fails on operation
+/// `op`, which it does for one operation in ten, so the thread has to write.
+fn check_fails(op: u64) -> bool {
+    op.is_multiple_of(10)
+}
+
+/// Benchmark for write-then-read on a parking_lot RwLock.
+/// This function measures 8 threads each performing 1,000 increments of a
+/// parking_lot RwLock-protected integer and then reading it back, either by
+/// downgrading the write guard to a read guard with `downgrade()`, which
+/// admits other readers without ever unlocking, or by dropping the write
+/// guard and acquiring a read lock. Only the downgrade guarantees the read
+/// sees the thread's own write, which its variant asserts, and both assert
+/// the counter grew by the number of increments.
+fn parking_lot_rwlock_downgrade(c: &mut Criterion) {
+    let data = Arc::new(parking_lot::RwLock::new(0u64));
+    let mut group = c.benchmark_group("parking_lot_rwlock_downgrade");
+    group.throughput(Throughput::Elements(UPGRADE_THREADS as u64 * UPGRADE_OPS));
+    group.bench_function("downgrade", |b| {
+        b.iter(|| {
+            let before = *data.read();
+            let data_clone = Arc::clone(&data);
+            spawn_and_join(UPGRADE_THREADS, move |_| {
+                for _ in 0..UPGRADE_OPS {
+                    let mut num = data_clone.write();
+                    *num += 1;
+                    let written = *num;
+                    let num = parking_lot::RwLockWriteGuard::downgrade(num);
+                    assert_eq!(black_box(*num), written);
+                }
+            });
+            assert_counter_delta(before, *data.read(), UPGRADE_THREADS as u64 * UPGRADE_OPS);
+        });
+    });
+    group.bench_function("reacquire", |b| {
+        b.iter(|| {
+            let before = *data.read();
+            let data_clone = Arc::clone(&data);
+            spawn_and_join(UPGRADE_THREADS, move |_| {
+                for _ in 0..UPGRADE_OPS {
+                    *data_clone.write() += 1;
+                    black_box(*data_clone.read());
+                }
+            });
+            assert_counter_delta(before, *data.read(), UPGRADE_THREADS as u64 * UPGRADE_OPS);
+        });
+    });
+    group.finish();
+}
+
+/// Benchmark for read-then-maybe-write on a parking_lot RwLock.
+/// This function measures 8 threads performing 1,000 operations each, where
+/// even threads only read and odd threads check the value and increment it
+/// when the check fails, one operation in ten. Under `upgradable_read`, the
+/// checking threads take an upgradable read lock and upgrade it only when the
+/// check fails, so plain readers keep going during the check. Under
+/// `write_lock`, they pessimistically take the write lock for every check,
+/// which shuts the readers out. Only one upgradable guard can be held at a
+/// time, so the checking threads serialize among themselves in both
+/// variants. Both assert the counter grew by the number of failed checks.
+fn parking_lot_rwlock_upgradable(c: &mut Criterion) {
+    let data = Arc::new(parking_lot::RwLock::new(0u64));
+    let expected = (UPGRADE_THREADS / 2) as u64 * UPGRADE_OPS / 10;
+    let mut group = c.benchmark_group("parking_lot_rwlock_upgradable");
+    group.throughput(Throughput::Elements(UPGRADE_THREADS as u64 * UPGRADE_OPS));
+    group.bench_function("upgradable_read", |b| {
+        b.iter(|| {
+            let before = *data.read();
+            let data_clone = Arc::clone(&data);
+            spawn_and_join(UPGRADE_THREADS, move |i| {
+                for op in 0..UPGRADE_OPS {
+                    if i.is_multiple_of(2) {
+                        black_box(*data_clone.read());
+                        continue;
+                    }
+                    let num = data_clone.upgradable_read();
+                    black_box(*num);
+                    if check_fails(op) {
+                        *parking_lot::RwLockUpgradableReadGuard::upgrade(num) += 1;
+                    }
+                }
+            });
+            assert_counter_delta(before, *data.read(), expected);
+        });
+    });
+    group.bench_function("write_lock", |b| {
+        b.iter(|| {
+            let before = *data.read();
+            let data_clone = Arc::clone(&data);
+            spawn_and_join(UPGRADE_THREADS, move |i| {
+                for op in 0..UPGRADE_OPS {
+                    if i.is_multiple_of(2) {
+                        black_box(*data_clone.read());
+                        continue;
+                    }
+                    let mut num = data_clone.write();
+                    black_box(*num);
+                    if check_fails(op) {
+                        *num += 1;
+                    }
+                }
+            });
+            assert_counter_delta(before, *data.read(), expected);
+        });
+    });
+    group.finish();
+}
+
+/// Benchmark for writer starvation comparing std RwLock and parking_lot RwLock.
+/// This function measures 8 reader threads continuously taking read locks
+/// for a 50ms window while 2 writer threads try to acquire the write lock,
+/// and reports how long the writers waited so each implementation's
+/// writer-preference policy shows up as data.
+fn rwlock_writer_starvation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("rwlock_writer_starvation");
+    let std_lock = RwLock::new(0u64);
+    let std_waits = Mutex::new(vec![]);
+    group.bench_function("std", |b| {
+        b.iter(|| {
+            run_writer_starvation(
+                || {
+                    let _unused = std_lock.read().unwrap();
+                },
+                || *std_lock.write().unwrap() += 1,
+                &std_waits,
+            );
+        });
+    });
+    let parking_lot_lock = parking_lot::RwLock::new(0u64);
+    let parking_lot_waits = Mutex::new(vec![]);
+    group.bench_function("parking_lot", |b| {
+        b.iter(|| {
+            run_writer_starvation(
+                || {
+                    let _unused = parking_lot_lock.read();
+                },
+                || *parking_lot_lock.write() += 1,
+                &parking_lot_waits,
+            );
+        });
+    });
+    group.finish();
+    report_writer_waits("rwlock_writer_starvation/std", &std_waits);
+    report_writer_waits("rwlock_writer_starvation/parking_lot", &parking_lot_waits);
+}
+
+/// Benchmark for finding the reader count at which RwLock overtakes Mutex.
+/// This function measures a pre-spawned pool of one writer and 1 to 32 readers,
+/// each performing 1,000 lock operations per iteration with no artificial delay,
+/// against both Arc<Mutex> and Arc<RwLock>, so the criterion line chart
+/// shows the crossover point directly.
+fn lock_crossover(c: &mut Criterion) {
+    let mut group = c.benchmark_group("lock_crossover");
+    for readers in [1, 2, 4, 8, 16, 32] {
+        group.throughput(Throughput::Elements((readers as u64 + 1) * 1000));
+        let mutex = Arc::new(Mutex::new(0u64));
+        let shared = Arc::clone(&mutex);
+        let pool = WorkerPool::new(readers + 1, move |i| {
+            for _ in 0..1000 {
+                if i == 0 {
+                    *shared.lock().unwrap() += 1;
+                } else {
+                    black_box(*shared.lock().unwrap());
+                }
+            }
+        });
+        group.bench_with_input(BenchmarkId::new("mutex", readers), &readers, |b, _| {
+            b.iter(|| {
+                let before = *mutex.lock().unwrap();
+                pool.run();
+                assert_counter_delta(before, *mutex.lock().unwrap(), 1000);
+            });
+        });
+        drop(pool);
+        let rwlock = Arc::new(RwLock::new(0u64));
+        let shared = Arc::clone(&rwlock);
+        let pool = WorkerPool::new(readers + 1, move |i| {
+            for _ in 0..1000 {
+                if i == 0 {
+                    *shared.write().unwrap() += 1;
+                } else {
+                    black_box(*shared.read().unwrap());
+                }
+            }
+        });
+        group.bench_with_input(BenchmarkId::new("rwlock", readers), &readers, |b, _| {
+            b.iter(|| {
+                let before = *rwlock.read().unwrap();
+                pool.run();
+                assert_counter_delta(before, *rwlock.read().unwrap(), 1000);
+            });
+        });
+    }
+    group.finish();
+}
+
+/// How long every operation of the critical section sweep busy-waits inside
+/// the lock, from nothing to long enough that the lock's own cost vanishes.
+const CRITICAL_SECTION_WORK: [Duration; 5] = [
+    Duration::ZERO,
+    Duration::from_nanos(100),
+    Duration::from_micros(1),
+    Duration::from_micros(10),
+    Duration::from_micros(100),
+];
+
+/// How long every thread of the duty cycle sweep busy-waits between
+/// operations, outside the lock.
+const WORK_BETWEEN_OPS: [Duration; 4] = [
+    Duration::ZERO,
+    Duration::from_micros(1),
+    Duration::from_micros(10),
+    Duration::from_micros(100),
+];
+
+/// Threads in the critical section and duty cycle sweeps.
+const BUSY_WORK_THREADS: usize = 8;
+
+/// Operations each thread performs per iteration of the critical section and
+/// duty cycle sweeps, few enough that 100µs inside a Mutex stays under 100ms
+/// an iteration.
+const BUSY_WORK_OPS: u64 = 100;
+
+/// Registers `name/{nanoseconds}` in `group` for every entry of `works`:
+/// `BUSY_WORK_THREADS` threads run the mixed workload on `state`, with
+/// `with_work` adding that much busy work to the scenario.
+fn bench_busy_work<S: scenario::SharedState>(
+    group: &mut BenchmarkGroup<'_, WallTime>,
+    name: &str,
+    state: &S,
+    works: &[Duration],
+    with_work: fn(Scenario, Duration) -> Scenario,
+) {
+    for &work in works {
+        let scenario = with_work(
+            Scenario::new(
+                BUSY_WORK_THREADS,
+                BUSY_WORK_OPS,
+                Workload::Mixed.write_percent(),
+            ),
+            work,
+        );
+        let expected_writes = scenario.expected_writes();
+        group.bench_with_input(
+            BenchmarkId::new(name, work.as_nanos()),
+            &scenario,
+            |b, scenario| {
+                b.iter(|| {
+                    let result = run_contention_scenario(scenario, state);
+                    assert_counter_delta(result.initial_value, result.final_value, expected_writes);
+                    assert!(result.reads_in_range());
+                });
+            },
+        );
+    }
+}
+
+/// Benchmark for how long a critical section has to be before the choice of
+/// lock stops mattering. This function runs the mixed workload with every
+/// operation busy-waiting 0ns to 100µs inside the lock, a calibrated spin
+/// that stays on the CPU, against std's and parking_lot's Mutex and RwLock.
+/// In the criterion line chart the primitives' lines converge once the work
+/// dwarfs the locking overhead.
+fn critical_section_length(c: &mut Criterion) {
+    basic_rust_concurrency_benchmarks::work::calibrate();
+    let mut group = c.benchmark_group("critical_section_length");
+    group.throughput(Throughput::Elements(
+        BUSY_WORK_THREADS as u64 * BUSY_WORK_OPS,
+    ));
+    bench_busy_work(
+        &mut group,
+        "std_mutex",
+        &Arc::new(Mutex::new(0u64)),
+        &CRITICAL_SECTION_WORK,
+        Scenario::with_critical_section_work,
+    );
+    bench_busy_work(
+        &mut group,
+        "std_rwlock",
+        &Arc::new(RwLock::new(0u64)),
+        &CRITICAL_SECTION_WORK,
+        Scenario::with_critical_section_work,
+    );
+    bench_busy_work(
+        &mut group,
+        "parking_lot_mutex",
+        &Arc::new(parking_lot::Mutex::new(0u64)),
+        &CRITICAL_SECTION_WORK,
+        Scenario::with_critical_section_work,
+    );
+    bench_busy_work(
+        &mut group,
+        "parking_lot_rwlock",
+        &Arc::new(parking_lot::RwLock::new(0u64)),
+        &CRITICAL_SECTION_WORK,
+        Scenario::with_critical_section_work,
+    );
+    group.finish();
+}
+
+/// Benchmark for how contention falls as threads spend more time away from
+/// the lock. This function runs the mixed workload with 8 threads, each
+/// busy-waiting 0 to 100µs outside the lock between operations, against
+/// std's Mutex and RwLock. Read next to `critical_section_length`, the two
+/// sweeps place a workload by its time inside and outside the lock.
+fn work_between_ops(c: &mut Criterion) {
+    basic_rust_concurrency_benchmarks::work::calibrate();
+    let mut group = c.benchmark_group("work_between_ops");
+    group.throughput(Throughput::Elements(
+        BUSY_WORK_THREADS as u64 * BUSY_WORK_OPS,
+    ));
+    bench_busy_work(
+        &mut group,
+        "std_mutex",
+        &Arc::new(Mutex::new(0u64)),
+        &WORK_BETWEEN_OPS,
+        Scenario::with_work_between_ops,
+    );
+    bench_busy_work(
+        &mut group,
+        "std_rwlock",
+        &Arc::new(RwLock::new(0u64)),
+        &WORK_BETWEEN_OPS,
+        Scenario::with_work_between_ops,
+    );
+    group.finish();
+}
+
+/// Threads of the start mode benchmark.
+const START_MODE_THREADS: usize = 8;
+
+/// Operations each thread of the start mode benchmark performs, few enough
+/// that how the threads start is a large part of how they contend.
+const START_MODE_OPS: u64 = 1_000;
+
+/// Benchmark for how much of a write-heavy run's contention comes from every
+/// thread starting at once. This function runs the arc_mutex_write_heavy
+/// workload with 8 threads released together by a barrier, released 100µs
+/// apart, and released after a random delay of up to 1ms. The delays count
+/// towards every iteration's time, at most 700µs staggered and 1ms jittered,
+/// so compare the modes by how far apart they are beyond that.
+fn start_mode(c: &mut Criterion) {
+    let data = Arc::new(Mutex::new(0u64));
+    let modes = [
+        ("simultaneous", StartMode::Simultaneous),
+        (
+            "staggered_100us",
+            StartMode::Staggered {
+                interval: Duration::from_micros(100),
+            },
+        ),
+        (
+            "random_jitter_1ms",
+            StartMode::RandomJitter {
+                max: Duration::from_millis(1),
+            },
+        ),
+    ];
+    let mut group = c.benchmark_group("start_mode");
+    group.throughput(Throughput::Elements(
+        START_MODE_THREADS as u64 * START_MODE_OPS,
+    ));
+    for (label, mode) in modes {
+        let scenario = Scenario::new(
+            START_MODE_THREADS,
+            START_MODE_OPS,
+            Workload::WriteHeavy.write_percent(),
+        )
+        .with_start(mode);
+        group.bench_with_input(
+            BenchmarkId::new("arc_mutex_write_heavy", label),
+            &scenario,
+            |b, scenario| {
+                b.iter(|| run_checked_scenario(scenario, &data));
+            },
+        );
+    }
+    group.finish();
+}
+
+/// The criterion configuration every group is registered with. With the
+/// `profiling` feature, `--profile-time` runs also write a flamegraph per
+/// benchmark under `target/criterion/<name>/profile/`.
+fn criterion_config() -> Criterion {
+    let criterion = Criterion::default();
+    #[cfg(feature = "profiling")]
+    let criterion = criterion
+        .with_profiler(basic_rust_concurrency_benchmarks::profiling::FlamegraphProfiler::default());
+    criterion
+}
+
+criterion_group!(
+    name = benches;
+    config = criterion_config();
+    targets = counter_benches,
+        channel_benches,
+        channel_fan_out,
+        channel_payloads,
+        arc_mutex_stall_policies,
+        arc_mutex_wait_vs_hold,
+        pinned_vs_unpinned,
+        oversubscription,
+        uncontended,
+        arc_mutex_try_lock_backoff,
+        poison_recovery,
+        arcswap_read_heavy,
+        arcswap_mixed,
+        value_size,
+        snapshot_read,
+        seqlock_read_heavy,
+        spin_lock_write_heavy,
+        spin_yield_lock_write_heavy,
+        spin_vs_park,
+        queue_locks,
+        atomic_load_read_heavy,
+        atomic_fetch_add_write_heavy,
+        atomic_cas_loop_write_heavy,
+        atomic_ordering,
+        mpsc_sync_channel,
+        crossbeam_bounded,
+        bounded_backpressure,
+        channel_bursts,
+        crossbeam_select,
+        spsc_polling,
+        actor_vs_mutex,
+        result_aggregation,
+        channel_pipeline,
+        channel_allocations,
+        async_tokio_mutex,
+        async_tokio_rwlock_mixed,
+        async_spawn_blocking_std_rwlock_mixed,
+        tokio_mpsc_read_heavy,
+        tokio_mpsc_write_heavy,
+        tokio_mpsc_mixed,
+        tokio_broadcast_fanout,
+        tokio_watch_read_heavy,
+        mpsc_request_response,
+        tokio_oneshot_request_response,
+        rayon_read_heavy,
+        rayon_write_heavy,
+        rayon_atomic_write_heavy,
+        scoped_arc_mutex_write_heavy,
+        scoped_arc_rwlock_mixed,
+        arc_mutex_read_heavy_pooled,
+        arc_mutex_write_heavy_pooled,
+        arc_mutex_mixed_pooled,
+        arc_rwlock_read_heavy_pooled,
+        arc_rwlock_write_heavy_pooled,
+        arc_rwlock_mixed_pooled,
+        condvar_producer_consumer,
+        condvar_notify,
+        barrier_wait,
+        completion,
+        handoff,
+        oncelock_init_race,
+        mutex_option_init_race,
+        shared_map,
+        sharded_counter,
+        lock_striping,
+        false_sharing,
+        arc_refcount,
+        lock_free_queues,
+        work_stealing,
+        treiber_stack,
+        rwlock_writer_starvation,
+        parking_lot_rwlock_downgrade,
+        parking_lot_rwlock_upgradable,
+        lock_crossover,
+        critical_section_length,
+        work_between_ops,
+        start_mode
+);
+
+/// Expands like `criterion_main!(benches)`, after printing the machine the
+/// numbers come from, so pasted results carry their context.
+fn main() {
+    println!("host: {}", HostInfo::current());
+    benches();
+    Criterion::default().configure_from_args().final_summary();
+}
@@ -0,0 +1,131 @@
+//! Instruction-count benchmarks for the uncontended single-threaded paths of
+//! every counter primitive. Callgrind executes each benchmark once on a
+//! simulated CPU, so the instruction and cache access counts it reports are
+//! deterministic and suitable for regression checks in CI, where the
+//! wall-clock numbers from `benches/bench.rs` are too noisy to gate on.
+//!
+//! Run with `cargo bench --features iai --bench iai`. This needs Valgrind and
+//! a matching `iai-callgrind-runner` on the PATH, so it only runs on Linux.
+
+#[cfg(target_os = "linux")]
+mod counters {
+    use basic_rust_concurrency_benchmarks::counter::{FairMutex, SharedCounter};
+    use basic_rust_concurrency_benchmarks::locks::{McsLock, TicketLock};
+    use iai_callgrind::{library_benchmark, library_benchmark_group};
+    use std::hint::black_box;
+    use std::sync::atomic::AtomicU64;
+    use std::sync::{Mutex, RwLock};
+
+    /// Operations each benchmark performs. Callgrind runs the code roughly
+    /// fifty times slower than native, and the counts are exact, so a small
+    /// loop is enough for the per-operation cost to dominate the total.
+    const IAI_OPS: u64 = 1_000;
+
+    /// Increments `counter` `IAI_OPS` times and returns its final value.
+    fn increments(counter: &impl SharedCounter) -> u64 {
+        for _ in 0..IAI_OPS {
+            black_box(counter).increment();
+        }
+        counter.get()
+    }
+
+    /// Reads `counter` `IAI_OPS` times and returns the sum of the reads.
+    fn reads(counter: &impl SharedCounter) -> u64 {
+        (0..IAI_OPS).map(|_| black_box(counter).get()).sum()
+    }
+
+    // The counters are built in the `bench` attribute's argument, which runs
+    // outside the measured region, so only the operations themselves count.
+
+    #[library_benchmark]
+    #[bench::increment(Mutex::default())]
+    fn std_mutex(counter: Mutex<u64>) -> u64 {
+        increments(&counter)
+    }
+
+    #[library_benchmark]
+    #[bench::increment(RwLock::default())]
+    fn std_rwlock_write(counter: RwLock<u64>) -> u64 {
+        increments(&counter)
+    }
+
+    #[library_benchmark]
+    #[bench::get(RwLock::default())]
+    fn std_rwlock_read(counter: RwLock<u64>) -> u64 {
+        reads(&counter)
+    }
+
+    #[library_benchmark]
+    #[bench::increment(parking_lot::Mutex::default())]
+    fn parking_lot_mutex(counter: parking_lot::Mutex<u64>) -> u64 {
+        increments(&counter)
+    }
+
+    #[library_benchmark]
+    #[bench::increment(FairMutex::default())]
+    fn parking_lot_fair_mutex(counter: FairMutex) -> u64 {
+        increments(&counter)
+    }
+
+    #[library_benchmark]
+    #[bench::increment(parking_lot::RwLock::default())]
+    fn parking_lot_rwlock_write(counter: parking_lot::RwLock<u64>) -> u64 {
+        increments(&counter)
+    }
+
+    #[library_benchmark]
+    #[bench::get(parking_lot::RwLock::default())]
+    fn parking_lot_rwlock_read(counter: parking_lot::RwLock<u64>) -> u64 {
+        reads(&counter)
+    }
+
+    #[library_benchmark]
+    #[bench::increment(TicketLock::default())]
+    fn ticket_lock(counter: TicketLock<u64>) -> u64 {
+        increments(&counter)
+    }
+
+    #[library_benchmark]
+    #[bench::increment(McsLock::default())]
+    fn mcs_lock(counter: McsLock<u64>) -> u64 {
+        increments(&counter)
+    }
+
+    #[library_benchmark]
+    #[bench::increment(AtomicU64::default())]
+    fn atomic_u64(counter: AtomicU64) -> u64 {
+        increments(&counter)
+    }
+
+    library_benchmark_group!(
+        name = uncontended;
+        benchmarks =
+            std_mutex,
+            std_rwlock_write,
+            std_rwlock_read,
+            parking_lot_mutex,
+            parking_lot_fair_mutex,
+            parking_lot_rwlock_write,
+            parking_lot_rwlock_read,
+            ticket_lock,
+            mcs_lock,
+            atomic_u64
+    );
+}
+
+#[cfg(target_os = "linux")]
+use counters::uncontended;
+
+// `--cache-sim=yes` adds the L1 and last-level cache hit and miss counts to
+// the instruction counts Callgrind reports by default.
+#[cfg(target_os = "linux")]
+iai_callgrind::main!(
+    config = iai_callgrind::LibraryBenchmarkConfig::default()
+        .tool(iai_callgrind::Callgrind::with_args(["--cache-sim=yes"]));
+    library_benchmark_groups = uncontended
+);
+
+#[cfg(not(target_os = "linux"))]
+fn main() {
+    eprintln!("the iai benchmarks need Valgrind, which is only supported on Linux");
+}
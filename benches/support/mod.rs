@@ -0,0 +1,6 @@
+//! Hand-rolled concurrency primitives used by the benchmarks.
+
+pub mod sharded;
+pub mod spin;
+mod sync;
+pub mod treiber;
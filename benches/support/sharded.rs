@@ -0,0 +1,30 @@
+use super::sync::{AtomicU64, Ordering};
+use crossbeam_utils::CachePadded;
+
+/// A counter split into one cache-padded atomic slot per thread.
+/// Writers only touch their own slot, so increments never contend, while
+/// reads pay for the contention-free writes by summing every slot.
+pub struct ShardedCounter {
+    shards: Vec<CachePadded<AtomicU64>>,
+}
+
+impl ShardedCounter {
+    pub fn new(shards: usize) -> Self {
+        ShardedCounter {
+            shards: (0..shards)
+                .map(|_| CachePadded::new(AtomicU64::new(0)))
+                .collect(),
+        }
+    }
+
+    pub fn increment(&self, shard: usize) {
+        self.shards[shard].fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn sum(&self) -> u64 {
+        self.shards
+            .iter()
+            .map(|shard| shard.load(Ordering::Relaxed))
+            .sum()
+    }
+}
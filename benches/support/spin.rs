@@ -0,0 +1,87 @@
+use super::sync::{spin_loop, yield_now, AtomicBool, Ordering};
+use std::cell::UnsafeCell;
+use std::ops::{Deref, DerefMut};
+
+/// Number of spin iterations `lock_spin_then_yield` tries before yielding the thread.
+const SPINS_BEFORE_YIELD: u32 = 100;
+
+/// A minimal test-and-test-and-set spin lock built on an AtomicBool.
+/// Waiting threads never park, so holding the lock for a long time burns
+/// a full core per waiter, which is exactly what the spin benchmarks show.
+pub struct SpinLock<T> {
+    locked: AtomicBool,
+    data: UnsafeCell<T>,
+}
+
+// SAFETY: access to `data` is serialized by `locked`, so the lock can be
+// shared between threads whenever the protected value can be sent between them.
+unsafe impl<T: Send> Sync for SpinLock<T> {}
+
+impl<T> SpinLock<T> {
+    pub fn new(data: T) -> Self {
+        SpinLock {
+            locked: AtomicBool::new(false),
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    /// Acquires the lock by spinning with `spin_loop` until it is free.
+    pub fn lock(&self) -> SpinLockGuard<'_, T> {
+        while !self.try_acquire() {
+            while self.locked.load(Ordering::Relaxed) {
+                spin_loop();
+            }
+        }
+        SpinLockGuard { lock: self }
+    }
+
+    /// Acquires the lock by spinning briefly, then falling back to
+    /// `thread::yield_now` between attempts so waiters give up their core.
+    pub fn lock_spin_then_yield(&self) -> SpinLockGuard<'_, T> {
+        let mut spins = 0;
+        while !self.try_acquire() {
+            while self.locked.load(Ordering::Relaxed) {
+                if spins < SPINS_BEFORE_YIELD {
+                    spins += 1;
+                    spin_loop();
+                } else {
+                    yield_now();
+                }
+            }
+        }
+        SpinLockGuard { lock: self }
+    }
+
+    fn try_acquire(&self) -> bool {
+        self.locked
+            .compare_exchange_weak(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_ok()
+    }
+}
+
+/// RAII guard for a held `SpinLock`, released on drop.
+pub struct SpinLockGuard<'a, T> {
+    lock: &'a SpinLock<T>,
+}
+
+impl<T> Deref for SpinLockGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: the guard proves this thread holds the lock.
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T> DerefMut for SpinLockGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: the guard proves this thread holds the lock exclusively.
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T> Drop for SpinLockGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.locked.store(false, Ordering::Release);
+    }
+}
@@ -0,0 +1,17 @@
+//! The atomics and thread hints the primitives are built on. Under `--cfg loom`
+//! these are loom's instrumented versions, so `tests/loom_tests.rs` can model
+//! check every interleaving; otherwise they are plain std and cost nothing.
+
+#[cfg(loom)]
+pub use loom::hint::spin_loop;
+#[cfg(loom)]
+pub use loom::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+#[cfg(loom)]
+pub use loom::thread::yield_now;
+
+#[cfg(not(loom))]
+pub use std::hint::spin_loop;
+#[cfg(not(loom))]
+pub use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+#[cfg(not(loom))]
+pub use std::thread::yield_now;
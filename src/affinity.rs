@@ -0,0 +1,74 @@
+//! Pinning scenario threads to cores, behind the `pin` feature.
+
+use crate::warn::warn_once;
+pub use core_affinity::CoreId;
+use std::sync::Once;
+
+/// Set once a pinning warning has been printed.
+static WARNED: Once = Once::new();
+
+/// Keeps one logical core per physical core, using the topology Linux reports
+/// under `/sys`. Anywhere that topology cannot be read, every core is kept.
+fn physical_cores(cores: Vec<CoreId>) -> Vec<CoreId> {
+    let topology = |core: &CoreId| -> Option<(String, String)> {
+        let dir = format!("/sys/devices/system/cpu/cpu{}/topology", core.id);
+        let read = |file| std::fs::read_to_string(format!("{}/{}", dir, file)).ok();
+        Some((read("physical_package_id")?, read("core_id")?))
+    };
+    let Some(keys) = cores.iter().map(topology).collect::<Option<Vec<_>>>() else {
+        return cores;
+    };
+    let mut seen = Vec::new();
+    cores
+        .into_iter()
+        .zip(keys)
+        .filter(|(_, key)| {
+            let first = !seen.contains(key);
+            if first {
+                seen.push(key.clone());
+            }
+            first
+        })
+        .map(|(core, _)| core)
+        .collect()
+}
+
+/// Picks a distinct physical core for each of `threads` workers. Returns
+/// `None`, after warning once on stderr, if the platform cannot list its cores
+/// or has fewer physical cores than workers.
+pub fn assign_cores(threads: usize) -> Option<Vec<CoreId>> {
+    let Some(cores) = core_affinity::get_core_ids() else {
+        warn_once(&WARNED, || {
+            "thread pinning is not supported on this platform; running unpinned".to_string()
+        });
+        return None;
+    };
+    let mut cores = physical_cores(cores);
+    if cores.len() < threads {
+        warn_once(&WARNED, || {
+            let plural = if cores.len() == 1 { "" } else { "s" };
+            format!(
+                "cannot pin {} threads to distinct cores with {} physical core{}; \
+                 running unpinned",
+                threads,
+                cores.len(),
+                plural
+            )
+        });
+        return None;
+    }
+    cores.truncate(threads);
+    Some(cores)
+}
+
+/// Pins the calling thread to `core`, warning once if the platform refuses.
+pub fn pin_current(core: CoreId) {
+    if !core_affinity::set_for_current(core) {
+        warn_once(&WARNED, || {
+            format!(
+                "could not pin a thread to core {}; it runs unpinned",
+                core.id
+            )
+        });
+    }
+}
@@ -0,0 +1,88 @@
+//! Heap allocation counting, behind the `count-allocations` feature.
+//!
+//! `CountingAllocator` wraps the system allocator and counts every allocation
+//! made while the gate opened by `count_allocations` is open. The gate is one
+//! process-wide `AtomicBool`, so allocations by any thread count, including
+//! the threads a benchmark spawns, while criterion's own bookkeeping between
+//! iterations happens with the gate closed and never shows up.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+static COUNTING: AtomicBool = AtomicBool::new(false);
+static ALLOCATIONS: AtomicU64 = AtomicU64::new(0);
+static BYTES: AtomicU64 = AtomicU64::new(0);
+
+/// A global allocator that forwards to `System` and counts allocations while
+/// `count_allocations` is running. Only a binary can install it:
+///
+/// ```ignore
+/// #[global_allocator]
+/// static ALLOCATOR: CountingAllocator = CountingAllocator;
+/// ```
+pub struct CountingAllocator;
+
+impl CountingAllocator {
+    fn record(size: usize) {
+        if COUNTING.load(Ordering::Relaxed) {
+            ALLOCATIONS.fetch_add(1, Ordering::Relaxed);
+            BYTES.fetch_add(size as u64, Ordering::Relaxed);
+        }
+    }
+}
+
+// SAFETY: every method forwards to `System` unchanged; counting only touches
+// atomics and never allocates itself.
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        Self::record(layout.size());
+        System.alloc(layout)
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        Self::record(layout.size());
+        System.alloc_zeroed(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    /// A reallocation counts as one allocation of the new size, since growing
+    /// a buffer in place and moving it both cost an allocator call.
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        Self::record(new_size);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+/// Heap allocations made while a closure ran.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct AllocationCounts {
+    pub allocations: u64,
+    pub bytes: u64,
+}
+
+impl std::ops::AddAssign for AllocationCounts {
+    fn add_assign(&mut self, other: AllocationCounts) {
+        self.allocations += other.allocations;
+        self.bytes += other.bytes;
+    }
+}
+
+/// Runs `f` with the gate open and returns its result with the allocations
+/// made by every thread in the meantime. Without `CountingAllocator`
+/// installed, the counts are always zero. The counters are process-wide, so
+/// calls must not overlap.
+pub fn count_allocations<R>(f: impl FnOnce() -> R) -> (R, AllocationCounts) {
+    ALLOCATIONS.store(0, Ordering::Relaxed);
+    BYTES.store(0, Ordering::Relaxed);
+    COUNTING.store(true, Ordering::SeqCst);
+    let result = f();
+    COUNTING.store(false, Ordering::SeqCst);
+    let counts = AllocationCounts {
+        allocations: ALLOCATIONS.load(Ordering::Relaxed),
+        bytes: BYTES.load(Ordering::Relaxed),
+    };
+    (result, counts)
+}
@@ -0,0 +1,1149 @@
+//! Runs contention scenarios outside of criterion and reports their throughput.
+//!
+//! ```text
+//! cargo run --release --bin concbench -- --primitive rwlock --workload mixed \
+//!     --threads 32 --duration 10s --stall none
+//! ```
+//!
+//! `--primitive` takes a comma-separated list; every primitive runs the same
+//! scenario in turn, and a table comparing their throughput and fairness (how
+//! evenly operations were spread across threads) is printed at the end.
+//!
+//! With `--timing`, the total time threads spent waiting for the lock and
+//! holding it is printed, so moving a stall inside or outside the critical
+//! section shows up as time moving between the two.
+//!
+//! With `--latency`, which needs the `latency` feature, every lock acquisition
+//! latency is recorded and the p50, p90, p99, p99.9, and max are printed.
+//!
+//! With the `cpu-time` feature on Linux or macOS, every thread's CPU time is
+//! measured too, and the total is printed with its efficiency, CPU time divided
+//! by wall time, which tells a lock whose waiters spin from one whose waiters park.
+//!
+//! With `--perf`, which needs the `perf` feature and Linux, the run is wrapped
+//! in perf counters and its context switches, cache misses, and CPU cycles are
+//! printed and included in the JSON report. Counters the kernel does not permit
+//! or the machine does not expose are skipped with a warning.
+//!
+//! With the `context-switches` feature on Linux, the process's voluntary and
+//! involuntary context switches during every run are counted with `getrusage`
+//! and printed, and a warning is printed if the run was preempted more than
+//! `INVOLUNTARY_SWITCHES_PER_OP_WARNING` times per operation.
+//!
+//! With `--pin-threads`, which needs the `pin` feature, every thread is pinned
+//! to a physical core of its own, so the OS cannot migrate threads mid-run.
+//! If the platform cannot pin or there are fewer cores than threads, a warning
+//! is printed and the threads run unpinned.
+//!
+//! With `--trace-out <path>`, which needs the `trace` feature, the runner's
+//! spawn, lock acquisition, critical section, and join spans are written to
+//! `path` as a chrome://tracing JSON file, one timeline row per thread. Every
+//! operation gets two spans, so keep `--duration` short.
+//!
+//! With `--json <path>`, the run is also written to `path` as one JSON object
+//! per line, in the `ScenarioReport` format. With `--csv <path>`, every thread's
+//! operation count, busy time, lock wait time, and critical section time is
+//! written to `path`; lock timing is only recorded when `--csv` is given,
+//! since it reads the clock around every acquisition.
+//!
+//! In the mixed workload every operation is a read or a write at random, drawn
+//! from `--seed` (`DEFAULT_SEED` if not given). The seed is printed with every
+//! result and written to its JSON report, so passing it back replays the same
+//! operations.
+//!
+//! With `--save-baseline <path>`, the run is merged into the baseline file at
+//! `path`, replacing any earlier run of the same primitive, workload, and thread
+//! count. With `--baseline <path>`, the run is compared with that file,
+//! and the runner exits with status 1 if ops/sec dropped by more than
+//! `--max-regression` percent (20 by default).
+//!
+//! `concbench run` instead runs every scenario in the library's `registry` in
+//! turn, or with `--config <path>` every scenario in a TOML scenario file (see
+//! the library's `config` module for the format), and with `--json` or `--csv`
+//! writes all of their results to one file. `--filter <regex>` runs only the
+//! scenarios whose names match, and `concbench list` prints the registered
+//! scenarios:
+//!
+//! ```text
+//! cargo run --release --bin concbench -- run --config scenarios.toml --json results.jsonl
+//! cargo run --release --bin concbench -- run --filter 'rwlock.*read_heavy'
+//! ```
+//!
+//! `concbench diff <old> <new>` compares two `--json` result files, matching
+//! scenarios as baselines do, and prints the change in ops/sec and p99
+//! latency of each, largest change first, with improvements in green and
+//! regressions in red unless `NO_COLOR` is set. Scenarios in only one file
+//! are listed after the table. It exits with status 1 if any scenario
+//! regressed by more than `--threshold` percent (20 by default).
+//!
+//! `concbench soak --scenario <name>` runs one registered scenario, or one
+//! from `--config`, over and over for `--report-every` at a time (10s by
+//! default), printing every interval's ops/sec, p99 latency (with the
+//! `latency` feature), and resident set size, until Ctrl-C stops it. It then
+//! prints the minimum, maximum, and mean interval throughput, so slow drift
+//! over a long run shows up as a spread that short criterion runs miss.
+//!
+//! `concbench plot <results> --out <dir>`, which needs the `plot` feature,
+//! draws one SVG chart per workload in a `--json` result file into `dir`,
+//! with ops/sec against thread count and one line per primitive.
+//!
+//! Exits with status 1 if the final counter value does not match the number of
+//! writes the threads performed, so it doubles as a stress test.
+//!
+//! Ctrl-C during a run, or during `concbench run`, stops the scenario in
+//! progress, drops it, and skips the rest. The scenarios that completed are
+//! still printed and written to `--json` and `--csv`, and the runner exits with
+//! status 130. A second Ctrl-C exits at once, without writing anything, for a
+//! scenario with a fixed operation count that will not stop early.
+
+use basic_rust_concurrency_benchmarks::config::{
+    parse_duration, parse_placement, parse_scenarios, parse_stall, RunLength, ScenarioConfig,
+};
+use basic_rust_concurrency_benchmarks::context_switches::INVOLUNTARY_SWITCHES_PER_OP_WARNING;
+use basic_rust_concurrency_benchmarks::plot::write_scaling_charts;
+use basic_rust_concurrency_benchmarks::registry::{
+    self, primitive_names, run_configured, SCENARIOS,
+};
+use basic_rust_concurrency_benchmarks::rss::resident_set_size;
+use basic_rust_concurrency_benchmarks::scenario::DEFAULT_SEED;
+use basic_rust_concurrency_benchmarks::{
+    available_threads, cancel_runs, diff_reports, find_regressions, read_reports, runs_cancelled,
+    write_reports, HostInfo, Scenario, ScenarioReport, ScenarioResult, StallPlacement, StallPolicy,
+    Workload, CSV_HEADER,
+};
+use regex::Regex;
+use std::env;
+use std::fs::File;
+use std::io::{BufWriter, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::time::{Duration, Instant};
+
+/// The exit status after Ctrl-C, 128 plus SIGINT as shells report it.
+const INTERRUPTED: i32 = 130;
+
+const USAGE: &str = "\
+usage: concbench [options]
+       concbench run [--config <path>] [--filter <regex>] [--seed <n>] [--json <path>] [--csv <path>]
+       concbench list [--filter <regex>]
+       concbench diff <old> <new> [--threshold <percent>]
+       concbench soak --scenario <name> [--config <path>] [--report-every <time>]
+       concbench plot <results> [--out <dir>]
+
+options:
+    --primitive <names>         comma-separated list of mutex, rwlock, parking_lot_mutex,
+                                parking_lot_fair_mutex, parking_lot_rwlock, or atomic (default: mutex)
+    --workload <name>           read_heavy, write_heavy, or mixed (default: mixed)
+    --threads <n>               number of threads (default: available parallelism)
+    --duration <time>           how long to run, such as 500ms or 10s (default: 1s)
+    --stall <policy>            none, fixed:<time>, uniform:<min>-<max>, or poisson:<mean> (default: none)
+    --placement <where>         stall in or outside the critical section (default: in)
+    --seed <n>                  seed for choosing reads and writes (default: 24301)
+    --timing                    print total lock wait and hold time
+    --latency                   record lock acquisition latency (needs the `latency` feature)
+    --perf                      count context switches, cache misses, and cycles (needs the `perf` feature)
+    --pin-threads               pin every thread to its own core (needs the `pin` feature)
+    --trace-out <path>          write a chrome://tracing timeline to <path> (needs the `trace` feature)
+    --json <path>               also write the result to <path> as JSON lines
+    --csv <path>                record lock timing and write per-thread results to <path>
+    --save-baseline <path>      merge the result into the baseline file at <path>
+    --baseline <path>           fail if ops/sec regressed against the baseline file at <path>
+    --max-regression <percent>  allowed ops/sec drop against the baseline (default: 20)
+    --help                      print this message
+
+`run` runs every registered scenario in turn, or with --config <path> every
+scenario in the TOML scenario file at <path>, and writes all of their results
+to <path> with --json or --csv. --filter <regex> runs only the scenarios
+whose names match, and --seed <n> seeds them all. `list` prints the
+registered scenarios. `diff` compares two --json result files and fails if
+ops/sec or p99 latency regressed by more than --threshold percent
+(default: 20). `soak` runs one scenario in a loop until Ctrl-C, reporting
+every --report-every (default: 10s). `plot` draws ops/sec against thread count
+for every workload in a --json result file into --out (default: charts; needs
+the `plot` feature).
+
+Ctrl-C stops a run or `run` early, writes the scenarios that completed, and
+exits with status 130; a second Ctrl-C exits at once.";
+
+struct Args {
+    primitives: Vec<String>,
+    workload: Workload,
+    threads: usize,
+    duration: Duration,
+    stall: StallPolicy,
+    placement: StallPlacement,
+    seed: u64,
+    timing: bool,
+    latency: bool,
+    perf: bool,
+    pin_threads: bool,
+    trace_out: Option<PathBuf>,
+    json: Option<PathBuf>,
+    csv: Option<PathBuf>,
+    save_baseline: Option<PathBuf>,
+    baseline: Option<PathBuf>,
+    max_regression_percent: f64,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Args {
+            primitives: vec!["mutex".to_string()],
+            workload: Workload::Mixed,
+            threads: available_threads(),
+            duration: Duration::from_secs(1),
+            stall: StallPolicy::None,
+            placement: StallPlacement::InCriticalSection,
+            seed: DEFAULT_SEED,
+            timing: false,
+            latency: false,
+            perf: false,
+            pin_threads: false,
+            trace_out: None,
+            json: None,
+            csv: None,
+            save_baseline: None,
+            baseline: None,
+            max_regression_percent: 20.0,
+        };
+        while let Some(flag) = args.next() {
+            if flag == "--help" {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            if flag == "--timing" {
+                parsed.timing = true;
+                continue;
+            }
+            if flag == "--latency" {
+                if cfg!(not(feature = "latency")) {
+                    return Err("`--latency` needs the `latency` feature".to_string());
+                }
+                parsed.latency = true;
+                continue;
+            }
+            if flag == "--perf" {
+                if cfg!(not(feature = "perf")) {
+                    return Err("`--perf` needs the `perf` feature".to_string());
+                }
+                parsed.perf = true;
+                continue;
+            }
+            if flag == "--pin-threads" {
+                if cfg!(not(feature = "pin")) {
+                    return Err("`--pin-threads` needs the `pin` feature".to_string());
+                }
+                parsed.pin_threads = true;
+                continue;
+            }
+            let value = args
+                .next()
+                .ok_or_else(|| format!("missing value for `{}`", flag))?;
+            match flag.as_str() {
+                "--primitive" => {
+                    parsed.primitives = value.split(',').map(str::to_string).collect();
+                    if let Some(unknown) = parsed
+                        .primitives
+                        .iter()
+                        .find(|primitive| registry::primitive(primitive).is_none())
+                    {
+                        return Err(format!("unknown primitive `{}`", unknown));
+                    }
+                }
+                "--workload" => parsed.workload = value.parse()?,
+                "--threads" => {
+                    parsed.threads = match value.parse() {
+                        Ok(threads) if threads > 0 => threads,
+                        _ => return Err(format!("invalid thread count `{}`", value)),
+                    }
+                }
+                "--duration" => parsed.duration = parse_duration(&value)?,
+                "--stall" => parsed.stall = parse_stall(&value)?,
+                "--placement" => parsed.placement = parse_placement(&value)?,
+                "--seed" => parsed.seed = parse_seed(&value)?,
+                "--trace-out" => {
+                    if cfg!(not(feature = "trace")) {
+                        return Err("`--trace-out` needs the `trace` feature".to_string());
+                    }
+                    parsed.trace_out = Some(PathBuf::from(value));
+                }
+                "--json" => parsed.json = Some(PathBuf::from(value)),
+                "--csv" => parsed.csv = Some(PathBuf::from(value)),
+                "--save-baseline" => parsed.save_baseline = Some(PathBuf::from(value)),
+                "--baseline" => parsed.baseline = Some(PathBuf::from(value)),
+                "--max-regression" => {
+                    parsed.max_regression_percent = match value.parse() {
+                        Ok(percent) if percent >= 0.0 => percent,
+                        _ => return Err(format!("invalid percentage `{}`", value)),
+                    }
+                }
+                _ => return Err(format!("unknown option `{}`", flag)),
+            }
+        }
+        Ok(parsed)
+    }
+}
+
+impl Args {
+    /// The scenario the flags describe, without an operation count, since
+    /// flag runs always last `--duration`.
+    fn scenario(&self) -> Scenario {
+        let mut scenario = Scenario::new(self.threads, 0, self.workload.write_percent())
+            .with_stall(self.stall, self.placement)
+            .with_seed(self.seed);
+        if self.timing || self.csv.is_some() {
+            scenario = scenario.with_timing();
+        }
+        #[cfg(feature = "latency")]
+        if self.latency {
+            scenario = scenario.with_latency();
+        }
+        #[cfg(feature = "perf")]
+        if self.perf {
+            scenario = scenario.with_perf_counters();
+        }
+        #[cfg(feature = "pin")]
+        if self.pin_threads {
+            scenario = scenario.with_pinned_threads();
+        }
+        scenario
+    }
+}
+
+/// The options of `concbench run` and `concbench list`.
+struct RunArgs {
+    config: Option<PathBuf>,
+    filter: Option<Regex>,
+    seed: u64,
+    json: Option<PathBuf>,
+    csv: Option<PathBuf>,
+}
+
+impl RunArgs {
+    /// Parses the options of `subcommand`, of which `list` only takes `--filter`.
+    fn parse(subcommand: &str, mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = RunArgs {
+            config: None,
+            filter: None,
+            seed: DEFAULT_SEED,
+            json: None,
+            csv: None,
+        };
+        while let Some(flag) = args.next() {
+            if flag == "--help" {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            let value = args
+                .next()
+                .ok_or_else(|| format!("missing value for `{}`", flag))?;
+            match (subcommand, flag.as_str()) {
+                (_, "--filter") => {
+                    parsed.filter = Some(
+                        Regex::new(&value)
+                            .map_err(|err| format!("invalid filter `{}`: {}", value, err))?,
+                    )
+                }
+                ("run", "--config") => parsed.config = Some(PathBuf::from(value)),
+                ("run", "--seed") => parsed.seed = parse_seed(&value)?,
+                ("run", "--json") => parsed.json = Some(PathBuf::from(value)),
+                ("run", "--csv") => parsed.csv = Some(PathBuf::from(value)),
+                _ => return Err(format!("unknown option `{}` for `{}`", flag, subcommand)),
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// The scenarios to run: those in the `--config` file, or the registered
+    /// ones with the default thread count, no stall, and 1s each, keeping only
+    /// those whose names match `--filter`.
+    fn scenarios(&self) -> Result<Vec<ScenarioConfig>, String> {
+        let scenarios = match &self.config {
+            Some(path) => std::fs::read_to_string(path)
+                .map_err(|err| err.to_string())
+                .and_then(|text| parse_scenarios(&text, &primitive_names()))
+                .map_err(|message| format!("{}: {}", path.display(), message))?,
+            None => SCENARIOS
+                .iter()
+                .map(|registered| ScenarioConfig {
+                    name: registered.name.to_string(),
+                    primitive: registered.primitive.to_string(),
+                    workload: registered.workload,
+                    threads: available_threads(),
+                    length: RunLength::Duration(Duration::from_secs(1)),
+                    stall: StallPolicy::None,
+                    placement: StallPlacement::InCriticalSection,
+                })
+                .collect(),
+        };
+        let Some(filter) = &self.filter else {
+            return Ok(scenarios);
+        };
+        let matching: Vec<ScenarioConfig> = scenarios
+            .into_iter()
+            .filter(|scenario| filter.is_match(&scenario.name))
+            .collect();
+        if matching.is_empty() {
+            return Err(format!("no scenario name matches `{}`", filter));
+        }
+        Ok(matching)
+    }
+}
+
+fn parse_seed(s: &str) -> Result<u64, String> {
+    s.parse().map_err(|_| format!("invalid seed `{}`", s))
+}
+
+/// Installs a Ctrl-C handler whose first press cancels the runs, so the runner
+/// can write what completed, and whose second exits at once.
+fn handle_ctrl_c() {
+    let handler = || {
+        if runs_cancelled() {
+            process::exit(INTERRUPTED);
+        }
+        cancel_runs();
+    };
+    if let Err(err) = ctrlc::set_handler(handler) {
+        eprintln!("error: could not install the Ctrl-C handler: {}", err);
+        process::exit(2);
+    }
+}
+
+/// If any scenario was dropped by Ctrl-C, says how many completed and exits
+/// with status `INTERRUPTED`. Call once the completed ones are written.
+fn exit_if_interrupted(completed: usize, selected: usize) {
+    if completed < selected {
+        eprintln!(
+            "interrupted: {} of {} scenarios completed",
+            completed, selected
+        );
+        process::exit(INTERRUPTED);
+    }
+}
+
+/// Runs `scenario` against the registered primitive named `primitive`.
+fn run_primitive(primitive: &str, scenario: &Scenario, length: RunLength) -> ScenarioResult {
+    registry::primitive(primitive)
+        .expect("primitive names are validated while parsing")
+        .run(scenario, length)
+}
+
+/// Installs a tracing subscriber that writes every span to `path` in the
+/// chrome://tracing format. The file is only complete once the returned guard
+/// has been dropped.
+#[cfg(feature = "trace")]
+fn start_trace(path: &Path) -> std::io::Result<tracing_chrome::FlushGuard> {
+    use tracing_subscriber::layer::SubscriberExt;
+    use tracing_subscriber::util::SubscriberInitExt;
+
+    let file = File::create(path)?;
+    let (layer, guard) = tracing_chrome::ChromeLayerBuilder::new()
+        .writer(file)
+        .include_args(true)
+        .build();
+    tracing_subscriber::registry().with(layer).init();
+    Ok(guard)
+}
+
+fn write_csv(path: &Path, runs: &[Run]) -> std::io::Result<()> {
+    let mut out = BufWriter::new(File::create(path)?);
+    writeln!(out, "{}", CSV_HEADER)?;
+    for run in runs {
+        let name = match &run.name {
+            Some(name) => name.clone(),
+            None => format!("{}_{}", run.primitive, run.workload),
+        };
+        run.result.write_csv(&name, &mut out)?;
+    }
+    out.flush()
+}
+
+/// Adds `new` to the baseline file at `path`, replacing any earlier reports
+/// of the same scenarios, and creating the file if it does not exist yet.
+fn save_baseline(path: &Path, new: &[ScenarioReport]) -> std::io::Result<()> {
+    let mut reports = if path.exists() {
+        read_reports(path)?
+    } else {
+        vec![]
+    };
+    reports.retain(|old| !new.iter().any(|report| old.same_scenario(report)));
+    reports.extend_from_slice(new);
+    write_reports(path, &reports)
+}
+
+/// One primitive's run of a scenario.
+struct Run {
+    /// The scenario's name, for runs from a scenario file.
+    name: Option<String>,
+    primitive: String,
+    workload: Workload,
+    result: ScenarioResult,
+}
+
+impl Run {
+    /// The scenario's name, or the primitive for runs configured by flags.
+    fn label(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.primitive)
+    }
+
+    fn report(&self) -> ScenarioReport {
+        ScenarioReport {
+            name: self.name.clone(),
+            ..self.result.report(&self.primitive, self.workload)
+        }
+    }
+
+    /// Prints what the run did, with lock wait and hold time if `timing`, and
+    /// returns whether its final counter value matched the writes performed
+    /// and every read was in range.
+    fn print(&self, timing: bool) -> bool {
+        let result = &self.result;
+        let expected = result.initial_value + result.writes();
+        let fairness = result.fairness();
+        if let Some(name) = &self.name {
+            println!("scenario:    {}", name);
+        }
+        println!("primitive:   {}", self.primitive);
+        println!("workload:    {}", self.workload);
+        println!("seed:        {}", result.seed);
+        println!("threads:     {}", result.threads.len());
+        println!("elapsed:     {:.3?}", result.elapsed);
+        println!("total ops:   {}", result.ops());
+        println!("ops/sec:     {:.0}", result.ops_per_sec());
+        if let Some((cpu_time, efficiency)) = result.cpu_time().zip(result.efficiency()) {
+            println!(
+                "cpu time:    {:.3?} across all threads, {:.2} cores busy on average",
+                cpu_time, efficiency
+            );
+        }
+        println!(
+            "fairness:    min/max ops {:.3}, coefficient of variation {:.3}",
+            fairness.min_max_ratio, fairness.coefficient_of_variation
+        );
+        println!(
+            "final value: {} (expected {})",
+            result.final_value, expected
+        );
+        #[cfg(feature = "latency")]
+        if let Some(histogram) = &result.acquisition_latency {
+            let summary = basic_rust_concurrency_benchmarks::latency::LatencySummary::of(histogram);
+            println!("wait latency: {}", summary);
+        }
+        #[cfg(feature = "perf")]
+        if let Some(perf) = &result.perf_counts {
+            let count =
+                |count: Option<u64>| count.map_or("unavailable".to_string(), |n| n.to_string());
+            println!("ctx switches: {}", count(perf.context_switches));
+            println!("cache misses: {}", count(perf.cache_misses));
+            println!("cpu cycles:  {}", count(perf.cpu_cycles));
+        }
+        if let Some(switches) = result.context_switches {
+            println!(
+                "switches:    {} voluntary, {} involuntary across the process",
+                switches.voluntary, switches.involuntary
+            );
+        }
+        if let Some(per_op) = result.involuntary_switches_per_op() {
+            if per_op > INVOLUNTARY_SWITCHES_PER_OP_WARNING {
+                eprintln!(
+                    "warning: {} was preempted {:.4} times per operation, more than {}; \
+                     the machine is likely too loaded for these numbers to be trusted",
+                    self.label(),
+                    per_op,
+                    INVOLUNTARY_SWITCHES_PER_OP_WARNING
+                );
+            }
+        }
+        if timing {
+            println!("waiting:     {:.3?} across all threads", result.waiting());
+            println!(
+                "holding:     {:.3?} across all threads",
+                result.in_critical_section()
+            );
+        }
+        let mut ok = true;
+        if result.final_value != expected {
+            eprintln!(
+                "error: {} final value {} does not match the {} writes performed",
+                self.primitive,
+                result.final_value,
+                result.writes()
+            );
+            ok = false;
+        }
+        if !result.reads_in_range() {
+            eprintln!(
+                "error: a {} read observed a value outside the run's range",
+                self.primitive
+            );
+            ok = false;
+        }
+        ok
+    }
+}
+
+/// Prints a Markdown table comparing the throughput, efficiency, and fairness
+/// of every run. Efficiency is `-` when CPU time was not measured, and runs
+/// from a scenario file are labelled by scenario name.
+fn print_comparison(runs: &[Run]) {
+    let named = runs.iter().all(|run| run.name.is_some());
+    println!(
+        "| {} | ops/sec | CPU/wall | min ops | max ops | min/max | CoV |",
+        if named { "scenario" } else { "primitive" }
+    );
+    println!("|---|---|---|---|---|---|---|");
+    for run in runs {
+        let ops = run.result.threads.iter().map(|thread| thread.ops());
+        let fairness = run.result.fairness();
+        let efficiency = run
+            .result
+            .efficiency()
+            .map_or("-".to_string(), |efficiency| format!("{:.2}", efficiency));
+        println!(
+            "| {} | {:.0} | {} | {} | {} | {:.3} | {:.3} |",
+            run.label(),
+            run.result.ops_per_sec(),
+            efficiency,
+            ops.clone().min().unwrap_or(0),
+            ops.max().unwrap_or(0),
+            fairness.min_max_ratio,
+            fairness.coefficient_of_variation
+        );
+    }
+}
+
+/// Prints every run and, if there were several, the comparison table, and
+/// returns whether every run's final counter value was correct.
+fn print_runs(runs: &[Run], timing: bool) -> bool {
+    let mut ok = true;
+    for (index, run) in runs.iter().enumerate() {
+        if index > 0 {
+            println!();
+        }
+        ok &= run.print(timing);
+    }
+    if runs.len() > 1 {
+        println!();
+        print_comparison(runs);
+    }
+    ok
+}
+
+/// Writes the reports to `json` and the per-thread results to `csv`, exiting
+/// with status 2 if either cannot be written.
+fn write_results(
+    json: Option<&Path>,
+    csv: Option<&Path>,
+    runs: &[Run],
+    reports: &[ScenarioReport],
+) {
+    if let Some(path) = json {
+        if let Err(err) = write_reports(path, reports) {
+            eprintln!("error: could not write {}: {}", path.display(), err);
+            process::exit(2);
+        }
+    }
+    if let Some(path) = csv {
+        if let Err(err) = write_csv(path, runs) {
+            eprintln!("error: could not write {}: {}", path.display(), err);
+            process::exit(2);
+        }
+    }
+}
+
+/// Builds the scenario a scenario file entry describes; lock timing is only
+/// recorded when the results go to a CSV file.
+fn configured_scenario(config: &ScenarioConfig, csv: bool) -> Scenario {
+    let scenario = config.scenario();
+    if csv {
+        scenario.with_timing()
+    } else {
+        scenario
+    }
+}
+
+/// `concbench list`: prints every scenario `run` would run.
+fn list(args: RunArgs) {
+    let scenarios = args.scenarios().unwrap_or_else(|message| {
+        eprintln!("error: {}", message);
+        process::exit(2);
+    });
+    for scenario in scenarios {
+        println!(
+            "{:<32} primitive {:<24} workload {:<12} {}% writes",
+            scenario.name,
+            scenario.primitive,
+            scenario.workload.name(),
+            scenario.workload.write_percent()
+        );
+    }
+}
+
+/// `concbench run`: runs every selected scenario in turn.
+fn run_scenarios(args: RunArgs) {
+    let scenarios = args.scenarios().unwrap_or_else(|message| {
+        eprintln!("error: {}", message);
+        process::exit(2);
+    });
+    handle_ctrl_c();
+    println!("host:        {}", HostInfo::current());
+    let timing = args.csv.is_some();
+    let results = run_configured(&scenarios, |scenario| {
+        let scenario = scenario.with_seed(args.seed);
+        if timing {
+            scenario.with_timing()
+        } else {
+            scenario
+        }
+    });
+    let runs: Vec<Run> = scenarios
+        .iter()
+        .zip(results)
+        .map(|(config, result)| Run {
+            name: Some(config.name.clone()),
+            primitive: config.primitive.clone(),
+            workload: config.workload,
+            result,
+        })
+        .collect();
+    let ok = print_runs(&runs, false);
+    let reports: Vec<ScenarioReport> = runs.iter().map(Run::report).collect();
+    write_results(args.json.as_deref(), args.csv.as_deref(), &runs, &reports);
+    if !ok {
+        process::exit(1);
+    }
+    exit_if_interrupted(runs.len(), scenarios.len());
+}
+
+/// The options of `concbench diff`.
+struct DiffArgs {
+    old: PathBuf,
+    new: PathBuf,
+    threshold_percent: f64,
+}
+
+impl DiffArgs {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut paths = vec![];
+        let mut threshold_percent = 20.0;
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--help" => {
+                    println!("{}", USAGE);
+                    process::exit(0);
+                }
+                "--threshold" => {
+                    let value = args.next().ok_or("missing value for `--threshold`")?;
+                    threshold_percent = match value.parse() {
+                        Ok(percent) if percent >= 0.0 => percent,
+                        _ => return Err(format!("invalid percentage `{}`", value)),
+                    }
+                }
+                flag if flag.starts_with("--") => {
+                    return Err(format!("unknown option `{}` for `diff`", flag))
+                }
+                path => paths.push(PathBuf::from(path)),
+            }
+        }
+        let [old, new] = <[PathBuf; 2]>::try_from(paths)
+            .map_err(|_| "`diff` needs exactly two result files".to_string())?;
+        Ok(DiffArgs {
+            old,
+            new,
+            threshold_percent,
+        })
+    }
+}
+
+/// Wraps `text` in the ANSI color `code` if `color` is set.
+fn paint(text: String, code: &str, color: bool) -> String {
+    if color {
+        format!("\x1b[{}m{}\x1b[0m", code, text)
+    } else {
+        text
+    }
+}
+
+/// Formats a percentage change, green if it is an improvement and red if it
+/// is a regression. `higher_is_better` says which way is which.
+fn colored_percent(percent: f64, higher_is_better: bool, color: bool) -> String {
+    let text = format!("{:+.1}%", percent);
+    if percent == 0.0 {
+        text
+    } else if (percent > 0.0) == higher_is_better {
+        paint(text, "32", color)
+    } else {
+        paint(text, "31", color)
+    }
+}
+
+/// `concbench diff`: compares two result files.
+fn diff(args: DiffArgs) {
+    let read = |path: &Path| {
+        read_reports(path).unwrap_or_else(|err| {
+            eprintln!("error: could not read {}: {}", path.display(), err);
+            process::exit(2);
+        })
+    };
+    let diff = diff_reports(&read(&args.old), &read(&args.new));
+    // See https://no-color.org: any non-empty NO_COLOR turns color off.
+    let color = env::var_os("NO_COLOR").is_none_or(|value| value.is_empty())
+        && std::io::stdout().is_terminal();
+    let p99 = |ns: Option<u64>| ns.map_or("-".to_string(), |ns| format!("{}ns", ns));
+    println!("| scenario | old ops/sec | new ops/sec | change | old p99 | new p99 | change |");
+    println!("|---|---|---|---|---|---|---|");
+    for change in &diff.changes {
+        println!(
+            "| {} | {:.0} | {:.0} | {} | {} | {} | {} |",
+            change.new.label(),
+            change.old.ops_per_sec,
+            change.new.ops_per_sec,
+            colored_percent(change.ops_per_sec_percent(), true, color),
+            p99(change.old.p99_latency_ns),
+            p99(change.new.p99_latency_ns),
+            change
+                .p99_latency_percent()
+                .map_or("-".to_string(), |percent| colored_percent(
+                    percent, false, color
+                )),
+        );
+    }
+    for (path, only) in [(&args.old, &diff.only_old), (&args.new, &diff.only_new)] {
+        if !only.is_empty() {
+            println!("\nonly in {}:", path.display());
+            for report in only {
+                println!("    {}", report.label());
+            }
+        }
+    }
+    let mut ok = true;
+    for change in &diff.changes {
+        if change.regression_percent() > args.threshold_percent {
+            eprintln!(
+                "error: {} regressed {:.1}% (limit {}%)",
+                change.new.label(),
+                change.regression_percent(),
+                args.threshold_percent
+            );
+            ok = false;
+        }
+    }
+    if !ok {
+        process::exit(1);
+    }
+}
+
+/// The options of `concbench soak`.
+struct SoakArgs {
+    scenario: String,
+    config: Option<PathBuf>,
+    report_every: Duration,
+}
+
+impl SoakArgs {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let (mut scenario, mut config) = (None, None);
+        let mut report_every = Duration::from_secs(10);
+        while let Some(flag) = args.next() {
+            if flag == "--help" {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            let value = args
+                .next()
+                .ok_or_else(|| format!("missing value for `{}`", flag))?;
+            match flag.as_str() {
+                "--scenario" => scenario = Some(value),
+                "--config" => config = Some(PathBuf::from(value)),
+                "--report-every" => {
+                    report_every = parse_duration(&value)?;
+                    if report_every.is_zero() {
+                        return Err("`--report-every` must be longer than zero".to_string());
+                    }
+                }
+                _ => return Err(format!("unknown option `{}` for `soak`", flag)),
+            }
+        }
+        Ok(SoakArgs {
+            scenario: scenario.ok_or("`soak` needs `--scenario <name>`")?,
+            config,
+            report_every,
+        })
+    }
+}
+
+/// Formats a byte count in MiB, or `-` if it is unknown.
+fn mib(bytes: Option<u64>) -> String {
+    bytes.map_or("-".to_string(), |bytes| {
+        format!("{:.1} MiB", bytes as f64 / (1024.0 * 1024.0))
+    })
+}
+
+/// `concbench soak`: runs one scenario in a loop until Ctrl-C.
+fn soak(args: SoakArgs) {
+    let selection = RunArgs {
+        config: args.config,
+        filter: None,
+        seed: DEFAULT_SEED,
+        json: None,
+        csv: None,
+    };
+    let config = match selection.scenarios().map(|scenarios| {
+        scenarios
+            .into_iter()
+            .find(|scenario| scenario.name == args.scenario)
+    }) {
+        Ok(Some(config)) => config,
+        Ok(None) => {
+            eprintln!(
+                "error: no scenario named `{}`; `concbench list` prints them",
+                args.scenario
+            );
+            process::exit(2);
+        }
+        Err(message) => {
+            eprintln!("error: {}", message);
+            process::exit(2);
+        }
+    };
+    handle_ctrl_c();
+    let scenario = configured_scenario(&config, false);
+    #[cfg(feature = "latency")]
+    let scenario = scenario.with_latency();
+    let primitive = registry::primitive(&config.primitive)
+        .expect("primitive names are validated while parsing");
+    println!("host:        {}", HostInfo::current());
+    println!(
+        "soaking {} ({}, {}, {} threads), reporting every {:?}; press Ctrl-C to stop",
+        config.name, config.primitive, config.workload, config.threads, args.report_every
+    );
+    let started = Instant::now();
+    let mut throughputs = vec![];
+    let mut ok = true;
+    loop {
+        let result = primitive.run(&scenario, RunLength::Duration(args.report_every));
+        if result.final_value != result.initial_value + result.writes() {
+            eprintln!(
+                "error: {} final value {} does not match the {} writes performed",
+                config.name,
+                result.final_value,
+                result.writes()
+            );
+            ok = false;
+        }
+        // An interval cut short by Ctrl-C is left out of the summary.
+        if runs_cancelled() {
+            break;
+        }
+        let p99 = result
+            .report(&config.primitive, config.workload)
+            .p99_latency_ns
+            .map_or("-".to_string(), |ns| format!("{}ns", ns));
+        println!(
+            "{:>10.1?}  ops/sec {:>12.0}  p99 {:>10}  rss {}",
+            started.elapsed(),
+            result.ops_per_sec(),
+            p99,
+            mib(resident_set_size())
+        );
+        throughputs.push(result.ops_per_sec());
+    }
+    println!();
+    println!(
+        "soaked {} for {:.1?} over {} full intervals",
+        config.name,
+        started.elapsed(),
+        throughputs.len()
+    );
+    if !throughputs.is_empty() {
+        let min = throughputs.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = throughputs.iter().copied().fold(0.0, f64::max);
+        let mean = throughputs.iter().sum::<f64>() / throughputs.len() as f64;
+        println!(
+            "ops/sec:     min {:.0}, max {:.0}, mean {:.0}, spread {:.1}% of the mean",
+            min,
+            max,
+            mean,
+            (max - min) / mean * 100.0
+        );
+    }
+    if !ok {
+        process::exit(1);
+    }
+}
+
+/// The options of `concbench plot`.
+struct PlotArgs {
+    results: PathBuf,
+    out: PathBuf,
+}
+
+impl PlotArgs {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut results = None;
+        let mut out = PathBuf::from("charts");
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--help" => {
+                    println!("{}", USAGE);
+                    process::exit(0);
+                }
+                "--out" => out = PathBuf::from(args.next().ok_or("missing value for `--out`")?),
+                flag if flag.starts_with("--") => {
+                    return Err(format!("unknown option `{}` for `plot`", flag))
+                }
+                _ if results.is_some() => return Err("`plot` takes one result file".to_string()),
+                path => results = Some(PathBuf::from(path)),
+            }
+        }
+        Ok(PlotArgs {
+            results: results.ok_or("`plot` needs a result file")?,
+            out,
+        })
+    }
+}
+
+/// `concbench plot`: draws the scaling charts of a result file.
+fn plot(args: PlotArgs) {
+    let reports = read_reports(&args.results).unwrap_or_else(|err| {
+        eprintln!("error: could not read {}: {}", args.results.display(), err);
+        process::exit(2);
+    });
+    match write_scaling_charts(&reports, &args.out) {
+        Ok(paths) => {
+            for path in paths {
+                println!("wrote {}", path.display());
+            }
+        }
+        Err(message) => {
+            eprintln!("error: {}", message);
+            process::exit(2);
+        }
+    }
+}
+
+fn main() {
+    let mut raw = env::args().skip(1).peekable();
+    if raw.next_if(|arg| arg == "soak").is_some() {
+        match SoakArgs::parse(raw) {
+            Ok(args) => soak(args),
+            Err(message) => {
+                eprintln!("error: {}\n\n{}", message, USAGE);
+                process::exit(2);
+            }
+        }
+        return;
+    }
+    if raw.next_if(|arg| arg == "plot").is_some() {
+        match PlotArgs::parse(raw) {
+            Ok(args) => plot(args),
+            Err(message) => {
+                eprintln!("error: {}\n\n{}", message, USAGE);
+                process::exit(2);
+            }
+        }
+        return;
+    }
+    if raw.next_if(|arg| arg == "diff").is_some() {
+        match DiffArgs::parse(raw) {
+            Ok(args) => diff(args),
+            Err(message) => {
+                eprintln!("error: {}\n\n{}", message, USAGE);
+                process::exit(2);
+            }
+        }
+        return;
+    }
+    if let Some(subcommand) = raw.next_if(|arg| arg == "run" || arg == "list") {
+        match RunArgs::parse(&subcommand, raw) {
+            Ok(args) if subcommand == "run" => run_scenarios(args),
+            Ok(args) => list(args),
+            Err(message) => {
+                eprintln!("error: {}\n\n{}", message, USAGE);
+                process::exit(2);
+            }
+        }
+        return;
+    }
+    let args = match Args::parse(raw) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
+    handle_ctrl_c();
+    println!("host:        {}", HostInfo::current());
+    #[cfg(feature = "trace")]
+    let trace = args
+        .trace_out
+        .as_deref()
+        .map(|path| match start_trace(path) {
+            Ok(guard) => guard,
+            Err(err) => {
+                eprintln!("error: could not write {}: {}", path.display(), err);
+                process::exit(2);
+            }
+        });
+    let scenario = args.scenario();
+    let mut runs: Vec<Run> = vec![];
+    for primitive in &args.primitives {
+        if runs_cancelled() {
+            break;
+        }
+        let result = run_primitive(primitive, &scenario, RunLength::Duration(args.duration));
+        // A run cut short by Ctrl-C is not comparable with complete ones.
+        if runs_cancelled() {
+            break;
+        }
+        runs.push(Run {
+            name: None,
+            primitive: primitive.clone(),
+            workload: args.workload,
+            result,
+        });
+    }
+    #[cfg(feature = "trace")]
+    drop(trace);
+    let mut ok = print_runs(&runs, args.timing);
+
+    let reports: Vec<ScenarioReport> = runs.iter().map(Run::report).collect();
+    write_results(args.json.as_deref(), args.csv.as_deref(), &runs, &reports);
+    if let Some(path) = &args.save_baseline {
+        if let Err(err) = save_baseline(path, &reports) {
+            eprintln!("error: could not save baseline {}: {}", path.display(), err);
+            process::exit(2);
+        }
+    }
+    if let Some(path) = &args.baseline {
+        let baseline = match read_reports(path) {
+            Ok(baseline) => baseline,
+            Err(err) => {
+                eprintln!("error: could not read baseline {}: {}", path.display(), err);
+                process::exit(2);
+            }
+        };
+        for report in &reports {
+            if !baseline.iter().any(|old| old.same_scenario(report)) {
+                eprintln!(
+                    "warning: {} has no baseline for {} {} with {} threads",
+                    path.display(),
+                    report.primitive,
+                    report.workload,
+                    report.threads
+                );
+            }
+        }
+        for regression in find_regressions(&baseline, &reports, args.max_regression_percent) {
+            eprintln!(
+                "error: {} (limit {}%)",
+                regression, args.max_regression_percent
+            );
+            ok = false;
+        }
+    }
+    if !ok {
+        process::exit(1);
+    }
+    exit_if_interrupted(runs.len(), args.primitives.len());
+}
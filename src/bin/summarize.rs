@@ -0,0 +1,206 @@
+//! Summarizes a `cargo bench` run as a Markdown table comparing primitives.
+//!
+//! ```text
+//! cargo bench
+//! cargo run --bin summarize -- --threads 8
+//! ```
+//!
+//! Reads criterion's `estimates.json` files from `target/criterion` and prints
+//! one row per workload and one column per primitive, showing the median time
+//! per iteration and the speedup relative to `Arc<Mutex>`. Benchmarks that were
+//! not run are shown as `-` rather than failing the summary.
+
+use serde_json::Value;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process;
+
+const USAGE: &str = "\
+usage: summarize [options]
+
+options:
+    --criterion-dir <path>  criterion output directory (default: target/criterion)
+    --stall <variant>       no_stall, sleep_in_critical_section, or sleep_outside_critical_section (default: no_stall)
+    --threads <n>           thread count to compare (default: the largest one found)
+    --ops <n>               operations per thread to compare (default: 1)
+    --help                  print this message";
+
+const WORKLOADS: [&str; 3] = ["read_heavy", "write_heavy", "mixed"];
+
+/// The primitives compared, by column title. Arc<Mutex> comes first because
+/// every speedup is relative to it.
+const PRIMITIVES: [&str; 5] = [
+    "arc_mutex",
+    "arc_rwlock",
+    "parking_lot_mutex",
+    "parking_lot_rwlock",
+    "atomic",
+];
+
+/// The benchmark group holding `primitive` under `workload` and `stall`, if
+/// that combination is benchmarked at all.
+/// The atomic benchmarks are named after the operation they use, have no mixed
+/// workload, and only have a `_no_stall` variant besides the plain 25ms one.
+fn group_name(primitive: &str, workload: &str, stall: &str) -> Option<String> {
+    if primitive != "atomic" {
+        return Some(format!("{}_{}_{}", primitive, workload, stall));
+    }
+    let name = match workload {
+        "read_heavy" => "atomic_load_read_heavy",
+        "write_heavy" => "atomic_fetch_add_write_heavy",
+        _ => return None,
+    };
+    (stall == "no_stall").then(|| format!("{}_{}", name, stall))
+}
+
+/// The criterion function id for `ops` operations per thread, matching
+/// `ops_function_name` in the benchmarks.
+fn function_name(ops: u64) -> String {
+    if ops == 1 {
+        "threads".to_string()
+    } else {
+        format!("threads_{}_ops", ops)
+    }
+}
+
+struct Args {
+    criterion_dir: PathBuf,
+    stall: String,
+    threads: Option<usize>,
+    ops: u64,
+}
+
+impl Args {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut parsed = Args {
+            criterion_dir: PathBuf::from("target/criterion"),
+            stall: "no_stall".to_string(),
+            threads: None,
+            ops: 1,
+        };
+        while let Some(flag) = args.next() {
+            if flag == "--help" {
+                println!("{}", USAGE);
+                process::exit(0);
+            }
+            let value = args
+                .next()
+                .ok_or_else(|| format!("missing value for `{}`", flag))?;
+            match flag.as_str() {
+                "--criterion-dir" => parsed.criterion_dir = PathBuf::from(value),
+                "--stall" => parsed.stall = value,
+                "--threads" => {
+                    parsed.threads = Some(
+                        value
+                            .parse()
+                            .map_err(|_| format!("invalid thread count `{}`", value))?,
+                    )
+                }
+                "--ops" => {
+                    parsed.ops = value
+                        .parse()
+                        .map_err(|_| format!("invalid operation count `{}`", value))?
+                }
+                _ => return Err(format!("unknown option `{}`", flag)),
+            }
+        }
+        Ok(parsed)
+    }
+
+    /// Directory holding every thread count of `group` for the chosen ops.
+    fn function_dir(&self, group: &str) -> PathBuf {
+        self.criterion_dir.join(group).join(function_name(self.ops))
+    }
+}
+
+/// Reads the median point estimate, in nanoseconds, from one benchmark's
+/// latest `estimates.json`, or `None` if it was not run or cannot be read.
+fn median_ns(bench_dir: &Path) -> Option<f64> {
+    let json = fs::read_to_string(bench_dir.join("new").join("estimates.json")).ok()?;
+    let estimates: Value = serde_json::from_str(&json).ok()?;
+    estimates["median"]["point_estimate"].as_f64()
+}
+
+/// The largest thread count with a result for any compared benchmark.
+fn largest_thread_count(args: &Args) -> Option<usize> {
+    PRIMITIVES
+        .iter()
+        .flat_map(|primitive| {
+            WORKLOADS
+                .iter()
+                .filter_map(move |workload| group_name(primitive, workload, &args.stall))
+        })
+        .filter_map(|group| fs::read_dir(args.function_dir(&group)).ok())
+        .flatten()
+        .filter_map(|entry| entry.ok()?.file_name().to_str()?.parse().ok())
+        .max()
+}
+
+fn format_ns(ns: f64) -> String {
+    if ns >= 1e9 {
+        format!("{:.2} s", ns / 1e9)
+    } else if ns >= 1e6 {
+        format!("{:.2} ms", ns / 1e6)
+    } else if ns >= 1e3 {
+        format!("{:.2} µs", ns / 1e3)
+    } else {
+        format!("{:.2} ns", ns)
+    }
+}
+
+fn main() {
+    let args = match Args::parse(env::args().skip(1)) {
+        Ok(args) => args,
+        Err(message) => {
+            eprintln!("error: {}\n\n{}", message, USAGE);
+            process::exit(2);
+        }
+    };
+    if !args.criterion_dir.is_dir() {
+        eprintln!(
+            "error: {} does not exist; run `cargo bench` first",
+            args.criterion_dir.display()
+        );
+        process::exit(1);
+    }
+    let Some(threads) = args.threads.or_else(|| largest_thread_count(&args)) else {
+        eprintln!(
+            "error: no {} results found in {}",
+            args.stall,
+            args.criterion_dir.display()
+        );
+        process::exit(1);
+    };
+
+    println!(
+        "Median time per iteration with {}, {} threads, and {} operations per thread. \
+         Speedups are relative to arc_mutex; `-` means the benchmark was not run.",
+        args.stall, threads, args.ops
+    );
+    println!();
+    println!("| workload | {} |", PRIMITIVES.join(" | "));
+    println!("|---|{}", "---|".repeat(PRIMITIVES.len()));
+    for workload in WORKLOADS {
+        let medians: Vec<Option<f64>> = PRIMITIVES
+            .iter()
+            .map(|primitive| {
+                let group = group_name(primitive, workload, &args.stall)?;
+                median_ns(&args.function_dir(&group).join(threads.to_string()))
+            })
+            .collect();
+        let baseline = medians[0];
+        let cells: Vec<String> = medians
+            .iter()
+            .enumerate()
+            .map(|(column, median)| match (median, baseline) {
+                (None, _) => "-".to_string(),
+                (Some(ns), Some(base)) if column > 0 => {
+                    format!("{} ({:.2}x)", format_ns(*ns), base / ns)
+                }
+                (Some(ns), _) => format_ns(*ns),
+            })
+            .collect();
+        println!("| {} | {} |", workload, cells.join(" | "));
+    }
+}
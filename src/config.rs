@@ -0,0 +1,188 @@
+//! Scenario files for `concbench run --config`, which list scenarios in TOML
+//! so a whole set of runs can be repeated with one command:
+//!
+//! ```toml
+//! [[scenario]]
+//! name = "mutex_mixed"
+//! primitive = "mutex"
+//! workload = "mixed"
+//! threads = 8
+//! duration = "1s"
+//! stall = "fixed:25ms"
+//! placement = "in"
+//! ```
+//!
+//! Only `name` and `primitive` are required. `workload` defaults to `mixed`,
+//! `threads` to the available parallelism, `stall` to `none`, and `placement`
+//! to `in`. A scenario runs for `duration`, or performs `ops` operations per
+//! thread, but not both, and runs for 1s if neither is given.
+
+use crate::scenario::{available_threads, Scenario, Workload};
+use crate::stall::{StallPlacement, StallPolicy};
+use serde::Deserialize;
+use std::time::Duration;
+
+/// How long a configured scenario runs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RunLength {
+    /// Every thread performs this many operations.
+    Ops(u64),
+    /// Every thread keeps going for this long.
+    Duration(Duration),
+}
+
+/// One validated scenario from a scenario file.
+#[derive(Clone, Debug)]
+pub struct ScenarioConfig {
+    pub name: String,
+    pub primitive: String,
+    pub workload: Workload,
+    pub threads: usize,
+    pub length: RunLength,
+    pub stall: StallPolicy,
+    pub placement: StallPlacement,
+}
+
+impl ScenarioConfig {
+    /// The scenario this entry describes. Its `ops_per_thread` is 0, since
+    /// `length` says how long it runs.
+    pub fn scenario(&self) -> Scenario {
+        Scenario::new(self.threads, 0, self.workload.write_percent())
+            .with_stall(self.stall, self.placement)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawFile {
+    #[serde(default)]
+    scenario: Vec<RawScenario>,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawScenario {
+    name: String,
+    primitive: String,
+    workload: Option<String>,
+    threads: Option<usize>,
+    ops: Option<u64>,
+    duration: Option<String>,
+    stall: Option<String>,
+    placement: Option<String>,
+}
+
+impl RawScenario {
+    fn validate(self, primitives: &[&str]) -> Result<ScenarioConfig, String> {
+        if !primitives.contains(&self.primitive.as_str()) {
+            return Err(format!(
+                "unknown primitive `{}`; expected one of {}",
+                self.primitive,
+                primitives.join(", ")
+            ));
+        }
+        let length = match (self.ops, self.duration) {
+            (Some(_), Some(_)) => return Err("give either `ops` or `duration`, not both".into()),
+            (Some(0), None) => return Err("`ops` must be at least 1".into()),
+            (Some(ops), None) => RunLength::Ops(ops),
+            (None, Some(duration)) => RunLength::Duration(parse_duration(&duration)?),
+            (None, None) => RunLength::Duration(Duration::from_secs(1)),
+        };
+        Ok(ScenarioConfig {
+            workload: match self.workload {
+                Some(workload) => workload.parse()?,
+                None => Workload::Mixed,
+            },
+            threads: match self.threads {
+                Some(0) => return Err("`threads` must be at least 1".into()),
+                Some(threads) => threads,
+                None => available_threads(),
+            },
+            length,
+            stall: parse_stall(self.stall.as_deref().unwrap_or("none"))?,
+            placement: parse_placement(self.placement.as_deref().unwrap_or("in"))?,
+            name: self.name,
+            primitive: self.primitive,
+        })
+    }
+}
+
+/// Parses and validates a scenario file, accepting only the primitive names
+/// in `primitives`. Errors name the offending scenario, and TOML syntax errors
+/// point at the line and column.
+pub fn parse_scenarios(toml: &str, primitives: &[&str]) -> Result<Vec<ScenarioConfig>, String> {
+    let file: RawFile = toml::from_str(toml).map_err(|err| err.to_string())?;
+    if file.scenario.is_empty() {
+        return Err("no `[[scenario]]` entries".to_string());
+    }
+    let mut scenarios: Vec<ScenarioConfig> = vec![];
+    for (index, raw) in file.scenario.into_iter().enumerate() {
+        let name = raw.name.clone();
+        let scenario = raw
+            .validate(primitives)
+            .map_err(|err| format!("scenario {} (`{}`): {}", index + 1, name, err))?;
+        if scenarios
+            .iter()
+            .any(|earlier| earlier.name == scenario.name)
+        {
+            return Err(format!(
+                "scenario {}: the name `{}` is already used",
+                index + 1,
+                name
+            ));
+        }
+        scenarios.push(scenario);
+    }
+    Ok(scenarios)
+}
+
+/// Parses a duration such as `250us`, `500ms`, or `10s`.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let invalid = || format!("invalid duration `{}`", s);
+    let split = s.find(|c: char| !c.is_ascii_digit()).ok_or_else(invalid)?;
+    let amount: u64 = s[..split].parse().map_err(|_| invalid())?;
+    match &s[split..] {
+        "ns" => Ok(Duration::from_nanos(amount)),
+        "us" => Ok(Duration::from_micros(amount)),
+        "ms" => Ok(Duration::from_millis(amount)),
+        "s" => Ok(Duration::from_secs(amount)),
+        _ => Err(invalid()),
+    }
+}
+
+/// Parses a stall policy such as `none`, `fixed:25ms`, `uniform:1ms-10ms`,
+/// or `poisson:5ms`.
+pub fn parse_stall(s: &str) -> Result<StallPolicy, String> {
+    if s == "none" {
+        return Ok(StallPolicy::None);
+    }
+    let (kind, value) = s
+        .split_once(':')
+        .ok_or_else(|| format!("unknown stall policy `{}`", s))?;
+    match kind {
+        "fixed" => Ok(StallPolicy::FixedEvenIterations(parse_duration(value)?)),
+        "uniform" => {
+            let (min, max) = value
+                .split_once('-')
+                .ok_or_else(|| format!("expected `uniform:<min>-<max>`, got `{}`", s))?;
+            let (min, max) = (parse_duration(min)?, parse_duration(max)?);
+            if min > max {
+                return Err("uniform stall needs min <= max".to_string());
+            }
+            Ok(StallPolicy::Uniform { min, max })
+        }
+        "poisson" => Ok(StallPolicy::Poisson {
+            mean: parse_duration(value)?,
+        }),
+        _ => Err(format!("unknown stall policy `{}`", s)),
+    }
+}
+
+/// Parses a stall placement, `in` or `outside` the critical section.
+pub fn parse_placement(s: &str) -> Result<StallPlacement, String> {
+    match s {
+        "in" => Ok(StallPlacement::InCriticalSection),
+        "outside" => Ok(StallPlacement::OutsideCriticalSection),
+        _ => Err(format!("unknown placement `{}`", s)),
+    }
+}
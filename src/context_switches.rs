@@ -0,0 +1,58 @@
+//! Process-wide context switch counts, behind the `context-switches` feature
+//! on Linux.
+//!
+//! Involuntary context switches, where the scheduler preempts a thread that
+//! could have kept running, are the signature of lock convoys and of a
+//! machine too loaded to benchmark on. Voluntary ones are threads parking,
+//! which is how most locks wait.
+
+use std::ops::Sub;
+
+/// Above this many involuntary context switches per operation, a run was
+/// preempted often enough that its numbers say more about the machine's load
+/// than about the primitive. An uncontended lock operation takes tens of
+/// nanoseconds and a preemption several microseconds, so even one switch per
+/// thousand operations costs more than the operations themselves.
+pub const INVOLUNTARY_SWITCHES_PER_OP_WARNING: f64 = 0.001;
+
+/// Context switches counted by `getrusage`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ContextSwitches {
+    /// Switches where a thread gave up the CPU, such as to park on a lock.
+    pub voluntary: u64,
+    /// Switches where the scheduler preempted a runnable thread.
+    pub involuntary: u64,
+}
+
+impl Sub for ContextSwitches {
+    type Output = ContextSwitches;
+
+    fn sub(self, earlier: ContextSwitches) -> ContextSwitches {
+        ContextSwitches {
+            voluntary: self.voluntary.saturating_sub(earlier.voluntary),
+            involuntary: self.involuntary.saturating_sub(earlier.involuntary),
+        }
+    }
+}
+
+/// Context switches of every thread the process has run so far, including
+/// threads that have exited. Threads outside the scenario, such as other
+/// tests running in parallel, count too.
+#[cfg(all(feature = "context-switches", target_os = "linux"))]
+pub fn process_context_switches() -> Option<ContextSwitches> {
+    // SAFETY: all-zero bytes are a valid `rusage`, a plain struct of integers.
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    // SAFETY: `usage` is a valid, writable rusage for the duration of the call.
+    let result = unsafe { libc::getrusage(libc::RUSAGE_SELF, &mut usage) };
+    (result == 0).then_some(ContextSwitches {
+        voluntary: usage.ru_nvcsw as u64,
+        involuntary: usage.ru_nivcsw as u64,
+    })
+}
+
+/// Without the `context-switches` feature, or on other platforms, context
+/// switches are not counted.
+#[cfg(not(all(feature = "context-switches", target_os = "linux")))]
+pub fn process_context_switches() -> Option<ContextSwitches> {
+    None
+}
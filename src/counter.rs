@@ -0,0 +1,428 @@
+use crate::locks::{McsLock, McsNode, TicketLock};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Mutex, MutexGuard, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::{Duration, Instant};
+
+/// How long one operation waited to acquire a lock and then held it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LockTiming {
+    pub waiting: Duration,
+    pub in_critical_section: Duration,
+}
+
+impl LockTiming {
+    /// Timing of an operation that started waiting at `start`, acquired the lock
+    /// at `acquired`, and released it at `released`.
+    fn between(start: Instant, acquired: Instant, released: Instant) -> Self {
+        LockTiming {
+            waiting: acquired - start,
+            in_critical_section: released - acquired,
+        }
+    }
+
+    /// Timing of a lock-free operation, which never waits and counts its whole
+    /// duration, from `start` until now, as the critical section.
+    fn lock_free(start: Instant) -> Self {
+        LockTiming {
+            waiting: Duration::ZERO,
+            in_critical_section: start.elapsed(),
+        }
+    }
+}
+
+/// A thread-safe counter, implemented once per synchronization primitive so
+/// every primitive plugs into the same contention scenario and bench body.
+/// `get_holding` and `increment_holding` additionally run `hold` while any
+/// lock is still held, which is how a stall inside the critical section is
+/// modelled; lock-free counters have nothing to hold and keep the defaults,
+/// which run `hold` after the operation.
+/// The `_timed` variants behave like the `_holding` ones but also report how
+/// long the call waited for and then held the lock; they read the clock around
+/// every acquisition, so they are only used when a scenario records timing.
+pub trait SharedCounter: Send + Sync {
+    fn increment(&self);
+
+    fn get(&self) -> u64;
+
+    fn increment_holding(&self, hold: impl FnOnce()) {
+        self.increment();
+        hold();
+    }
+
+    fn get_holding(&self, hold: impl FnOnce()) -> u64 {
+        let value = self.get();
+        hold();
+        value
+    }
+
+    fn increment_timed(&self, hold: impl FnOnce()) -> LockTiming {
+        let start = Instant::now();
+        self.increment();
+        let timing = LockTiming::lock_free(start);
+        hold();
+        timing
+    }
+
+    fn get_timed(&self, hold: impl FnOnce()) -> (u64, LockTiming) {
+        let start = Instant::now();
+        let value = self.get();
+        let timing = LockTiming::lock_free(start);
+        hold();
+        (value, timing)
+    }
+}
+
+// The std locks are poisoned when a thread panics while holding them. A bare
+// counter has no invariant a panic can leave half-updated, so the impls below
+// recover the guard instead of cascading the panic through every other thread.
+
+fn lock(mutex: &Mutex<u64>) -> MutexGuard<'_, u64> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn read(rwlock: &RwLock<u64>) -> RwLockReadGuard<'_, u64> {
+    rwlock.read().unwrap_or_else(PoisonError::into_inner)
+}
+
+fn write(rwlock: &RwLock<u64>) -> RwLockWriteGuard<'_, u64> {
+    rwlock.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl SharedCounter for Mutex<u64> {
+    fn increment(&self) {
+        *lock(self) += 1;
+    }
+
+    fn get(&self) -> u64 {
+        *lock(self)
+    }
+
+    fn increment_holding(&self, hold: impl FnOnce()) {
+        let mut guard = lock(self);
+        *guard += 1;
+        hold();
+    }
+
+    fn get_holding(&self, hold: impl FnOnce()) -> u64 {
+        let guard = lock(self);
+        let value = *guard;
+        hold();
+        value
+    }
+
+    fn increment_timed(&self, hold: impl FnOnce()) -> LockTiming {
+        let start = Instant::now();
+        let mut guard = lock(self);
+        let acquired = Instant::now();
+        *guard += 1;
+        hold();
+        drop(guard);
+        LockTiming::between(start, acquired, Instant::now())
+    }
+
+    fn get_timed(&self, hold: impl FnOnce()) -> (u64, LockTiming) {
+        let start = Instant::now();
+        let guard = lock(self);
+        let acquired = Instant::now();
+        let value = *guard;
+        hold();
+        drop(guard);
+        (value, LockTiming::between(start, acquired, Instant::now()))
+    }
+}
+
+impl SharedCounter for RwLock<u64> {
+    fn increment(&self) {
+        *write(self) += 1;
+    }
+
+    fn get(&self) -> u64 {
+        *read(self)
+    }
+
+    fn increment_holding(&self, hold: impl FnOnce()) {
+        let mut guard = write(self);
+        *guard += 1;
+        hold();
+    }
+
+    fn get_holding(&self, hold: impl FnOnce()) -> u64 {
+        let guard = read(self);
+        let value = *guard;
+        hold();
+        value
+    }
+
+    fn increment_timed(&self, hold: impl FnOnce()) -> LockTiming {
+        let start = Instant::now();
+        let mut guard = write(self);
+        let acquired = Instant::now();
+        *guard += 1;
+        hold();
+        drop(guard);
+        LockTiming::between(start, acquired, Instant::now())
+    }
+
+    fn get_timed(&self, hold: impl FnOnce()) -> (u64, LockTiming) {
+        let start = Instant::now();
+        let guard = read(self);
+        let acquired = Instant::now();
+        let value = *guard;
+        hold();
+        drop(guard);
+        (value, LockTiming::between(start, acquired, Instant::now()))
+    }
+}
+
+impl SharedCounter for parking_lot::Mutex<u64> {
+    fn increment(&self) {
+        *self.lock() += 1;
+    }
+
+    fn get(&self) -> u64 {
+        *self.lock()
+    }
+
+    fn increment_holding(&self, hold: impl FnOnce()) {
+        let mut guard = self.lock();
+        *guard += 1;
+        hold();
+    }
+
+    fn get_holding(&self, hold: impl FnOnce()) -> u64 {
+        let guard = self.lock();
+        let value = *guard;
+        hold();
+        value
+    }
+
+    fn increment_timed(&self, hold: impl FnOnce()) -> LockTiming {
+        let start = Instant::now();
+        let mut guard = self.lock();
+        let acquired = Instant::now();
+        *guard += 1;
+        hold();
+        drop(guard);
+        LockTiming::between(start, acquired, Instant::now())
+    }
+
+    fn get_timed(&self, hold: impl FnOnce()) -> (u64, LockTiming) {
+        let start = Instant::now();
+        let guard = self.lock();
+        let acquired = Instant::now();
+        let value = *guard;
+        hold();
+        drop(guard);
+        (value, LockTiming::between(start, acquired, Instant::now()))
+    }
+}
+
+/// A parking_lot Mutex that releases with `unlock_fair`, handing the lock
+/// directly to the longest waiting thread instead of letting the releasing
+/// thread barge back in. This trades throughput for fairness.
+#[derive(Debug, Default)]
+pub struct FairMutex(pub parking_lot::Mutex<u64>);
+
+impl From<u64> for FairMutex {
+    fn from(value: u64) -> Self {
+        FairMutex(parking_lot::Mutex::new(value))
+    }
+}
+
+impl SharedCounter for FairMutex {
+    fn increment(&self) {
+        let mut guard = self.0.lock();
+        *guard += 1;
+        parking_lot::MutexGuard::unlock_fair(guard);
+    }
+
+    fn get(&self) -> u64 {
+        let guard = self.0.lock();
+        let value = *guard;
+        parking_lot::MutexGuard::unlock_fair(guard);
+        value
+    }
+
+    fn increment_holding(&self, hold: impl FnOnce()) {
+        let mut guard = self.0.lock();
+        *guard += 1;
+        hold();
+        parking_lot::MutexGuard::unlock_fair(guard);
+    }
+
+    fn get_holding(&self, hold: impl FnOnce()) -> u64 {
+        let guard = self.0.lock();
+        let value = *guard;
+        hold();
+        parking_lot::MutexGuard::unlock_fair(guard);
+        value
+    }
+
+    fn increment_timed(&self, hold: impl FnOnce()) -> LockTiming {
+        let start = Instant::now();
+        let mut guard = self.0.lock();
+        let acquired = Instant::now();
+        *guard += 1;
+        hold();
+        parking_lot::MutexGuard::unlock_fair(guard);
+        LockTiming::between(start, acquired, Instant::now())
+    }
+
+    fn get_timed(&self, hold: impl FnOnce()) -> (u64, LockTiming) {
+        let start = Instant::now();
+        let guard = self.0.lock();
+        let acquired = Instant::now();
+        let value = *guard;
+        hold();
+        parking_lot::MutexGuard::unlock_fair(guard);
+        (value, LockTiming::between(start, acquired, Instant::now()))
+    }
+}
+
+impl SharedCounter for parking_lot::RwLock<u64> {
+    fn increment(&self) {
+        *self.write() += 1;
+    }
+
+    fn get(&self) -> u64 {
+        *self.read()
+    }
+
+    fn increment_holding(&self, hold: impl FnOnce()) {
+        let mut guard = self.write();
+        *guard += 1;
+        hold();
+    }
+
+    fn get_holding(&self, hold: impl FnOnce()) -> u64 {
+        let guard = self.read();
+        let value = *guard;
+        hold();
+        value
+    }
+
+    fn increment_timed(&self, hold: impl FnOnce()) -> LockTiming {
+        let start = Instant::now();
+        let mut guard = self.write();
+        let acquired = Instant::now();
+        *guard += 1;
+        hold();
+        drop(guard);
+        LockTiming::between(start, acquired, Instant::now())
+    }
+
+    fn get_timed(&self, hold: impl FnOnce()) -> (u64, LockTiming) {
+        let start = Instant::now();
+        let guard = self.read();
+        let acquired = Instant::now();
+        let value = *guard;
+        hold();
+        drop(guard);
+        (value, LockTiming::between(start, acquired, Instant::now()))
+    }
+}
+
+impl SharedCounter for AtomicU64 {
+    fn increment(&self) {
+        self.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn get(&self) -> u64 {
+        self.load(Ordering::Relaxed)
+    }
+}
+
+impl SharedCounter for TicketLock<u64> {
+    fn increment(&self) {
+        *self.lock() += 1;
+    }
+
+    fn get(&self) -> u64 {
+        *self.lock()
+    }
+
+    fn increment_holding(&self, hold: impl FnOnce()) {
+        let mut guard = self.lock();
+        *guard += 1;
+        hold();
+    }
+
+    fn get_holding(&self, hold: impl FnOnce()) -> u64 {
+        let guard = self.lock();
+        let value = *guard;
+        hold();
+        value
+    }
+
+    fn increment_timed(&self, hold: impl FnOnce()) -> LockTiming {
+        let start = Instant::now();
+        let mut guard = self.lock();
+        let acquired = Instant::now();
+        *guard += 1;
+        hold();
+        drop(guard);
+        LockTiming::between(start, acquired, Instant::now())
+    }
+
+    fn get_timed(&self, hold: impl FnOnce()) -> (u64, LockTiming) {
+        let start = Instant::now();
+        let guard = self.lock();
+        let acquired = Instant::now();
+        let value = *guard;
+        hold();
+        drop(guard);
+        (value, LockTiming::between(start, acquired, Instant::now()))
+    }
+}
+
+// Each acquisition queues a node on the calling thread's stack, which lives
+// until the guard is dropped at the end of the method.
+impl SharedCounter for McsLock<u64> {
+    fn increment(&self) {
+        let mut node = McsNode::new();
+        *self.lock(&mut node) += 1;
+    }
+
+    fn get(&self) -> u64 {
+        let mut node = McsNode::new();
+        let value = *self.lock(&mut node);
+        value
+    }
+
+    fn increment_holding(&self, hold: impl FnOnce()) {
+        let mut node = McsNode::new();
+        let mut guard = self.lock(&mut node);
+        *guard += 1;
+        hold();
+    }
+
+    fn get_holding(&self, hold: impl FnOnce()) -> u64 {
+        let mut node = McsNode::new();
+        let guard = self.lock(&mut node);
+        let value = *guard;
+        hold();
+        value
+    }
+
+    fn increment_timed(&self, hold: impl FnOnce()) -> LockTiming {
+        let mut node = McsNode::new();
+        let start = Instant::now();
+        let mut guard = self.lock(&mut node);
+        let acquired = Instant::now();
+        *guard += 1;
+        hold();
+        drop(guard);
+        LockTiming::between(start, acquired, Instant::now())
+    }
+
+    fn get_timed(&self, hold: impl FnOnce()) -> (u64, LockTiming) {
+        let mut node = McsNode::new();
+        let start = Instant::now();
+        let guard = self.lock(&mut node);
+        let acquired = Instant::now();
+        let value = *guard;
+        hold();
+        drop(guard);
+        (value, LockTiming::between(start, acquired, Instant::now()))
+    }
+}
@@ -0,0 +1,23 @@
+//! Per-thread CPU time, behind the `cpu-time` feature on Linux and macOS.
+
+use std::time::Duration;
+
+/// CPU time the calling thread has consumed so far, read from the
+/// per-thread CPU clock.
+#[cfg(all(feature = "cpu-time", any(target_os = "linux", target_os = "macos")))]
+pub fn thread_cpu_time() -> Option<Duration> {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    // SAFETY: `ts` is a valid, writable timespec for the duration of the call.
+    let result = unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut ts) };
+    (result == 0).then(|| Duration::new(ts.tv_sec as u64, ts.tv_nsec as u32))
+}
+
+/// Without the `cpu-time` feature, or on other platforms, thread CPU time is
+/// not measured.
+#[cfg(not(all(feature = "cpu-time", any(target_os = "linux", target_os = "macos"))))]
+pub fn thread_cpu_time() -> Option<Duration> {
+    None
+}
@@ -0,0 +1,59 @@
+//! Lock acquisition latency histograms, behind the `latency` feature.
+
+pub use hdrhistogram::Histogram;
+use std::fmt;
+use std::time::Duration;
+
+/// Creates an empty histogram of nanosecond latencies with three significant
+/// digits, which grows to fit whatever is recorded.
+pub fn new_histogram() -> Histogram<u64> {
+    Histogram::new(3).expect("three significant digits is a valid precision")
+}
+
+/// Merges per-thread histograms into one.
+pub fn merge<'a>(histograms: impl IntoIterator<Item = &'a Histogram<u64>>) -> Histogram<u64> {
+    let mut merged = new_histogram();
+    for histogram in histograms {
+        merged
+            .add(histogram)
+            .expect("auto-resizing histograms accept any other histogram");
+    }
+    merged
+}
+
+/// The tail percentiles of a latency histogram, in nanoseconds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LatencySummary {
+    pub p50: u64,
+    pub p90: u64,
+    pub p99: u64,
+    pub p999: u64,
+    pub max: u64,
+}
+
+impl LatencySummary {
+    pub fn of(histogram: &Histogram<u64>) -> Self {
+        LatencySummary {
+            p50: histogram.value_at_quantile(0.5),
+            p90: histogram.value_at_quantile(0.9),
+            p99: histogram.value_at_quantile(0.99),
+            p999: histogram.value_at_quantile(0.999),
+            max: histogram.max(),
+        }
+    }
+}
+
+impl fmt::Display for LatencySummary {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ns = Duration::from_nanos;
+        write!(
+            f,
+            "p50 {:?}, p90 {:?}, p99 {:?}, p99.9 {:?}, max {:?}",
+            ns(self.p50),
+            ns(self.p90),
+            ns(self.p99),
+            ns(self.p999),
+            ns(self.max)
+        )
+    }
+}
@@ -0,0 +1,45 @@
+//! Reusable contention workloads behind the benchmarks.
+//!
+//! The benchmarks in `benches/bench.rs` drive these workloads through criterion,
+//! and `src/bin/concbench.rs` runs them from the command line, but they can
+//! equally be run from tests or other benchmarks.
+
+#[cfg(feature = "pin")]
+pub mod affinity;
+#[cfg(feature = "count-allocations")]
+pub mod allocations;
+pub mod config;
+pub mod context_switches;
+pub mod counter;
+pub mod cpu_time;
+#[cfg(feature = "latency")]
+pub mod latency;
+pub mod locks;
+#[cfg(feature = "perf")]
+pub mod perf;
+pub mod plot;
+#[cfg(feature = "profiling")]
+pub mod profiling;
+pub mod registry;
+pub mod report;
+pub mod rss;
+pub mod scenario;
+pub mod stall;
+pub mod striped;
+#[cfg(any(feature = "perf", feature = "pin"))]
+mod warn;
+pub mod work;
+
+pub use counter::{FairMutex, LockTiming, SharedCounter};
+pub use cpu_time::thread_cpu_time;
+pub use report::{
+    diff_reports, find_regressions, read_reports, write_reports, HostInfo, Regression,
+    ReportChange, ReportDiff, ScenarioReport, CSV_HEADER, SCHEMA_VERSION,
+};
+pub use scenario::{
+    available_threads, cancel_runs, run_contention_scenario, run_for_duration, runs_cancelled,
+    scaled_thread_counts, Fairness, Scenario, ScenarioResult, SharedState, StartMode, ThreadResult,
+    Workload,
+};
+pub use stall::{LockStall, StallPlacement, StallPolicy};
+pub use striped::StripedMutex;
@@ -0,0 +1,150 @@
+use super::sync::{spin_loop, AtomicBool, AtomicPtr, Ordering};
+use std::cell::UnsafeCell;
+use std::marker::PhantomData;
+use std::ops::{Deref, DerefMut};
+use std::ptr;
+
+/// A waiter's place in an `McsLock` queue. Each acquisition needs a node
+/// that outlives the guard, typically a local on the locking thread's stack:
+///
+/// ```
+/// use basic_rust_concurrency_benchmarks::locks::{McsLock, McsNode};
+///
+/// let lock = McsLock::new(0u64);
+/// let mut node = McsNode::new();
+/// *lock.lock(&mut node) += 1;
+/// ```
+pub struct McsNode {
+    next: AtomicPtr<McsNode>,
+    locked: AtomicBool,
+}
+
+impl McsNode {
+    pub fn new() -> Self {
+        McsNode {
+            next: AtomicPtr::new(ptr::null_mut()),
+            locked: AtomicBool::new(false),
+        }
+    }
+}
+
+impl Default for McsNode {
+    fn default() -> Self {
+        McsNode::new()
+    }
+}
+
+/// An MCS lock: waiters form a linked queue of their own `McsNode`s, each
+/// spinning on the flag in its own node until its predecessor clears it.
+/// A release therefore writes only to the next waiter's node, instead of to
+/// a line every waiter is reading as in `TicketLock`, and the lock is still
+/// granted in arrival order.
+pub struct McsLock<T> {
+    tail: AtomicPtr<McsNode>,
+    data: UnsafeCell<T>,
+}
+
+// SAFETY: access to `data` is serialized by the queue, so the lock can be
+// shared between threads whenever the protected value can be sent between them.
+unsafe impl<T: Send> Sync for McsLock<T> {}
+
+impl<T> McsLock<T> {
+    pub fn new(data: T) -> Self {
+        McsLock {
+            tail: AtomicPtr::new(ptr::null_mut()),
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    /// Enqueues `node` and spins with `spin_loop` on its flag until the
+    /// previous holder hands the lock over. The node stays borrowed, and in
+    /// the queue, until the guard is dropped.
+    pub fn lock<'a>(&'a self, node: &'a mut McsNode) -> McsGuard<'a, T> {
+        node.next = AtomicPtr::new(ptr::null_mut());
+        node.locked = AtomicBool::new(true);
+        let node: *mut McsNode = node;
+        // AcqRel: publishes the node's initialization to whoever links to it,
+        // and acquires the previous holder's writes if it already left.
+        let prev = self.tail.swap(node, Ordering::AcqRel);
+        if !prev.is_null() {
+            // SAFETY: a node in the queue stays alive until its holder has
+            // handed the lock on, which cannot happen before this link.
+            unsafe { (*prev).next.store(node, Ordering::Release) };
+            // SAFETY: `node` is borrowed for the guard's lifetime.
+            while unsafe { (*node).locked.load(Ordering::Acquire) } {
+                spin_loop();
+            }
+        }
+        McsGuard {
+            lock: self,
+            node,
+            _node: PhantomData,
+        }
+    }
+}
+
+impl<T: Default> Default for McsLock<T> {
+    fn default() -> Self {
+        McsLock::new(T::default())
+    }
+}
+
+/// RAII guard for a held `McsLock`, which hands the lock to the next queued
+/// node on drop.
+pub struct McsGuard<'a, T> {
+    lock: &'a McsLock<T>,
+    node: *mut McsNode,
+    _node: PhantomData<&'a mut McsNode>,
+}
+
+impl<T> Deref for McsGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: the guard proves this thread holds the lock.
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T> DerefMut for McsGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: the guard proves this thread holds the lock exclusively.
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T> Drop for McsGuard<'_, T> {
+    fn drop(&mut self) {
+        // SAFETY: the node is borrowed for the guard's lifetime, and the
+        // successor's node stays alive until its flag is cleared below.
+        unsafe {
+            let mut next = (*self.node).next.load(Ordering::Acquire);
+            if next.is_null() {
+                // No successor linked yet: either none exists and the queue
+                // can be emptied, or one has swapped itself in as the tail and
+                // is about to link, so wait for it.
+                if self
+                    .lock
+                    .tail
+                    .compare_exchange(
+                        self.node,
+                        ptr::null_mut(),
+                        Ordering::Release,
+                        Ordering::Relaxed,
+                    )
+                    .is_ok()
+                {
+                    return;
+                }
+                loop {
+                    next = (*self.node).next.load(Ordering::Acquire);
+                    if !next.is_null() {
+                        break;
+                    }
+                    spin_loop();
+                }
+            }
+            (*next).locked.store(false, Ordering::Release);
+        }
+    }
+}
@@ -0,0 +1,31 @@
+//! Spin locks that queue their waiters, showing why queue-based locks exist,
+//! and a sequence lock for small values that are read far more than written.
+//!
+//! A test-and-set spin lock has every waiter hammer the same cache line and
+//! hands the lock to whichever thread wins the race. `TicketLock` serves
+//! waiters strictly in arrival order, though they still all spin on one
+//! shared counter. `McsLock` gives each waiter its own queue node to spin
+//! on, so a release only touches the next waiter's cache line.
+//!
+//! Both spin without ever parking, so with more threads than cores a waiter
+//! whose turn has come may not even be running, and every thread behind it
+//! waits for the scheduler.
+//!
+//! `SeqLock` lets readers copy the value without writing to any shared
+//! state, retrying if a write overlapped, so reads never contend at all.
+
+pub mod mcs;
+pub mod seqlock;
+pub mod ticket;
+
+pub use mcs::{McsGuard, McsLock, McsNode};
+pub use seqlock::SeqLock;
+pub use ticket::{TicketLock, TicketLockGuard};
+
+/// The atomics and thread hints the locks are built on. `tests/loom_tests.rs`
+/// compiles the queue lock files against loom's versions instead, which is
+/// why the locks import them through this module.
+mod sync {
+    pub use std::hint::spin_loop;
+    pub use std::sync::atomic::{fence, AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+}
@@ -0,0 +1,92 @@
+use super::sync::{fence, spin_loop, AtomicUsize, Ordering};
+use std::cell::UnsafeCell;
+use std::ptr;
+
+/// A sequence lock: writers bump a sequence counter to an odd value, write,
+/// and bump it back to even, while readers copy the value without writing
+/// any shared state and retry if the sequence was odd or changed meanwhile.
+/// Reads therefore never contend with each other, but a steady stream of
+/// writes can keep a reader retrying indefinitely.
+///
+/// Only `Copy` types can be stored. A reader may copy the value while a
+/// write is halfway through and only afterwards notice and discard the
+/// copy, so the torn copy must be safe to make and to throw away. `Copy`
+/// rules out types with a destructor, and so anything that owns heap data,
+/// such as a `Vec` or `String`, whose torn pointer would otherwise be freed:
+///
+/// ```compile_fail
+/// use basic_rust_concurrency_benchmarks::locks::SeqLock;
+///
+/// let lock = SeqLock::new(vec![1u64, 2]);
+/// ```
+pub struct SeqLock<T: Copy> {
+    seq: AtomicUsize,
+    data: UnsafeCell<T>,
+}
+
+// SAFETY: writers are serialized by the odd sequence number, and readers
+// only keep copies made while no write was in progress.
+unsafe impl<T: Copy + Send> Sync for SeqLock<T> {}
+
+impl<T: Copy> SeqLock<T> {
+    pub fn new(data: T) -> Self {
+        SeqLock {
+            seq: AtomicUsize::new(0),
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    /// Returns a copy of the value, spinning with `spin_loop` while a write
+    /// is in progress and retrying whenever one overlapped the copy.
+    pub fn read(&self) -> T {
+        loop {
+            let before = self.seq.load(Ordering::Acquire);
+            if before % 2 == 1 {
+                spin_loop();
+                continue;
+            }
+            // SAFETY: the copy may race with a write, in which case the
+            // sequence check below discards it, and `T: Copy` makes a torn
+            // copy harmless to discard.
+            let value = unsafe { ptr::read_volatile(self.data.get()) };
+            // Keeps the copy above from being reordered after the load below.
+            fence(Ordering::Acquire);
+            if self.seq.load(Ordering::Relaxed) == before {
+                return value;
+            }
+        }
+    }
+
+    /// Replaces the value, spinning while another writer is in progress.
+    pub fn write(&self, value: T) {
+        let mut seq = self.seq.load(Ordering::Relaxed);
+        loop {
+            if seq % 2 == 1 {
+                spin_loop();
+                seq = self.seq.load(Ordering::Relaxed);
+                continue;
+            }
+            match self.seq.compare_exchange_weak(
+                seq,
+                seq.wrapping_add(1),
+                Ordering::Acquire,
+                Ordering::Relaxed,
+            ) {
+                Ok(_) => break,
+                Err(current) => seq = current,
+            }
+        }
+        // Keeps the odd sequence number visible before any byte of the write.
+        fence(Ordering::Release);
+        // SAFETY: the odd sequence number excludes other writers, and readers
+        // discard anything they copy while it is odd.
+        unsafe { ptr::write_volatile(self.data.get(), value) };
+        self.seq.store(seq.wrapping_add(2), Ordering::Release);
+    }
+}
+
+impl<T: Copy + Default> Default for SeqLock<T> {
+    fn default() -> Self {
+        SeqLock::new(T::default())
+    }
+}
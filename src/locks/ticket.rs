@@ -0,0 +1,73 @@
+use super::sync::{spin_loop, AtomicUsize, Ordering};
+use std::cell::UnsafeCell;
+use std::ops::{Deref, DerefMut};
+
+/// A ticket lock: every thread takes the next ticket from `next_ticket` and
+/// spins until `now_serving` reaches it, so the lock is granted strictly in
+/// the order threads asked for it. All waiters still spin on the same
+/// counter, which every release invalidates in every waiter's cache.
+pub struct TicketLock<T> {
+    next_ticket: AtomicUsize,
+    now_serving: AtomicUsize,
+    data: UnsafeCell<T>,
+}
+
+// SAFETY: access to `data` is serialized by the tickets, so the lock can be
+// shared between threads whenever the protected value can be sent between them.
+unsafe impl<T: Send> Sync for TicketLock<T> {}
+
+impl<T> TicketLock<T> {
+    pub fn new(data: T) -> Self {
+        TicketLock {
+            next_ticket: AtomicUsize::new(0),
+            now_serving: AtomicUsize::new(0),
+            data: UnsafeCell::new(data),
+        }
+    }
+
+    /// Takes a ticket and spins with `spin_loop` until it is served.
+    pub fn lock(&self) -> TicketLockGuard<'_, T> {
+        let ticket = self.next_ticket.fetch_add(1, Ordering::Relaxed);
+        while self.now_serving.load(Ordering::Acquire) != ticket {
+            spin_loop();
+        }
+        TicketLockGuard { lock: self, ticket }
+    }
+}
+
+impl<T: Default> Default for TicketLock<T> {
+    fn default() -> Self {
+        TicketLock::new(T::default())
+    }
+}
+
+/// RAII guard for a held `TicketLock`, which serves the next ticket on drop.
+pub struct TicketLockGuard<'a, T> {
+    lock: &'a TicketLock<T>,
+    ticket: usize,
+}
+
+impl<T> Deref for TicketLockGuard<'_, T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        // SAFETY: the guard proves this thread holds the lock.
+        unsafe { &*self.lock.data.get() }
+    }
+}
+
+impl<T> DerefMut for TicketLockGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        // SAFETY: the guard proves this thread holds the lock exclusively.
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+
+impl<T> Drop for TicketLockGuard<'_, T> {
+    fn drop(&mut self) {
+        // Only the holder writes `now_serving`, so a plain store suffices.
+        self.lock
+            .now_serving
+            .store(self.ticket.wrapping_add(1), Ordering::Release);
+    }
+}
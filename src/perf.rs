@@ -0,0 +1,129 @@
+//! Linux perf event counters around a scenario run, behind the `perf` feature.
+
+use std::sync::Once;
+
+/// Event counts for one scenario run, summed over every thread it spawned.
+/// A count is `None` when its counter could not be opened, which is common:
+/// `kernel.perf_event_paranoid` may forbid `perf_event_open` outright, and
+/// virtual machines often expose the software events but no hardware ones.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PerfCounts {
+    pub context_switches: Option<u64>,
+    pub cache_misses: Option<u64>,
+    pub cpu_cycles: Option<u64>,
+}
+
+/// Set once a perf counter warning has been printed.
+static WARNED: Once = Once::new();
+
+#[cfg(target_os = "linux")]
+mod counters {
+    use super::{PerfCounts, WARNED};
+    use crate::warn::warn_once;
+    use perf_event::events::{Event, Hardware, Software};
+    use perf_event::{Builder, Counter};
+    use std::io;
+
+    /// Opens and enables a counter for `event` on the calling thread that also
+    /// counts every thread it spawns afterwards. Time spent in the kernel is
+    /// counted too, which is where lock waits park and context switch.
+    fn open(event: Event, exclude_kernel: bool) -> io::Result<Counter> {
+        let mut builder = Builder::new().kind(event);
+        builder.inherit(true).exclude_kernel(exclude_kernel);
+        let mut counter = builder.build()?;
+        counter.enable()?;
+        Ok(counter)
+    }
+
+    /// The counters open for one run, each `None` if it could not be opened.
+    pub struct PerfCounters {
+        context_switches: Option<Counter>,
+        cache_misses: Option<Counter>,
+        cpu_cycles: Option<Counter>,
+    }
+
+    impl PerfCounters {
+        /// Opens and enables the counters on the calling thread. This must
+        /// happen before the scenario's threads are spawned, since inherited
+        /// counters only follow threads created after them. Counters that
+        /// cannot be opened are skipped with a warning.
+        pub fn start() -> Self {
+            let mut failures = vec![];
+            // Unprivileged users may only count user space under the default
+            // `perf_event_paranoid`. That is still meaningful for hardware
+            // events, but context switches only happen in the kernel.
+            let mut start = |name: &str, event: Event, user_only_fallback: bool| {
+                let counter = match open(event.clone(), false) {
+                    Err(err)
+                        if err.kind() == io::ErrorKind::PermissionDenied && user_only_fallback =>
+                    {
+                        open(event, true)
+                    }
+                    counter => counter,
+                };
+                counter
+                    .map_err(|err| failures.push(format!("{} ({})", name, err)))
+                    .ok()
+            };
+            let counters = PerfCounters {
+                context_switches: start(
+                    "context-switches",
+                    Software::CONTEXT_SWITCHES.into(),
+                    false,
+                ),
+                cache_misses: start("cache-misses", Hardware::CACHE_MISSES.into(), true),
+                cpu_cycles: start("cpu-cycles", Hardware::CPU_CYCLES.into(), true),
+            };
+            if !failures.is_empty() {
+                warn_once(&WARNED, || {
+                    format!(
+                        "cannot open perf counters for {}; check kernel.perf_event_paranoid \
+                         and whether the machine exposes hardware events, counting without them",
+                        failures.join(", ")
+                    )
+                });
+            }
+            counters
+        }
+
+        /// Stops the counters and returns their totals. Threads only add their
+        /// counts to an inherited counter when they exit, so call this after
+        /// the scenario's threads have been joined.
+        pub fn stop(self) -> PerfCounts {
+            let stop = |counter: Option<Counter>| {
+                let mut counter = counter?;
+                counter.disable().ok()?;
+                counter.read().ok()
+            };
+            PerfCounts {
+                context_switches: stop(self.context_switches),
+                cache_misses: stop(self.cache_misses),
+                cpu_cycles: stop(self.cpu_cycles),
+            }
+        }
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+mod counters {
+    use super::{PerfCounts, WARNED};
+    use crate::warn::warn_once;
+
+    /// Stands in for the Linux counters, which perf_event_open provides.
+    pub struct PerfCounters;
+
+    impl PerfCounters {
+        pub fn start() -> Self {
+            warn_once(&WARNED, || {
+                "perf counters are only available on Linux".to_string()
+            });
+            PerfCounters
+        }
+
+        pub fn stop(self) -> PerfCounts {
+            PerfCounts::default()
+        }
+    }
+}
+
+pub use counters::PerfCounters;
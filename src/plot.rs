@@ -0,0 +1,194 @@
+//! Scaling charts, ops/sec against thread count with one line per primitive.
+//! Drawing them needs the `plot` feature.
+//!
+//! Criterion plots every benchmark on its own axes; these overlay every
+//! primitive of a workload on one, which is the comparison that matters when
+//! choosing a lock.
+
+use crate::report::ScenarioReport;
+#[cfg(feature = "plot")]
+use plotters::prelude::*;
+use std::path::{Path, PathBuf};
+
+/// One primitive's line on a scaling chart.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScalingSeries {
+    pub primitive: String,
+    /// The primitive's ops/sec at each of the chart's thread counts, or
+    /// `None` where it was not run.
+    pub ops_per_sec: Vec<Option<f64>>,
+}
+
+/// The scaling chart of one workload.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScalingChart {
+    pub workload: String,
+    /// Every thread count any primitive was run at, in increasing order.
+    pub thread_counts: Vec<usize>,
+    pub series: Vec<ScalingSeries>,
+}
+
+impl ScalingChart {
+    /// The runs of consecutive thread counts at which `series` has a value,
+    /// as `(threads, ops_per_sec)` points. A thread count the primitive was
+    /// not run at ends one run and the next value starts another, so the
+    /// chart breaks the line there rather than interpolating across it.
+    pub fn segments(&self, series: &ScalingSeries) -> Vec<Vec<(usize, f64)>> {
+        let mut segments = vec![];
+        let mut current = vec![];
+        for (&threads, ops_per_sec) in self.thread_counts.iter().zip(&series.ops_per_sec) {
+            match ops_per_sec {
+                Some(ops_per_sec) => current.push((threads, *ops_per_sec)),
+                None if !current.is_empty() => segments.push(std::mem::take(&mut current)),
+                None => {}
+            }
+        }
+        if !current.is_empty() {
+            segments.push(current);
+        }
+        segments
+    }
+}
+
+/// Groups `reports` into one chart per workload, in order of first
+/// appearance, with one series per primitive. When several reports share a
+/// primitive, workload, and thread count, as named scenarios that differ only
+/// in their stall do, the last one is plotted.
+pub fn scaling_charts(reports: &[ScenarioReport]) -> Vec<ScalingChart> {
+    let mut workloads: Vec<&str> = vec![];
+    for report in reports {
+        if !workloads.contains(&report.workload.as_str()) {
+            workloads.push(&report.workload);
+        }
+    }
+    workloads
+        .into_iter()
+        .map(|workload| {
+            let reports: Vec<&ScenarioReport> = reports
+                .iter()
+                .filter(|report| report.workload == workload)
+                .collect();
+            let mut thread_counts: Vec<usize> =
+                reports.iter().map(|report| report.threads).collect();
+            thread_counts.sort_unstable();
+            thread_counts.dedup();
+            let mut primitives: Vec<&str> = vec![];
+            for report in &reports {
+                if !primitives.contains(&report.primitive.as_str()) {
+                    primitives.push(&report.primitive);
+                }
+            }
+            let series = primitives
+                .into_iter()
+                .map(|primitive| ScalingSeries {
+                    primitive: primitive.to_string(),
+                    ops_per_sec: thread_counts
+                        .iter()
+                        .map(|&threads| {
+                            reports
+                                .iter()
+                                .rev()
+                                .find(|report| {
+                                    report.primitive == primitive && report.threads == threads
+                                })
+                                .map(|report| report.ops_per_sec)
+                        })
+                        .collect(),
+                })
+                .collect();
+            ScalingChart {
+                workload: workload.to_string(),
+                thread_counts,
+                series,
+            }
+        })
+        .collect()
+}
+
+/// Writes every chart of `scaling_charts(reports)` to `out_dir` as
+/// `{workload}.svg`, creating the directory if needed, and returns the paths
+/// written.
+#[cfg(feature = "plot")]
+pub fn write_scaling_charts(
+    reports: &[ScenarioReport],
+    out_dir: &Path,
+) -> Result<Vec<PathBuf>, String> {
+    std::fs::create_dir_all(out_dir)
+        .map_err(|err| format!("could not create {}: {}", out_dir.display(), err))?;
+    scaling_charts(reports)
+        .iter()
+        .map(|chart| {
+            let path = out_dir.join(format!("{}.svg", chart.workload));
+            draw(chart, &path)
+                .map_err(|err| format!("could not draw {}: {}", path.display(), err))?;
+            Ok(path)
+        })
+        .collect()
+}
+
+/// Without the `plot` feature, charts cannot be drawn.
+#[cfg(not(feature = "plot"))]
+pub fn write_scaling_charts(_: &[ScenarioReport], _: &Path) -> Result<Vec<PathBuf>, String> {
+    Err("drawing charts needs the `plot` feature".to_string())
+}
+
+#[cfg(feature = "plot")]
+fn draw(chart: &ScalingChart, path: &Path) -> Result<(), Box<dyn std::error::Error>> {
+    let root = SVGBackend::new(path, (800, 500)).into_drawing_area();
+    root.fill(&WHITE)?;
+    let max_ops_per_sec = chart
+        .series
+        .iter()
+        .flat_map(|series| series.ops_per_sec.iter().flatten())
+        .fold(0.0, |max: f64, &ops_per_sec| max.max(ops_per_sec));
+    let first = chart.thread_counts[0];
+    let last = chart.thread_counts[chart.thread_counts.len() - 1];
+    let mut context = ChartBuilder::on(&root)
+        .caption(
+            format!("{}: ops/sec by thread count", chart.workload),
+            ("sans-serif", 24),
+        )
+        .margin(20)
+        .x_label_area_size(40)
+        .y_label_area_size(90)
+        .build_cartesian_2d(
+            first.saturating_sub(1)..last + 1,
+            0.0..(max_ops_per_sec * 1.1).max(1.0),
+        )?;
+    context
+        .configure_mesh()
+        .x_desc("threads")
+        .y_desc("ops/sec")
+        .y_label_formatter(&|ops_per_sec| format!("{:.0}", ops_per_sec))
+        .draw()?;
+    for (index, series) in chart.series.iter().enumerate() {
+        let color = Palette99::pick(index).to_rgba();
+        let segments = chart.segments(series);
+        for (segment_index, segment) in segments.iter().enumerate() {
+            let line = context.draw_series(LineSeries::new(
+                segment.iter().copied(),
+                color.stroke_width(2),
+            ))?;
+            if segment_index == 0 {
+                line.label(series.primitive.as_str()).legend(move |(x, y)| {
+                    PathElement::new(vec![(x, y), (x + 20, y)], color.stroke_width(2))
+                });
+            }
+        }
+        // Points mark every measurement, so a run at a single thread count
+        // between two gaps still shows up.
+        context.draw_series(
+            segments
+                .iter()
+                .flatten()
+                .map(|&point| Circle::new(point, 3, color.filled())),
+        )?;
+    }
+    context
+        .configure_series_labels()
+        .background_style(WHITE.mix(0.8))
+        .border_style(BLACK)
+        .draw()?;
+    root.present()?;
+    Ok(())
+}
@@ -0,0 +1,83 @@
+//! Flamegraphs for criterion benchmarks, behind the `profiling` feature.
+
+use criterion::profiler::Profiler;
+use pprof::{ProfilerGuard, ProfilerGuardBuilder};
+use std::fs::{self, File};
+use std::path::Path;
+
+/// Samples per second the profiler takes by default. High enough to resolve
+/// microsecond-scale lock operations in a 10 second `--profile-time` run,
+/// low enough that the signal handler barely perturbs them.
+pub const DEFAULT_FREQUENCY: i32 = 997;
+
+/// A criterion `Profiler` that samples the bench process with pprof and writes
+/// `flamegraph.svg` into the directory criterion passes, which for a
+/// `--profile-time` run is `target/criterion/<name>/profile/`.
+///
+/// Criterion only calls a profiler in `--profile-time` mode, which runs the
+/// benchmark for the given time without taking measurements, so a normal
+/// `cargo bench` never starts sampling and its numbers are unaffected.
+/// Sampling starts just before criterion's profiling iterations and the
+/// flamegraph is symbolized and written only after they have stopped.
+pub struct FlamegraphProfiler {
+    frequency: i32,
+    active: Option<ProfilerGuard<'static>>,
+}
+
+impl FlamegraphProfiler {
+    /// Creates a profiler taking `frequency` samples per second.
+    pub fn new(frequency: i32) -> Self {
+        FlamegraphProfiler {
+            frequency,
+            active: None,
+        }
+    }
+}
+
+impl Default for FlamegraphProfiler {
+    fn default() -> Self {
+        FlamegraphProfiler::new(DEFAULT_FREQUENCY)
+    }
+}
+
+// A failed profile should not abort the remaining benchmarks, so errors are
+// reported on stderr and the benchmark carries on unprofiled.
+
+impl Profiler for FlamegraphProfiler {
+    fn start_profiling(&mut self, benchmark_id: &str, _benchmark_dir: &Path) {
+        // Samples inside libc and the vDSO cannot be unwound reliably.
+        let guard = ProfilerGuardBuilder::default()
+            .frequency(self.frequency)
+            .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+            .build();
+        match guard {
+            Ok(guard) => self.active = Some(guard),
+            Err(err) => eprintln!("warning: cannot profile {}: {}", benchmark_id, err),
+        }
+    }
+
+    fn stop_profiling(&mut self, benchmark_id: &str, benchmark_dir: &Path) {
+        let Some(guard) = self.active.take() else {
+            return;
+        };
+        let path = benchmark_dir.join("flamegraph.svg");
+        let written = guard
+            .report()
+            .build()
+            .map_err(|err| err.to_string())
+            .and_then(|report| {
+                fs::create_dir_all(benchmark_dir).map_err(|err| err.to_string())?;
+                let file = File::create(&path).map_err(|err| err.to_string())?;
+                report.flamegraph(file).map_err(|err| err.to_string())
+            });
+        match written {
+            Ok(()) => println!("{}: flamegraph written to {}", benchmark_id, path.display()),
+            Err(err) => eprintln!(
+                "warning: cannot write flamegraph for {} to {}: {}",
+                benchmark_id,
+                path.display(),
+                err
+            ),
+        }
+    }
+}
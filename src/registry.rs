@@ -0,0 +1,183 @@
+//! The one table of named primitives and scenarios that the criterion
+//! benchmarks, `concbench run`, and `concbench list` all read, so a scenario
+//! cannot exist under a name in one of them and not the others.
+//!
+//! `PRIMITIVES` holds every counter the command-line runner can drive, and
+//! `SCENARIOS` every primitive/workload pair the lock matrix benchmarks, named
+//! as criterion names them.
+//!
+//! `run_configured` runs a list of scenario file entries against these
+//! primitives, stopping early if the runs are cancelled.
+
+use crate::config::{RunLength, ScenarioConfig};
+use crate::counter::{FairMutex, SharedCounter};
+use crate::scenario::{
+    run_contention_scenario, run_for_duration, runs_cancelled, Scenario, ScenarioResult, Workload,
+};
+use std::sync::atomic::AtomicU64;
+use std::sync::{Arc, Mutex, RwLock};
+
+/// A counter the scenario runner can drive, by command-line name.
+#[derive(Clone, Copy)]
+pub struct Primitive {
+    pub name: &'static str,
+    run: fn(&Scenario, RunLength) -> ScenarioResult,
+}
+
+impl Primitive {
+    /// Runs `scenario` against a fresh counter starting at 0, for `length`
+    /// rather than the scenario's own `ops_per_thread`.
+    pub fn run(&self, scenario: &Scenario, length: RunLength) -> ScenarioResult {
+        (self.run)(scenario, length)
+    }
+}
+
+fn run_counter<C: SharedCounter + From<u64> + 'static>(
+    scenario: &Scenario,
+    length: RunLength,
+) -> ScenarioResult {
+    let state = Arc::new(C::from(0));
+    match length {
+        RunLength::Ops(ops) => {
+            let mut scenario = *scenario;
+            scenario.ops_per_thread = ops;
+            run_contention_scenario(&scenario, &state)
+        }
+        RunLength::Duration(duration) => run_for_duration(scenario, &state, duration),
+    }
+}
+
+/// Every primitive the command-line runner accepts.
+pub static PRIMITIVES: [Primitive; 6] = [
+    Primitive {
+        name: "mutex",
+        run: run_counter::<Mutex<u64>>,
+    },
+    Primitive {
+        name: "rwlock",
+        run: run_counter::<RwLock<u64>>,
+    },
+    Primitive {
+        name: "parking_lot_mutex",
+        run: run_counter::<parking_lot::Mutex<u64>>,
+    },
+    Primitive {
+        name: "parking_lot_fair_mutex",
+        run: run_counter::<FairMutex>,
+    },
+    Primitive {
+        name: "parking_lot_rwlock",
+        run: run_counter::<parking_lot::RwLock<u64>>,
+    },
+    Primitive {
+        name: "atomic",
+        run: run_counter::<AtomicU64>,
+    },
+];
+
+/// The names in `PRIMITIVES`, in order.
+pub fn primitive_names() -> Vec<&'static str> {
+    PRIMITIVES.iter().map(|primitive| primitive.name).collect()
+}
+
+/// Looks up a primitive in `PRIMITIVES` by name.
+pub fn primitive(name: &str) -> Option<&'static Primitive> {
+    PRIMITIVES.iter().find(|primitive| primitive.name == name)
+}
+
+/// Runs `scenarios` in turn against the primitives they name, with `prepare`
+/// applied to each one's `ScenarioConfig::scenario` first, and returns one
+/// result per scenario, in order.
+///
+/// Once `cancel_runs` has been called, no further scenario starts and the one
+/// in progress, which stops early, is left out, so every result returned is
+/// of a complete run; fewer results than scenarios means the runs were
+/// cancelled. A scenario of `RunLength::Ops` cannot stop early, so it still
+/// finishes before being left out.
+///
+/// # Panics
+///
+/// If a scenario's primitive is not in `PRIMITIVES`.
+pub fn run_configured(
+    scenarios: &[ScenarioConfig],
+    prepare: impl Fn(Scenario) -> Scenario,
+) -> Vec<ScenarioResult> {
+    let mut results = vec![];
+    for config in scenarios {
+        if runs_cancelled() {
+            break;
+        }
+        let primitive = primitive(&config.primitive)
+            .unwrap_or_else(|| panic!("unknown primitive `{}`", config.primitive));
+        let result = primitive.run(&prepare(config.scenario()), config.length);
+        if runs_cancelled() {
+            break;
+        }
+        results.push(result);
+    }
+    results
+}
+
+/// A named primitive/workload pair. Thread count, stall, and run length are
+/// left to whoever runs it: the benchmarks sweep them, and the command-line
+/// runner takes them from its options.
+#[derive(Clone, Copy, Debug)]
+pub struct RegisteredScenario {
+    /// The criterion benchmark name prefix, `{lock}_{workload}`.
+    pub name: &'static str,
+    /// A name in `PRIMITIVES`.
+    pub primitive: &'static str,
+    pub workload: Workload,
+}
+
+const fn scenario(
+    name: &'static str,
+    primitive: &'static str,
+    workload: Workload,
+) -> RegisteredScenario {
+    RegisteredScenario {
+        name,
+        primitive,
+        workload,
+    }
+}
+
+/// Every scenario of the lock matrix benchmarks.
+pub static SCENARIOS: [RegisteredScenario; 12] = [
+    scenario("arc_mutex_read_heavy", "mutex", Workload::ReadHeavy),
+    scenario("arc_mutex_write_heavy", "mutex", Workload::WriteHeavy),
+    scenario("arc_mutex_mixed", "mutex", Workload::Mixed),
+    scenario("arc_rwlock_read_heavy", "rwlock", Workload::ReadHeavy),
+    scenario("arc_rwlock_write_heavy", "rwlock", Workload::WriteHeavy),
+    scenario("arc_rwlock_mixed", "rwlock", Workload::Mixed),
+    scenario(
+        "parking_lot_mutex_read_heavy",
+        "parking_lot_mutex",
+        Workload::ReadHeavy,
+    ),
+    scenario(
+        "parking_lot_mutex_write_heavy",
+        "parking_lot_mutex",
+        Workload::WriteHeavy,
+    ),
+    scenario(
+        "parking_lot_mutex_mixed",
+        "parking_lot_mutex",
+        Workload::Mixed,
+    ),
+    scenario(
+        "parking_lot_rwlock_read_heavy",
+        "parking_lot_rwlock",
+        Workload::ReadHeavy,
+    ),
+    scenario(
+        "parking_lot_rwlock_write_heavy",
+        "parking_lot_rwlock",
+        Workload::WriteHeavy,
+    ),
+    scenario(
+        "parking_lot_rwlock_mixed",
+        "parking_lot_rwlock",
+        Workload::Mixed,
+    ),
+];
@@ -0,0 +1,456 @@
+use crate::scenario::{available_threads, ScenarioResult, Workload};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fmt;
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::sync::OnceLock;
+
+/// Version of the `ScenarioReport` JSON format. Bump it whenever a field is
+/// added, removed, or changes meaning, so downstream tooling can tell formats apart.
+pub const SCHEMA_VERSION: u32 = 7;
+
+/// Header of the per-thread CSV written by `ScenarioResult::write_csv`.
+/// The columns after `critical_section_ns` repeat the `HostInfo` on every row.
+pub const CSV_HEADER: &str = "scenario,thread,ops,busy_ns,wait_ns,critical_section_ns,\
+logical_cores,physical_cores,cpu_model,os,arch,rustc_version";
+
+/// Stands in for host details that could not be probed.
+const UNKNOWN: &str = "unknown";
+
+/// The machine a scenario ran on. Reports written before schema version 2
+/// called `logical_cores` `cores` and lack the other probed fields, which
+/// read back as `unknown`, or as 0 for `physical_cores`.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HostInfo {
+    /// Available parallelism, which is usually the number of logical cores.
+    #[serde(alias = "cores")]
+    pub logical_cores: usize,
+    /// Physical cores as reported by the platform, or the logical core count
+    /// where the platform cannot tell them apart.
+    #[serde(default)]
+    pub physical_cores: usize,
+    #[serde(default = "unknown")]
+    pub cpu_model: String,
+    pub os: String,
+    pub arch: String,
+    /// The `rustc --version` of the compiler that built the crate.
+    #[serde(default = "unknown")]
+    pub rustc_version: String,
+}
+
+fn unknown() -> String {
+    UNKNOWN.to_string()
+}
+
+impl HostInfo {
+    /// Describes the current machine. The platform is only probed once per
+    /// process; later calls return a copy.
+    pub fn current() -> Self {
+        static CURRENT: OnceLock<HostInfo> = OnceLock::new();
+        CURRENT
+            .get_or_init(|| {
+                let logical_cores = available_threads();
+                HostInfo {
+                    logical_cores,
+                    physical_cores: physical_cores()
+                        .filter(|&cores| cores > 0)
+                        .unwrap_or(logical_cores),
+                    cpu_model: cpu_model().unwrap_or_else(unknown),
+                    os: env::consts::OS.to_string(),
+                    arch: env::consts::ARCH.to_string(),
+                    rustc_version: env!("RUSTC_VERSION").to_string(),
+                }
+            })
+            .clone()
+    }
+}
+
+impl fmt::Display for HostInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} logical / {} physical cores, {}, {} {}, {}",
+            self.logical_cores,
+            self.physical_cores,
+            self.cpu_model,
+            self.os,
+            self.arch,
+            self.rustc_version
+        )
+    }
+}
+
+/// Counts distinct (package, core) pairs in the topology Linux reports under `/sys`.
+#[cfg(target_os = "linux")]
+fn physical_cores() -> Option<usize> {
+    let mut cores = Vec::new();
+    for entry in fs::read_dir("/sys/devices/system/cpu").ok()? {
+        let path = entry.ok()?.path();
+        let is_cpu = path
+            .file_name()
+            .and_then(|name| name.to_str())
+            .and_then(|name| name.strip_prefix("cpu"))
+            .is_some_and(|id| !id.is_empty() && id.bytes().all(|b| b.is_ascii_digit()));
+        if !is_cpu {
+            continue;
+        }
+        let read = |file| fs::read_to_string(path.join("topology").join(file)).ok();
+        let core = (read("physical_package_id")?, read("core_id")?);
+        if !cores.contains(&core) {
+            cores.push(core);
+        }
+    }
+    Some(cores.len())
+}
+
+/// Asks `sysctl` for the physical core count.
+#[cfg(target_os = "macos")]
+fn physical_cores() -> Option<usize> {
+    sysctl("hw.physicalcpu")?.parse().ok()
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn physical_cores() -> Option<usize> {
+    None
+}
+
+/// Reads the first `model name` from `/proc/cpuinfo`.
+#[cfg(target_os = "linux")]
+fn cpu_model() -> Option<String> {
+    let cpuinfo = fs::read_to_string("/proc/cpuinfo").ok()?;
+    cpuinfo.lines().find_map(|line| {
+        let (key, value) = line.split_once(':')?;
+        (key.trim() == "model name").then(|| value.trim().to_string())
+    })
+}
+
+/// Asks `sysctl` for the CPU brand string.
+#[cfg(target_os = "macos")]
+fn cpu_model() -> Option<String> {
+    sysctl("machdep.cpu.brand_string")
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn cpu_model() -> Option<String> {
+    None
+}
+
+/// Returns the value of the sysctl `name`.
+#[cfg(target_os = "macos")]
+fn sysctl(name: &str) -> Option<String> {
+    let output = std::process::Command::new("sysctl")
+        .args(["-n", name])
+        .output()
+        .ok()?;
+    let value = String::from_utf8(output.stdout).ok()?;
+    output.status.success().then(|| value.trim().to_string())
+}
+
+/// Quotes `field` for CSV if it contains a comma, quote, or newline.
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// A machine-readable summary of one scenario run, serialized as one JSON object.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ScenarioReport {
+    pub schema_version: u32,
+    /// The scenario's name for runs of named scenarios, from the registry or
+    /// a scenario file, or `None` for runs configured any other way. Reports
+    /// before schema version 5 lack it.
+    pub name: Option<String>,
+    pub primitive: String,
+    pub workload: String,
+    pub threads: usize,
+    /// The seed the run's reads and writes were drawn from, so it can be
+    /// replayed with `Scenario::with_seed`. Reports before schema version 7
+    /// lack it.
+    pub seed: Option<u64>,
+    pub ops: u64,
+    pub duration_ns: u64,
+    pub ops_per_sec: f64,
+    /// CPU time of all threads, and that divided by `duration_ns`, or `None`
+    /// when it was not measured, which needs the `cpu-time` feature on Linux
+    /// or macOS. Reports before schema version 4 lack them.
+    pub cpu_time_ns: Option<u64>,
+    pub efficiency: Option<f64>,
+    /// Lock acquisition latency percentiles, or `None` when they were not
+    /// collected, which needs the `latency` feature and `Scenario::with_latency`.
+    pub p50_latency_ns: Option<u64>,
+    pub p99_latency_ns: Option<u64>,
+    /// Perf event counts for the whole run, or `None` when they were not
+    /// collected, which needs the `perf` feature, Linux, and
+    /// `Scenario::with_perf_counters`, or when the counter could not be opened.
+    /// Reports before schema version 3 lack them.
+    pub context_switches: Option<u64>,
+    pub cache_misses: Option<u64>,
+    pub cpu_cycles: Option<u64>,
+    /// Context switches across the whole process during the run, from
+    /// `getrusage`, or `None` when they were not counted, which needs the
+    /// `context-switches` feature on Linux. Reports before schema version 6
+    /// lack them.
+    pub voluntary_context_switches: Option<u64>,
+    pub involuntary_context_switches: Option<u64>,
+    pub host: HostInfo,
+}
+
+impl ScenarioReport {
+    /// Whether `other` ran the same primitive and workload with the same number
+    /// of threads under the same scenario name, so the two can be compared.
+    /// Unnamed reports, including all from before schema version 5, only
+    /// match other unnamed ones.
+    pub fn same_scenario(&self, other: &ScenarioReport) -> bool {
+        self.name == other.name
+            && self.primitive == other.primitive
+            && self.workload == other.workload
+            && self.threads == other.threads
+    }
+
+    /// The scenario's name, or its primitive, workload, and thread count if
+    /// it has none.
+    pub fn label(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => format!(
+                "{} {} with {} threads",
+                self.primitive, self.workload, self.threads
+            ),
+        }
+    }
+}
+
+/// Reads reports written by `write_reports`, one JSON object per line.
+pub fn read_reports(path: &Path) -> io::Result<Vec<ScenarioReport>> {
+    fs::read_to_string(path)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| serde_json::from_str(line).map_err(io::Error::from))
+        .collect()
+}
+
+/// Writes `reports` to `path`, one JSON object per line.
+pub fn write_reports(path: &Path, reports: &[ScenarioReport]) -> io::Result<()> {
+    let mut out = String::new();
+    for report in reports {
+        out += &serde_json::to_string(report).map_err(io::Error::from)?;
+        out.push('\n');
+    }
+    fs::write(path, out)
+}
+
+/// A scenario whose throughput dropped by more than the allowed percentage
+/// compared with its baseline.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Regression {
+    pub primitive: String,
+    pub workload: String,
+    pub threads: usize,
+    pub baseline_ops_per_sec: f64,
+    pub current_ops_per_sec: f64,
+}
+
+impl Regression {
+    /// How much slower the current run is, as a percentage of the baseline.
+    pub fn percent(&self) -> f64 {
+        (self.baseline_ops_per_sec - self.current_ops_per_sec) / self.baseline_ops_per_sec * 100.0
+    }
+}
+
+impl fmt::Display for Regression {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} with {} threads regressed {:.1}%: {:.0} ops/sec in the baseline, {:.0} ops/sec now",
+            self.primitive,
+            self.workload,
+            self.threads,
+            self.percent(),
+            self.baseline_ops_per_sec,
+            self.current_ops_per_sec
+        )
+    }
+}
+
+/// Compares every report in `current` with the report for the same scenario in
+/// `baseline`, and returns the ones whose ops/sec dropped by more than
+/// `max_regression_percent`. Scenarios missing from the baseline are skipped.
+pub fn find_regressions(
+    baseline: &[ScenarioReport],
+    current: &[ScenarioReport],
+    max_regression_percent: f64,
+) -> Vec<Regression> {
+    current
+        .iter()
+        .filter_map(|report| {
+            let old = baseline.iter().find(|old| old.same_scenario(report))?;
+            let regression = Regression {
+                primitive: report.primitive.clone(),
+                workload: report.workload.clone(),
+                threads: report.threads,
+                baseline_ops_per_sec: old.ops_per_sec,
+                current_ops_per_sec: report.ops_per_sec,
+            };
+            (regression.percent() > max_regression_percent).then_some(regression)
+        })
+        .collect()
+}
+
+/// One scenario found in both sets of reports passed to `diff_reports`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReportChange {
+    pub old: ScenarioReport,
+    pub new: ScenarioReport,
+}
+
+impl ReportChange {
+    /// The change in ops/sec as a percentage of the old value, positive when
+    /// the new run is faster.
+    pub fn ops_per_sec_percent(&self) -> f64 {
+        (self.new.ops_per_sec - self.old.ops_per_sec) / self.old.ops_per_sec * 100.0
+    }
+
+    /// The change in p99 lock acquisition latency as a percentage of the old
+    /// value, positive when the new run is slower, or `None` unless both runs
+    /// collected latency.
+    pub fn p99_latency_percent(&self) -> Option<f64> {
+        let (old, new) = self.old.p99_latency_ns.zip(self.new.p99_latency_ns)?;
+        (old > 0).then(|| (new as f64 - old as f64) / old as f64 * 100.0)
+    }
+
+    /// How much worse the new run is, as the larger of its ops/sec drop and
+    /// its p99 latency rise in percent; zero or negative if neither got worse.
+    pub fn regression_percent(&self) -> f64 {
+        let throughput = -self.ops_per_sec_percent();
+        self.p99_latency_percent()
+            .map_or(throughput, |latency| throughput.max(latency))
+    }
+
+    /// The largest change in either direction, which `diff_reports` sorts by.
+    fn magnitude(&self) -> f64 {
+        let throughput = self.ops_per_sec_percent().abs();
+        self.p99_latency_percent()
+            .map_or(throughput, |latency| throughput.max(latency.abs()))
+    }
+}
+
+/// The result of `diff_reports`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ReportDiff {
+    /// Scenarios in both sets, largest change first.
+    pub changes: Vec<ReportChange>,
+    /// Scenarios only in the old set.
+    pub only_old: Vec<ScenarioReport>,
+    /// Scenarios only in the new set.
+    pub only_new: Vec<ScenarioReport>,
+}
+
+/// Matches every report in `new` with the report for the same scenario in
+/// `old`, as `ScenarioReport::same_scenario` decides, and sorts the matches
+/// by the magnitude of their change. Scenarios in only one set are returned
+/// separately.
+pub fn diff_reports(old: &[ScenarioReport], new: &[ScenarioReport]) -> ReportDiff {
+    let mut diff = ReportDiff::default();
+    for report in new {
+        match old.iter().find(|old| old.same_scenario(report)) {
+            Some(old) => diff.changes.push(ReportChange {
+                old: old.clone(),
+                new: report.clone(),
+            }),
+            None => diff.only_new.push(report.clone()),
+        }
+    }
+    diff.only_old = old
+        .iter()
+        .filter(|old| !new.iter().any(|report| report.same_scenario(old)))
+        .cloned()
+        .collect();
+    diff.changes
+        .sort_by(|a, b| b.magnitude().total_cmp(&a.magnitude()));
+    diff
+}
+
+impl ScenarioResult {
+    /// Summarizes the result of running `workload` against `primitive`.
+    pub fn report(&self, primitive: &str, workload: Workload) -> ScenarioReport {
+        #[cfg(feature = "latency")]
+        let percentile = |quantile| {
+            self.acquisition_latency
+                .as_ref()
+                .map(|histogram| histogram.value_at_quantile(quantile))
+        };
+        #[cfg(not(feature = "latency"))]
+        let percentile = |_: f64| None;
+        #[cfg(feature = "perf")]
+        let (context_switches, cache_misses, cpu_cycles) = {
+            let perf = self.perf_counts.unwrap_or_default();
+            (perf.context_switches, perf.cache_misses, perf.cpu_cycles)
+        };
+        #[cfg(not(feature = "perf"))]
+        let (context_switches, cache_misses, cpu_cycles) = (None, None, None);
+        ScenarioReport {
+            schema_version: SCHEMA_VERSION,
+            name: None,
+            primitive: primitive.to_string(),
+            workload: workload.name().to_string(),
+            threads: self.threads.len(),
+            seed: Some(self.seed),
+            ops: self.ops(),
+            duration_ns: self.elapsed.as_nanos() as u64,
+            ops_per_sec: self.ops_per_sec(),
+            cpu_time_ns: self.cpu_time().map(|cpu| cpu.as_nanos() as u64),
+            efficiency: self.efficiency(),
+            p50_latency_ns: percentile(0.5),
+            p99_latency_ns: percentile(0.99),
+            context_switches,
+            cache_misses,
+            cpu_cycles,
+            voluntary_context_switches: self.context_switches.map(|switches| switches.voluntary),
+            involuntary_context_switches: self
+                .context_switches
+                .map(|switches| switches.involuntary),
+            host: HostInfo::current(),
+        }
+    }
+
+    /// Serializes `report(primitive, workload)` as a single-line JSON object.
+    pub fn to_json(&self, primitive: &str, workload: Workload) -> String {
+        serde_json::to_string(&self.report(primitive, workload))
+            .expect("a scenario report always serializes")
+    }
+
+    /// Writes one CSV row per thread, in `CSV_HEADER` order, labelled with
+    /// `scenario` and followed by the current `HostInfo`. Wait and critical
+    /// section times are zero unless the scenario recorded timing.
+    pub fn write_csv(&self, scenario: &str, out: &mut impl Write) -> io::Result<()> {
+        let host = HostInfo::current();
+        let host = format!(
+            "{},{},{},{},{},{}",
+            host.logical_cores,
+            host.physical_cores,
+            csv_field(&host.cpu_model),
+            csv_field(&host.os),
+            csv_field(&host.arch),
+            csv_field(&host.rustc_version)
+        );
+        for (index, thread) in self.threads.iter().enumerate() {
+            writeln!(
+                out,
+                "{},{},{},{},{},{},{}",
+                csv_field(scenario),
+                index,
+                thread.ops(),
+                thread.busy.as_nanos(),
+                thread.waiting.as_nanos(),
+                thread.in_critical_section.as_nanos(),
+                host
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,19 @@
+//! The process's resident set size, for spotting memory growth over a long
+//! run.
+
+/// The resident set size of the process in bytes, read from
+/// `/proc/self/status`.
+#[cfg(target_os = "linux")]
+pub fn resident_set_size() -> Option<u64> {
+    let status = std::fs::read_to_string("/proc/self/status").ok()?;
+    let line = status.lines().find(|line| line.starts_with("VmRSS:"))?;
+    // The line reads `VmRSS:     1234 kB`.
+    let kib: u64 = line.split_whitespace().nth(1)?.parse().ok()?;
+    Some(kib * 1024)
+}
+
+/// On other platforms the resident set size is not read.
+#[cfg(not(target_os = "linux"))]
+pub fn resident_set_size() -> Option<u64> {
+    None
+}
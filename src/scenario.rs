@@ -0,0 +1,892 @@
+#[cfg(feature = "pin")]
+use crate::affinity;
+use crate::context_switches::{process_context_switches, ContextSwitches};
+use crate::counter::{LockTiming, SharedCounter};
+use crate::cpu_time::thread_cpu_time;
+#[cfg(feature = "latency")]
+use crate::latency::{self, Histogram};
+#[cfg(feature = "perf")]
+use crate::perf::{PerfCounters, PerfCounts};
+use crate::stall::{LockStall, StallPlacement, StallPolicy};
+use crate::work::busy_wait;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::fmt;
+use std::hint::black_box;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Barrier};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// A shared integer that contention scenarios read from and write to.
+/// `read` returns the current value and `write` increments it by one.
+/// `read_holding` and `write_holding` additionally run `hold` while any lock
+/// is still held, which is how a stall inside the critical section is modelled;
+/// lock-free implementations have nothing to hold and keep the default,
+/// which runs `hold` after the operation.
+/// Every `Arc`-wrapped `SharedCounter` implements it.
+/// On `Arc<RwLock<u64>>` these `read` and `write` methods shadow the lock's own,
+/// so code that also locks the RwLock directly should name this trait by path
+/// rather than importing it.
+pub trait SharedState: Clone + Send + 'static {
+    fn read(&self) -> u64;
+
+    fn write(&self);
+
+    fn read_holding(&self, hold: impl FnOnce()) -> u64 {
+        let value = self.read();
+        hold();
+        value
+    }
+
+    fn write_holding(&self, hold: impl FnOnce()) {
+        self.write();
+        hold();
+    }
+
+    /// Like `read_holding`, but also reports how long the read waited for and
+    /// then held any lock. The default counts the whole read as held.
+    fn read_timed(&self, hold: impl FnOnce()) -> (u64, LockTiming) {
+        let start = Instant::now();
+        let value = self.read();
+        let timing = LockTiming {
+            waiting: Duration::ZERO,
+            in_critical_section: start.elapsed(),
+        };
+        hold();
+        (value, timing)
+    }
+
+    /// Like `write_holding`, but also reports how long the write waited for and
+    /// then held any lock. The default counts the whole write as held.
+    fn write_timed(&self, hold: impl FnOnce()) -> LockTiming {
+        let start = Instant::now();
+        self.write();
+        let timing = LockTiming {
+            waiting: Duration::ZERO,
+            in_critical_section: start.elapsed(),
+        };
+        hold();
+        timing
+    }
+}
+
+/// Every shared counter behind an `Arc` is shared state: reads are `get` and
+/// writes are `increment`, so any `SharedCounter` can run a contention scenario.
+impl<C: SharedCounter + 'static> SharedState for Arc<C> {
+    fn read(&self) -> u64 {
+        self.get()
+    }
+
+    fn write(&self) {
+        self.increment();
+    }
+
+    fn read_holding(&self, hold: impl FnOnce()) -> u64 {
+        self.get_holding(hold)
+    }
+
+    fn write_holding(&self, hold: impl FnOnce()) {
+        self.increment_holding(hold);
+    }
+
+    fn read_timed(&self, hold: impl FnOnce()) -> (u64, LockTiming) {
+        self.get_timed(hold)
+    }
+
+    fn write_timed(&self, hold: impl FnOnce()) -> LockTiming {
+        self.increment_timed(hold)
+    }
+}
+
+/// A named read/write mix: read-heavy scenarios only read, write-heavy ones
+/// only write, and in mixed ones every operation is a write with even odds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Workload {
+    ReadHeavy,
+    WriteHeavy,
+    Mixed,
+}
+
+impl Workload {
+    pub const ALL: [Workload; 3] = [Workload::ReadHeavy, Workload::WriteHeavy, Workload::Mixed];
+
+    /// The name used in benchmark names and on the command line.
+    pub fn name(self) -> &'static str {
+        match self {
+            Workload::ReadHeavy => "read_heavy",
+            Workload::WriteHeavy => "write_heavy",
+            Workload::Mixed => "mixed",
+        }
+    }
+
+    /// Percentage of operations that are writes, as taken by `Scenario::new`.
+    pub fn write_percent(self) -> u32 {
+        match self {
+            Workload::ReadHeavy => 0,
+            Workload::WriteHeavy => 100,
+            Workload::Mixed => 50,
+        }
+    }
+}
+
+impl fmt::Display for Workload {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Workload {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Workload::ALL
+            .into_iter()
+            .find(|workload| workload.name() == s)
+            .ok_or_else(|| format!("unknown workload `{}`", s))
+    }
+}
+
+/// The number of threads the machine can run in parallel, or 1 if that cannot
+/// be determined. This is the default thread count for scenarios.
+pub fn available_threads() -> usize {
+    thread::available_parallelism().map_or(1, |n| n.get())
+}
+
+/// Thread counts scaled to the machine: half, all, and twice
+/// `available_threads`, so every machine runs an undersubscribed, a fully
+/// subscribed, and an oversubscribed scenario. On a single core the first
+/// two coincide and only 1 and 2 are returned.
+pub fn scaled_thread_counts() -> Vec<usize> {
+    let available = available_threads();
+    let mut counts = vec![(available / 2).max(1), available, available * 2];
+    counts.dedup();
+    counts
+}
+
+/// When each thread of a scenario performs its first operation.
+///
+/// Threads spawned one after another would otherwise start in a ragged
+/// line: on a loaded machine the first can finish before the last starts,
+/// and on an idle one they pile onto the lock together. Each mode picks one
+/// of those deliberately.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StartMode {
+    /// Every thread waits on a `Barrier` until all of them are running, so
+    /// they all contend from the first operation on.
+    #[default]
+    Simultaneous,
+    /// After the barrier, thread `i` waits `i * interval`, so threads join
+    /// the contention one at a time.
+    Staggered { interval: Duration },
+    /// After the barrier, every thread waits for a time drawn uniformly from
+    /// zero to `max`, from the scenario's seed, so a seed replays its delays.
+    RandomJitter { max: Duration },
+}
+
+/// Mixed into the seed of the start jitter, to draw it independently of the
+/// reads and writes.
+const START_JITTER_SALT: u64 = 0x6a17_7e12;
+
+/// Mixed into the seed of each thread's stalls, so drawing a stall does not
+/// shift the reads and writes that follow it.
+const STALL_SALT: u64 = 0x57a1_1500;
+
+impl StartMode {
+    /// How long thread `thread` of a scenario seeded with `seed` waits after
+    /// the barrier. Jitter is drawn from a `StdRng` seeded like the thread's
+    /// operations but salted, so it does not track whether they start with a
+    /// write.
+    pub fn delay(self, seed: u64, thread: usize) -> Duration {
+        match self {
+            StartMode::Simultaneous => Duration::ZERO,
+            StartMode::Staggered { interval } => {
+                interval.saturating_mul(thread.try_into().unwrap_or(u32::MAX))
+            }
+            StartMode::RandomJitter { max } => {
+                StdRng::seed_from_u64((seed ^ START_JITTER_SALT).wrapping_add(thread as u64))
+                    .random_range(Duration::ZERO..=max)
+            }
+        }
+    }
+
+    /// The longest any of `threads` threads waits after the barrier.
+    pub fn upper_bound(self, threads: usize) -> Duration {
+        match self {
+            StartMode::Simultaneous => Duration::ZERO,
+            StartMode::Staggered { interval } => {
+                interval.saturating_mul(threads.saturating_sub(1).try_into().unwrap_or(u32::MAX))
+            }
+            StartMode::RandomJitter { max } => max,
+        }
+    }
+}
+
+/// The seed `Scenario::new` starts with, so every run draws the same reads
+/// and writes unless asked for a different seed.
+pub const DEFAULT_SEED: u64 = 0x5eed;
+
+/// Describes one contention workload: how many threads, how many operations
+/// each performs, which fraction of those are writes, and how threads stall.
+#[derive(Clone, Copy, Debug)]
+pub struct Scenario {
+    pub threads: usize,
+    /// Ignored by `run_for_duration`, which runs for a fixed time.
+    pub ops_per_thread: u64,
+    /// Chance, as a percentage from 0 to 100, that an operation is a write.
+    pub write_percent: u32,
+    /// Seeds the choice between reads and writes; see `Scenario::operations`.
+    pub seed: u64,
+    /// Applied once per thread, on its first operation.
+    pub stall: StallPolicy,
+    pub placement: StallPlacement,
+    /// How long every operation busy-waits while holding the lock, on the
+    /// CPU rather than asleep, with `work::busy_wait`. Zero by default.
+    pub critical_section_work: Duration,
+    /// How long every thread busy-waits after each operation, once the lock
+    /// is released, modelling work that does not need the lock. Together with
+    /// `critical_section_work` this sets the duty cycle: the share of each
+    /// thread's time spent holding the lock. Zero by default.
+    pub work_between_ops: Duration,
+    /// When each thread performs its first operation.
+    /// `StartMode::Simultaneous` by default.
+    pub start: StartMode,
+    /// Whether every operation records how long it waited for and held the lock.
+    /// This reads the clock around every acquisition, so it is off by default.
+    pub record_timing: bool,
+    /// Whether every lock acquisition latency is recorded into a histogram.
+    #[cfg(feature = "latency")]
+    pub record_latency: bool,
+    /// Whether the run is wrapped in perf counters for context switches,
+    /// cache misses, and CPU cycles.
+    #[cfg(feature = "perf")]
+    pub record_perf: bool,
+    /// Whether every thread is pinned to a core of its own. If the platform
+    /// cannot pin or there are too few cores, the threads run unpinned.
+    #[cfg(feature = "pin")]
+    pub pin_threads: bool,
+    /// How long the runner waits for the threads to finish before panicking
+    /// on a likely deadlock. `None` scales the deadline with the work; see
+    /// `Scenario::deadline`.
+    pub deadline: Option<Duration>,
+}
+
+/// The part of the default deadline that does not depend on the work, enough
+/// for any scenario without stalls that is not enormous.
+const BASE_DEADLINE: Duration = Duration::from_secs(30);
+
+/// How much every operation adds to the default deadline, far more than an
+/// uncontended lock operation takes even on a slow machine.
+const DEADLINE_PER_OP: Duration = Duration::from_micros(100);
+
+impl Scenario {
+    /// Creates a scenario with no stall.
+    pub fn new(threads: usize, ops_per_thread: u64, write_percent: u32) -> Self {
+        assert!(write_percent <= 100, "write_percent must be at most 100");
+        Scenario {
+            threads,
+            ops_per_thread,
+            write_percent,
+            seed: DEFAULT_SEED,
+            stall: StallPolicy::None,
+            placement: StallPlacement::InCriticalSection,
+            critical_section_work: Duration::ZERO,
+            work_between_ops: Duration::ZERO,
+            start: StartMode::Simultaneous,
+            record_timing: false,
+            #[cfg(feature = "latency")]
+            record_latency: false,
+            #[cfg(feature = "perf")]
+            record_perf: false,
+            #[cfg(feature = "pin")]
+            pin_threads: false,
+            deadline: None,
+        }
+    }
+
+    /// Returns the scenario with the given stall policy and placement.
+    pub fn with_stall(self, stall: StallPolicy, placement: StallPlacement) -> Self {
+        Scenario {
+            stall,
+            placement,
+            ..self
+        }
+    }
+
+    /// Returns the scenario with its reads and writes drawn from `seed`.
+    pub fn with_seed(self, seed: u64) -> Self {
+        Scenario { seed, ..self }
+    }
+
+    /// Returns the scenario with every operation busy-waiting for `work`
+    /// inside the critical section.
+    pub fn with_critical_section_work(self, work: Duration) -> Self {
+        Scenario {
+            critical_section_work: work,
+            ..self
+        }
+    }
+
+    /// Returns the scenario with every thread busy-waiting for `work` between
+    /// one operation releasing the lock and the next acquiring it.
+    pub fn with_work_between_ops(self, work: Duration) -> Self {
+        Scenario {
+            work_between_ops: work,
+            ..self
+        }
+    }
+
+    /// Returns the scenario with its threads started as `start` says.
+    pub fn with_start(self, start: StartMode) -> Self {
+        Scenario { start, ..self }
+    }
+
+    /// Returns the scenario with per-operation lock timing recorded.
+    pub fn with_timing(self) -> Self {
+        Scenario {
+            record_timing: true,
+            ..self
+        }
+    }
+
+    /// Returns the scenario with every lock acquisition latency recorded into
+    /// `ScenarioResult::acquisition_latency`. This also records timing.
+    #[cfg(feature = "latency")]
+    pub fn with_latency(self) -> Self {
+        Scenario {
+            record_latency: true,
+            ..self.with_timing()
+        }
+    }
+
+    /// Returns the scenario with context switches, cache misses, and CPU cycles
+    /// counted across all its threads into `ScenarioResult::perf_counts`.
+    #[cfg(feature = "perf")]
+    pub fn with_perf_counters(self) -> Self {
+        Scenario {
+            record_perf: true,
+            ..self
+        }
+    }
+
+    /// Returns the scenario with every thread pinned to a distinct physical
+    /// core, so the OS cannot migrate threads while they run.
+    #[cfg(feature = "pin")]
+    pub fn with_pinned_threads(self) -> Self {
+        Scenario {
+            pin_threads: true,
+            ..self
+        }
+    }
+
+    /// Returns the scenario with a fixed deadline instead of the scaled default.
+    pub fn with_deadline(self, deadline: Duration) -> Self {
+        Scenario {
+            deadline: Some(deadline),
+            ..self
+        }
+    }
+
+    /// How long the runner waits for the threads before panicking, once they
+    /// are all running and, for `run_for_duration`, the window has closed.
+    /// Unless set with `with_deadline`, this is 30s plus a small allowance for
+    /// every operation, and its busy work, plus one worst-case
+    /// stall per thread, since in the worst case every thread stalls while
+    /// holding the lock and they run one by one, plus the longest start delay.
+    pub fn deadline(&self) -> Duration {
+        self.deadline.unwrap_or_else(|| {
+            let per_op = DEADLINE_PER_OP
+                .saturating_add(self.critical_section_work)
+                .saturating_add(self.work_between_ops);
+            let stalls = self
+                .stall
+                .upper_bound()
+                .saturating_mul(self.threads.try_into().unwrap_or(u32::MAX));
+            BASE_DEADLINE
+                .saturating_add(
+                    per_op.saturating_mul(self.total_ops().try_into().unwrap_or(u32::MAX)),
+                )
+                .saturating_add(stalls)
+                .saturating_add(self.start.upper_bound(self.threads))
+        })
+    }
+
+    /// Total number of operations across all threads.
+    pub fn total_ops(&self) -> u64 {
+        self.threads as u64 * self.ops_per_thread
+    }
+
+    /// The reads and writes thread `thread` performs, in order. Every
+    /// operation is a write with probability `write_percent`, drawn from a
+    /// `StdRng` seeded with `seed + thread`, so the same seed replays the same
+    /// run, and changing the thread count leaves the operations of the threads
+    /// both counts share as they were.
+    pub fn operations(&self, thread: usize) -> Operations {
+        Operations {
+            rng: StdRng::seed_from_u64(self.seed.wrapping_add(thread as u64)),
+            write_percent: self.write_percent,
+        }
+    }
+
+    /// The generator thread `thread` draws its uniform and Poisson stalls
+    /// from: a `StdRng` seeded from `seed + thread` and a salt, so one seed
+    /// replays the stalls as well as the reads and writes.
+    pub fn stall_rng(&self, thread: usize) -> StdRng {
+        StdRng::seed_from_u64((self.seed ^ STALL_SALT).wrapping_add(thread as u64))
+    }
+
+    /// Number of writes the scenario performs in total. This replays every
+    /// thread's draws, so it takes time in proportion to `total_ops`; after a
+    /// run, `ScenarioResult::writes` is the same number for free.
+    pub fn expected_writes(&self) -> u64 {
+        (0..self.threads)
+            .map(|thread| {
+                let mut operations = self.operations(thread);
+                (0..self.ops_per_thread)
+                    .filter(|_| operations.next_is_write())
+                    .count() as u64
+            })
+            .sum()
+    }
+}
+
+/// One thread's sequence of reads and writes, from `Scenario::operations`.
+#[derive(Clone, Debug)]
+pub struct Operations {
+    rng: StdRng,
+    write_percent: u32,
+}
+
+impl Operations {
+    /// Draws the next operation, returning whether it is a write.
+    pub fn next_is_write(&mut self) -> bool {
+        self.rng.random_range(0..100) < self.write_percent
+    }
+}
+
+/// What one thread of a scenario did.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ThreadResult {
+    pub reads: u64,
+    pub writes: u64,
+    /// Sum of every value this thread read.
+    pub read_sum: u64,
+    /// Time from the thread's first operation starting to its last finishing.
+    pub busy: Duration,
+    /// Time spent waiting to acquire the lock, or zero unless the scenario
+    /// records timing.
+    pub waiting: Duration,
+    /// Time spent holding the lock, or zero unless the scenario records timing.
+    pub in_critical_section: Duration,
+    /// CPU time the thread consumed over the same span as `busy`, or `None`
+    /// without the `cpu-time` feature or outside Linux and macOS. A thread
+    /// that parks while it waits uses far less CPU time than it is busy; one
+    /// that spins uses all of it.
+    pub cpu: Option<Duration>,
+}
+
+impl ThreadResult {
+    /// Number of operations this thread completed.
+    pub fn ops(&self) -> u64 {
+        self.reads + self.writes
+    }
+
+    fn add_timing(&mut self, timing: LockTiming) {
+        self.waiting += timing.waiting;
+        self.in_critical_section += timing.in_critical_section;
+    }
+}
+
+/// How evenly a scenario's operations were spread across its threads.
+/// Unfair locks let a few threads hog the lock, which shows up here but not
+/// in total throughput.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Fairness {
+    /// The fewest operations any thread completed divided by the most, where
+    /// 1 means every thread completed the same number.
+    pub min_max_ratio: f64,
+    /// The standard deviation of per-thread operation counts divided by their
+    /// mean, where 0 means every thread completed the same number.
+    pub coefficient_of_variation: f64,
+}
+
+/// Outcome of `run_contention_scenario`.
+#[derive(Clone, Debug)]
+pub struct ScenarioResult {
+    /// The `Scenario::seed` the reads and writes were drawn from.
+    pub seed: u64,
+    /// The shared value before any thread was spawned.
+    pub initial_value: u64,
+    /// The shared value after every thread was joined.
+    pub final_value: u64,
+    /// One entry per thread, in thread index order.
+    pub threads: Vec<ThreadResult>,
+    /// Wall-clock time from spawning the first thread to joining the last.
+    pub elapsed: Duration,
+    /// Nanoseconds every operation waited to acquire the lock, merged across
+    /// threads, if the scenario recorded latency.
+    #[cfg(feature = "latency")]
+    pub acquisition_latency: Option<Histogram<u64>>,
+    /// Perf event counts for the whole run, if the scenario recorded them.
+    #[cfg(feature = "perf")]
+    pub perf_counts: Option<PerfCounts>,
+    /// Context switches across the whole process during the run, or `None`
+    /// when they were not counted, which needs the `context-switches`
+    /// feature on Linux.
+    pub context_switches: Option<ContextSwitches>,
+}
+
+impl ScenarioResult {
+    /// Total writes across all threads.
+    pub fn writes(&self) -> u64 {
+        self.threads.iter().map(|thread| thread.writes).sum()
+    }
+
+    /// Total reads across all threads.
+    pub fn reads(&self) -> u64 {
+        self.threads.iter().map(|thread| thread.reads).sum()
+    }
+
+    /// Total operations across all threads.
+    pub fn ops(&self) -> u64 {
+        self.threads.iter().map(ThreadResult::ops).sum()
+    }
+
+    /// How evenly operations were spread across threads. Most telling for
+    /// `run_for_duration`, where threads that get the lock more often
+    /// complete more operations; with a fixed count every thread completes the same.
+    pub fn fairness(&self) -> Fairness {
+        let ops: Vec<f64> = self
+            .threads
+            .iter()
+            .map(|thread| thread.ops() as f64)
+            .collect();
+        let max = ops.iter().copied().fold(0.0, f64::max);
+        let min = ops.iter().copied().fold(max, f64::min);
+        let mean = ops.iter().sum::<f64>() / ops.len().max(1) as f64;
+        if mean == 0.0 {
+            return Fairness {
+                min_max_ratio: 1.0,
+                coefficient_of_variation: 0.0,
+            };
+        }
+        let variance = ops.iter().map(|ops| (ops - mean).powi(2)).sum::<f64>() / ops.len() as f64;
+        Fairness {
+            min_max_ratio: min / max,
+            coefficient_of_variation: variance.sqrt() / mean,
+        }
+    }
+
+    /// Time every thread spent waiting to acquire the lock, summed across threads.
+    /// Zero unless the scenario recorded timing.
+    pub fn waiting(&self) -> Duration {
+        self.threads.iter().map(|thread| thread.waiting).sum()
+    }
+
+    /// Time every thread spent holding the lock, summed across threads.
+    /// Zero unless the scenario recorded timing.
+    pub fn in_critical_section(&self) -> Duration {
+        self.threads
+            .iter()
+            .map(|thread| thread.in_critical_section)
+            .sum()
+    }
+
+    /// CPU time every thread consumed, summed across threads, or `None` if it
+    /// was not measured.
+    pub fn cpu_time(&self) -> Option<Duration> {
+        self.threads.iter().map(|thread| thread.cpu).sum()
+    }
+
+    /// CPU time divided by wall time: how many cores the run kept busy on
+    /// average. Threads that park while they wait for a lock keep this near
+    /// the number that can make progress at once, which is 1 for a single
+    /// lock, while spinning waiters push it towards the thread count even
+    /// when the wall time looks fast.
+    pub fn efficiency(&self) -> Option<f64> {
+        Some(self.cpu_time()?.as_secs_f64() / self.elapsed.as_secs_f64())
+    }
+
+    /// Involuntary context switches per operation, or `None` if context
+    /// switches were not counted. Above
+    /// `context_switches::INVOLUNTARY_SWITCHES_PER_OP_WARNING`, the machine
+    /// was likely too loaded for the run to be trusted.
+    pub fn involuntary_switches_per_op(&self) -> Option<f64> {
+        let switches = self.context_switches?;
+        Some(switches.involuntary as f64 / self.ops().max(1) as f64)
+    }
+
+    /// Operations per second over the whole run.
+    pub fn ops_per_sec(&self) -> f64 {
+        self.ops() as f64 / self.elapsed.as_secs_f64()
+    }
+
+    /// Returns whether every read observed a value between the initial and
+    /// final value, judged per thread from its read count and read sum.
+    pub fn reads_in_range(&self) -> bool {
+        self.threads.iter().all(|thread| {
+            (self.initial_value * thread.reads..=self.final_value * thread.reads)
+                .contains(&thread.read_sum)
+        })
+    }
+}
+
+/// Set by `cancel_runs`.
+static CANCELLED: AtomicBool = AtomicBool::new(false);
+
+/// Cancels every `run_for_duration` in the process, running or yet to start,
+/// for good: their threads stop after the operation they are on, and the run
+/// returns what was done so far. Meant for a Ctrl-C handler; there is no way
+/// to undo it.
+pub fn cancel_runs() {
+    CANCELLED.store(true, Ordering::Relaxed);
+}
+
+/// Whether `cancel_runs` has been called.
+pub fn runs_cancelled() -> bool {
+    CANCELLED.load(Ordering::Relaxed)
+}
+
+/// How long each thread of a scenario keeps performing operations.
+#[derive(Clone)]
+enum Limit {
+    Ops(u64),
+    /// Checked before every operation, along with `CANCELLED`, so a thread
+    /// stuck in a long stall finishes at most the operation it is on once
+    /// either is set.
+    UntilStopped(Arc<AtomicBool>),
+}
+
+impl Limit {
+    fn allows(&self, op: u64) -> bool {
+        match self {
+            Limit::Ops(ops) => op < *ops,
+            Limit::UntilStopped(stop) => !stop.load(Ordering::Relaxed) && !runs_cancelled(),
+        }
+    }
+}
+
+/// Runs `scenario` against `state`: spawns one thread per `scenario.threads`,
+/// each performing `scenario.ops_per_thread` reads or writes, joins them, and
+/// reports what every thread did along with the value before and after.
+pub fn run_contention_scenario<S: SharedState>(scenario: &Scenario, state: &S) -> ScenarioResult {
+    run_threads(scenario, state, Limit::Ops(scenario.ops_per_thread), || {})
+}
+
+/// Like `run_contention_scenario`, but every thread keeps performing reads or
+/// writes until `duration` has passed instead of stopping after a fixed count,
+/// so the number of operations completed in that window is only known
+/// afterwards from the result. Threads watch a stop flag that is set once
+/// `duration` is up, so the run overshoots by at most one operation per thread.
+/// After `cancel_runs`, the run stops early and covers the time up to then;
+/// whether it was cut short is for the caller to check with `runs_cancelled`.
+pub fn run_for_duration<S: SharedState>(
+    scenario: &Scenario,
+    state: &S,
+    duration: Duration,
+) -> ScenarioResult {
+    let stop = Arc::new(AtomicBool::new(false));
+    let limit = Limit::UntilStopped(stop.clone());
+    run_threads(scenario, state, limit, || {
+        // Waking up now and then to check for cancellation lets the runner
+        // join the threads soon after they stop, not once `duration` is up.
+        let deadline = Instant::now() + duration;
+        while !runs_cancelled() {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                break;
+            }
+            thread::sleep(remaining.min(CANCEL_POLL_INTERVAL));
+        }
+        stop.store(true, Ordering::Relaxed);
+    })
+}
+
+/// How often `run_for_duration` checks whether runs were cancelled.
+const CANCEL_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// What one thread of `run_threads` hands back when it is joined.
+struct ThreadOutput {
+    result: ThreadResult,
+    #[cfg(feature = "latency")]
+    latency: Option<Histogram<u64>>,
+}
+
+/// Spawns the scenario's threads, calls `wait` once they are all running, and
+/// joins them. `JoinHandle::join` has no timeout, so every thread sends its
+/// output over a channel and the runner waits on that with
+/// `Scenario::deadline`, panicking if the threads are still running by then.
+fn run_threads<S: SharedState>(
+    scenario: &Scenario,
+    state: &S,
+    limit: Limit,
+    wait: impl FnOnce(),
+) -> ScenarioResult {
+    let initial_value = state.read();
+    let scenario = *scenario;
+    // Opened before spawning, since the counters only follow threads created
+    // after them, and before the clock starts, so opening is not timed.
+    #[cfg(feature = "perf")]
+    let perf = scenario.record_perf.then(PerfCounters::start);
+    let switches_before = process_context_switches();
+    let start = Instant::now();
+    let (outputs_tx, outputs_rx) = mpsc::channel();
+    #[cfg(feature = "pin")]
+    let cores = scenario
+        .pin_threads
+        .then(|| affinity::assign_cores(scenario.threads))
+        .flatten();
+    let barrier = Arc::new(Barrier::new(scenario.threads));
+    let mut handles = vec![];
+    for i in 0..scenario.threads {
+        let state = state.clone();
+        let limit = limit.clone();
+        let barrier = barrier.clone();
+        let outputs_tx = outputs_tx.clone();
+        #[cfg(feature = "pin")]
+        let core = cores.as_ref().map(|cores| cores[i]);
+        #[cfg(feature = "trace")]
+        let spawn_span = tracing::trace_span!("spawn", thread = i).entered();
+        let handle = thread::spawn(move || {
+            #[cfg(feature = "trace")]
+            let _worker_span = tracing::trace_span!("worker", thread = i).entered();
+            #[cfg(feature = "pin")]
+            if let Some(core) = core {
+                affinity::pin_current(core);
+            }
+            barrier.wait();
+            let delay = scenario.start.delay(scenario.seed, i);
+            if !delay.is_zero() {
+                thread::sleep(delay);
+            }
+            let mut result = ThreadResult::default();
+            let mut operations = scenario.operations(i);
+            let mut stall_rng = scenario.stall_rng(i);
+            #[cfg(feature = "latency")]
+            let mut latency = scenario.record_latency.then(latency::new_histogram);
+            let cpu_started = thread_cpu_time();
+            let started = Instant::now();
+            let mut op = 0;
+            while limit.allows(op) {
+                let stall = LockStall {
+                    policy: scenario.stall,
+                    placement: scenario.placement,
+                }
+                .only_on_first(op);
+                // `lock_acquire` runs until `hold` is called, which is once the
+                // lock is held, and `critical_section` from then until the
+                // guard is about to be released.
+                #[cfg(feature = "trace")]
+                let acquire_span = tracing::trace_span!("lock_acquire", thread = i, op).entered();
+                let hold = || {
+                    #[cfg(feature = "trace")]
+                    let _critical_section_span = {
+                        drop(acquire_span);
+                        tracing::trace_span!("critical_section", thread = i, op).entered()
+                    };
+                    busy_wait(scenario.critical_section_work);
+                    stall.while_locked_with(i, &mut stall_rng);
+                };
+                let timing = if operations.next_is_write() {
+                    result.writes += 1;
+                    if scenario.record_timing {
+                        Some(state.write_timed(hold))
+                    } else {
+                        state.write_holding(hold);
+                        None
+                    }
+                } else {
+                    result.reads += 1;
+                    let (value, timing) = if scenario.record_timing {
+                        let (value, timing) = state.read_timed(hold);
+                        (value, Some(timing))
+                    } else {
+                        (state.read_holding(hold), None)
+                    };
+                    result.read_sum += black_box(value);
+                    timing
+                };
+                if let Some(timing) = timing {
+                    result.add_timing(timing);
+                    #[cfg(feature = "latency")]
+                    if let Some(histogram) = &mut latency {
+                        histogram.saturating_record(timing.waiting.as_nanos() as u64);
+                    }
+                }
+                stall.after_unlock_with(i, &mut stall_rng);
+                busy_wait(scenario.work_between_ops);
+                op += 1;
+            }
+            result.busy = started.elapsed();
+            result.cpu = cpu_started
+                .zip(thread_cpu_time())
+                .map(|(started, finished)| finished - started);
+            let output = ThreadOutput {
+                result,
+                #[cfg(feature = "latency")]
+                latency,
+            };
+            // The runner only stops listening after panicking on the deadline.
+            let _ = outputs_tx.send((i, output));
+        });
+        #[cfg(feature = "trace")]
+        drop(spawn_span);
+        handles.push(handle);
+    }
+    drop(outputs_tx);
+    wait();
+    #[cfg(feature = "trace")]
+    let join_span = tracing::trace_span!("join", threads = scenario.threads).entered();
+    let deadline = scenario.deadline();
+    let timeout = Instant::now() + deadline;
+    let mut outputs: Vec<Option<ThreadOutput>> = (0..scenario.threads).map(|_| None).collect();
+    for _ in 0..scenario.threads {
+        match outputs_rx.recv_timeout(timeout.saturating_duration_since(Instant::now())) {
+            Ok((i, output)) => outputs[i] = Some(output),
+            Err(RecvTimeoutError::Timeout) => panic!(
+                "scenario with {} threads, {} ops per thread, {}% writes, and seed {} \
+                 exceeded {:?} deadline; likely deadlock",
+                scenario.threads,
+                scenario.ops_per_thread,
+                scenario.write_percent,
+                scenario.seed,
+                deadline
+            ),
+            // A thread panicked; joining it below passes the panic on.
+            Err(RecvTimeoutError::Disconnected) => break,
+        }
+    }
+    let elapsed = start.elapsed();
+    for handle in handles {
+        handle.join().unwrap();
+    }
+    #[cfg(feature = "trace")]
+    drop(join_span);
+    #[cfg(feature = "perf")]
+    let perf_counts = perf.map(PerfCounters::stop);
+    let context_switches = switches_before
+        .zip(process_context_switches())
+        .map(|(before, after)| after - before);
+    let outputs: Vec<ThreadOutput> = outputs
+        .into_iter()
+        .map(|output| output.expect("a thread that did not panic sent its output"))
+        .collect();
+    #[cfg(feature = "latency")]
+    let acquisition_latency = scenario
+        .record_latency
+        .then(|| latency::merge(outputs.iter().filter_map(|output| output.latency.as_ref())));
+    ScenarioResult {
+        seed: scenario.seed,
+        initial_value,
+        final_value: state.read(),
+        threads: outputs.into_iter().map(|output| output.result).collect(),
+        elapsed,
+        #[cfg(feature = "latency")]
+        acquisition_latency,
+        #[cfg(feature = "perf")]
+        perf_counts,
+        context_switches,
+    }
+}
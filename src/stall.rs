@@ -0,0 +1,159 @@
+use rand::Rng;
+use std::thread;
+use std::time::Duration;
+
+/// How a benchmark simulates a stall inside each thread's piece of work.
+/// The original benchmarks all slept 25ms on every even iteration, which
+/// dwarfs the lock overhead being measured, so every stalled bench is also
+/// registered with `StallPolicy::None` to expose the primitive's own cost.
+#[derive(Clone, Copy, Debug)]
+pub enum StallPolicy {
+    /// No artificial delay at all.
+    None,
+    /// Sleeps for the given duration on every even iteration.
+    FixedEvenIterations(Duration),
+    /// Sleeps on every iteration for a duration drawn uniformly from `min..=max`.
+    /// Bounds given the wrong way round are swapped.
+    Uniform { min: Duration, max: Duration },
+    /// Sleeps on every iteration for an exponentially distributed duration
+    /// with the given mean, modelling stalls that arrive as a Poisson process.
+    Poisson { mean: Duration },
+}
+
+impl StallPolicy {
+    /// The 25ms-on-even-iterations delay the benchmarks have always used.
+    pub const EVEN_25MS: StallPolicy = StallPolicy::FixedEvenIterations(Duration::from_millis(25));
+
+    /// Returns this policy for a thread's first operation and `None` for the rest,
+    /// so a thread looping over many operations stalls once rather than per operation.
+    pub fn only_on_first(self, op: u64) -> StallPolicy {
+        if op == 0 {
+            self
+        } else {
+            StallPolicy::None
+        }
+    }
+
+    /// Returns how long iteration `i` should stall, if at all, drawing
+    /// uniform and Poisson stalls from `rng`. A seeded `rng` replays them.
+    pub fn duration(self, i: usize, rng: &mut impl Rng) -> Option<Duration> {
+        match self {
+            StallPolicy::None => None,
+            StallPolicy::FixedEvenIterations(delay) => i.is_multiple_of(2).then_some(delay),
+            StallPolicy::Uniform { min, max } => {
+                Some(rng.random_range(min.min(max)..=min.max(max)))
+            }
+            StallPolicy::Poisson { mean } => {
+                let u: f64 = rng.random();
+                Some(mean.mul_f64(-(1.0 - u).ln()))
+            }
+        }
+    }
+
+    /// The longest a single stall is expected to last, for sizing deadlines.
+    /// Poisson stalls are unbounded, so they count as ten times their mean,
+    /// which a stall exceeds with probability e^-10.
+    pub fn upper_bound(self) -> Duration {
+        match self {
+            StallPolicy::None => Duration::ZERO,
+            StallPolicy::FixedEvenIterations(delay) => delay,
+            StallPolicy::Uniform { min, max } => min.max(max),
+            StallPolicy::Poisson { mean } => mean.saturating_mul(10),
+        }
+    }
+
+    /// Blocks the current thread for iteration `i`'s stall, drawn from the
+    /// thread-local generator.
+    pub fn apply(self, i: usize) {
+        self.apply_with(i, &mut rand::rng());
+    }
+
+    /// Blocks the current thread for iteration `i`'s stall, drawn from `rng`.
+    pub fn apply_with(self, i: usize, rng: &mut impl Rng) {
+        if let Some(delay) = self.duration(i, rng) {
+            thread::sleep(delay);
+        }
+    }
+
+    /// Async counterpart of `apply` for tokio tasks.
+    /// Sleeps without blocking the runtime thread.
+    pub async fn apply_async(self, i: usize) {
+        // Drawn before awaiting, since the thread-local generator is not Send.
+        let delay = self.duration(i, &mut rand::rng());
+        if let Some(delay) = delay {
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// Where a lock benchmark applies its stall relative to the critical section.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StallPlacement {
+    /// Stalls while the guard is still held, modelling a thread that
+    /// sleeps or does slow work while holding the lock.
+    InCriticalSection,
+    /// Drops the guard first and then stalls, so only the lock operation
+    /// itself is serialized.
+    OutsideCriticalSection,
+}
+
+/// A stall policy paired with where lock benchmarks should apply it.
+/// Bench bodies call `while_locked` just before releasing the guard and
+/// `after_unlock` just after, and only the call matching `placement` stalls.
+#[derive(Clone, Copy, Debug)]
+pub struct LockStall {
+    pub policy: StallPolicy,
+    pub placement: StallPlacement,
+}
+
+impl LockStall {
+    /// Returns this stall for a thread's first operation and no stall for the rest.
+    pub fn only_on_first(self, op: u64) -> LockStall {
+        LockStall {
+            policy: self.policy.only_on_first(op),
+            ..self
+        }
+    }
+
+    /// Stalls for iteration `i` if the stall belongs inside the critical section.
+    pub fn while_locked(self, i: usize) {
+        if self.placement == StallPlacement::InCriticalSection {
+            self.policy.apply(i);
+        }
+    }
+
+    /// Stalls for iteration `i` if the stall belongs after the guard is dropped.
+    pub fn after_unlock(self, i: usize) {
+        if self.placement == StallPlacement::OutsideCriticalSection {
+            self.policy.apply(i);
+        }
+    }
+
+    /// Like `while_locked`, drawing the stall from `rng`.
+    pub fn while_locked_with(self, i: usize, rng: &mut impl Rng) {
+        if self.placement == StallPlacement::InCriticalSection {
+            self.policy.apply_with(i, rng);
+        }
+    }
+
+    /// Like `after_unlock`, drawing the stall from `rng`.
+    pub fn after_unlock_with(self, i: usize, rng: &mut impl Rng) {
+        if self.placement == StallPlacement::OutsideCriticalSection {
+            self.policy.apply_with(i, rng);
+        }
+    }
+
+    /// Async counterpart of `while_locked` for tokio tasks.
+    pub async fn while_locked_async(self, i: usize) {
+        if self.placement == StallPlacement::InCriticalSection {
+            self.policy.apply_async(i).await;
+        }
+    }
+
+    /// Async counterpart of `after_unlock` for tokio tasks.
+    pub async fn after_unlock_async(self, i: usize) {
+        if self.placement == StallPlacement::OutsideCriticalSection {
+            self.policy.apply_async(i).await;
+        }
+    }
+}
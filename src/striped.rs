@@ -0,0 +1,59 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::{Mutex, MutexGuard, PoisonError};
+
+/// A value split into a fixed number of stripes, each behind its own Mutex,
+/// with every key mapped to one stripe by its hash. Operations on keys in
+/// different stripes never contend, so more stripes trade memory for less
+/// contention, and one stripe is just one big lock.
+///
+/// Keys are hashed with `DefaultHasher::new()`, which is not randomly seeded,
+/// so a key lands in the same stripe in every run.
+#[derive(Debug)]
+pub struct StripedMutex<T> {
+    stripes: Vec<Mutex<T>>,
+}
+
+impl<T: Default> StripedMutex<T> {
+    /// Creates `stripes` stripes, each holding `T::default()`.
+    pub fn new(stripes: usize) -> Self {
+        assert!(stripes > 0, "a StripedMutex needs at least one stripe");
+        StripedMutex {
+            stripes: (0..stripes).map(|_| Mutex::default()).collect(),
+        }
+    }
+}
+
+impl<T> StripedMutex<T> {
+    /// Number of stripes.
+    pub fn stripes(&self) -> usize {
+        self.stripes.len()
+    }
+
+    /// Index of the stripe `key` maps to.
+    pub fn stripe_of<K: Hash + ?Sized>(&self, key: &K) -> usize {
+        let mut hasher = DefaultHasher::new();
+        key.hash(&mut hasher);
+        (hasher.finish() % self.stripes.len() as u64) as usize
+    }
+
+    /// Locks the stripe `key` maps to.
+    pub fn lock<K: Hash + ?Sized>(&self, key: &K) -> MutexGuard<'_, T> {
+        self.lock_stripe(self.stripe_of(key))
+    }
+
+    /// Locks stripe `index`. As with the counters, a poisoned stripe is
+    /// recovered rather than treated as an error.
+    pub fn lock_stripe(&self, index: usize) -> MutexGuard<'_, T> {
+        self.stripes[index]
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Folds over every stripe in index order, locking each only while `f`
+    /// runs on it, so the result is not a consistent snapshot if other
+    /// threads are writing.
+    pub fn fold<B>(&self, init: B, mut f: impl FnMut(B, &T) -> B) -> B {
+        (0..self.stripes.len()).fold(init, |acc, index| f(acc, &self.lock_stripe(index)))
+    }
+}
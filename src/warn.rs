@@ -0,0 +1,10 @@
+//! One-time warnings for the optional features that can fail at run time.
+
+use std::sync::Once;
+
+/// Prints `message` to stderr the first time `once` is passed in. Benchmarks
+/// run a scenario thousands of times, and one warning is enough; each caller
+/// keeps its own `Once`, so one feature's warning never hides another's.
+pub(crate) fn warn_once(once: &Once, message: impl FnOnce() -> String) {
+    once.call_once(|| eprintln!("warning: {}", message()));
+}
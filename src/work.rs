@@ -0,0 +1,61 @@
+//! Calibrated busy work, for modelling a thread that keeps the CPU busy for
+//! a while, such as inside a critical section.
+//!
+//! Sleeping gives the CPU away and wakes up late, and reading the clock in a
+//! loop costs tens of nanoseconds a read, which swamps a 100ns wait. So the
+//! spin loop is timed once, and afterwards a wait is a fixed number of
+//! iterations with no clock reads at all.
+
+use std::hint::black_box;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+/// How long each calibration round spins for. Long enough that the clock's
+/// resolution barely moves the result.
+const CALIBRATION_ROUND: Duration = Duration::from_millis(5);
+
+/// Calibration keeps the fastest of this many rounds, since a round during
+/// which the thread was preempted looks slower than the loop is.
+const CALIBRATION_ROUNDS: u32 = 5;
+
+/// Spin loop iterations per millisecond, measured on first use.
+static ITERATIONS_PER_MS: OnceLock<u64> = OnceLock::new();
+
+fn spin(iterations: u64) {
+    for i in 0..iterations {
+        black_box(i);
+    }
+}
+
+/// Times the spin loop, if that has not happened yet, and returns how many
+/// iterations it runs per millisecond. `busy_wait` calibrates on first use;
+/// call this beforehand to keep the calibration out of a measurement.
+pub fn calibrate() -> u64 {
+    *ITERATIONS_PER_MS.get_or_init(|| {
+        let mut iterations = 1_000;
+        let mut elapsed = Duration::ZERO;
+        while elapsed < CALIBRATION_ROUND {
+            iterations *= 2;
+            let started = Instant::now();
+            spin(iterations);
+            elapsed = started.elapsed();
+        }
+        for _ in 1..CALIBRATION_ROUNDS {
+            let started = Instant::now();
+            spin(iterations);
+            elapsed = elapsed.min(started.elapsed());
+        }
+        let per_ms = iterations as u128 * 1_000_000 / elapsed.as_nanos();
+        (per_ms as u64).max(1)
+    })
+}
+
+/// Keeps the current thread on the CPU for about `duration`, without
+/// reading the clock or yielding. Returns at once for a zero duration.
+pub fn busy_wait(duration: Duration) {
+    if duration.is_zero() {
+        return;
+    }
+    let iterations = duration.as_nanos() * calibrate() as u128 / 1_000_000;
+    spin(iterations.try_into().unwrap_or(u64::MAX));
+}
@@ -0,0 +1,45 @@
+//! Checks for the counting allocator, run with
+//!
+//! ```text
+//! cargo test --features count-allocations --test allocations
+//! ```
+//!
+//! The test binary installs `CountingAllocator` as its global allocator, so it
+//! only builds with the feature.
+#![cfg(feature = "count-allocations")]
+
+use basic_rust_concurrency_benchmarks::allocations::{
+    count_allocations, AllocationCounts, CountingAllocator,
+};
+use std::hint::black_box;
+use std::thread;
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+#[test]
+fn counts_only_allocations_made_while_the_gate_is_open() {
+    // Other tests in this binary may allocate concurrently, so this is the
+    // only test, and it runs alone.
+    let (_, counts) = count_allocations(|| black_box(Vec::<u64>::with_capacity(100)));
+    assert_eq!(
+        counts,
+        AllocationCounts {
+            allocations: 1,
+            bytes: 800
+        }
+    );
+
+    let outside = black_box(Vec::<u64>::with_capacity(100));
+    let (_, counts) = count_allocations(|| drop(outside));
+    assert_eq!(counts, AllocationCounts::default());
+
+    // Allocations on other threads count too, thread spawning included.
+    let (_, counts) = count_allocations(|| {
+        thread::scope(|scope| {
+            scope.spawn(|| black_box(Box::new(0u64)));
+        })
+    });
+    assert!(counts.allocations >= 1);
+    assert!(counts.bytes >= 8);
+}
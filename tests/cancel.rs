@@ -0,0 +1,70 @@
+//! Checks that `cancel_runs` cuts a list of scenarios short and leaves only
+//! complete results behind. Cancellation is process-wide and cannot be undone,
+//! so this runs as its own test binary, apart from every other run.
+
+use basic_rust_concurrency_benchmarks::config::parse_scenarios;
+use basic_rust_concurrency_benchmarks::registry::{primitive_names, run_configured};
+use basic_rust_concurrency_benchmarks::{
+    cancel_runs, read_reports, runs_cancelled, write_reports, ScenarioReport,
+};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const SCENARIOS: &str = r#"
+[[scenario]]
+name = "first"
+primitive = "mutex"
+threads = 2
+duration = "200ms"
+
+[[scenario]]
+name = "second"
+primitive = "rwlock"
+threads = 2
+duration = "60s"
+
+[[scenario]]
+name = "third"
+primitive = "atomic"
+threads = 2
+duration = "60s"
+"#;
+
+#[test]
+fn cancelling_mid_run_keeps_only_completed_scenarios() {
+    let scenarios = parse_scenarios(SCENARIOS, &primitive_names()).unwrap();
+    let started = Instant::now();
+    let results = thread::scope(|scope| {
+        scope.spawn(|| {
+            // Well into `second`, which would otherwise run for a minute.
+            thread::sleep(Duration::from_millis(500));
+            cancel_runs();
+        });
+        run_configured(&scenarios, |scenario| scenario)
+    });
+    assert!(runs_cancelled());
+    assert!(
+        started.elapsed() < Duration::from_secs(30),
+        "{:?}",
+        started.elapsed()
+    );
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].final_value, results[0].writes());
+
+    let reports: Vec<ScenarioReport> = scenarios
+        .iter()
+        .zip(&results)
+        .map(|(config, result)| ScenarioReport {
+            name: Some(config.name.clone()),
+            ..result.report(&config.primitive, config.workload)
+        })
+        .collect();
+    let path = std::env::temp_dir().join(format!("cancelled-{}.jsonl", std::process::id()));
+    write_reports(&path, &reports).unwrap();
+    let written = read_reports(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(written.len(), 1);
+    assert_eq!(written[0].name.as_deref(), Some("first"));
+    assert_eq!(written[0].primitive, "mutex");
+    assert!(written[0].ops_per_sec > 0.0);
+}
@@ -0,0 +1,202 @@
+//! Model checks for the hand-rolled primitives in `benches/support` and the
+//! library's `locks`, run with
+//!
+//! ```text
+//! RUSTFLAGS="--cfg loom" cargo test --release --test loom_tests
+//! ```
+//!
+//! Under `--cfg loom` the primitives are compiled against loom's atomics, so
+//! every test below explores all interleavings of its threads instead of the
+//! few a real scheduler happens to produce.
+#![cfg(loom)]
+
+/// The library's lock files, built against loom's atomics in place of the
+/// std ones `src/locks/mod.rs` gives them.
+#[path = "../src/locks"]
+mod locks {
+    pub mod mcs;
+    pub mod ticket;
+
+    mod sync {
+        pub use loom::hint::spin_loop;
+        pub use loom::sync::atomic::{AtomicBool, AtomicPtr, AtomicUsize, Ordering};
+    }
+}
+#[path = "../benches/support/sharded.rs"]
+mod sharded;
+#[path = "../benches/support/spin.rs"]
+mod spin;
+#[path = "../benches/support/sync.rs"]
+mod sync;
+
+use locks::mcs::{McsLock, McsNode};
+use locks::ticket::TicketLock;
+use loom::sync::atomic::{AtomicBool, Ordering};
+use loom::sync::Arc;
+use loom::thread;
+use sharded::ShardedCounter;
+use spin::SpinLock;
+
+#[test]
+fn spin_lock_concurrent_increments_produce_two() {
+    loom::model(|| {
+        let lock = Arc::new(SpinLock::new(0u64));
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let lock = Arc::clone(&lock);
+                thread::spawn(move || *lock.lock() += 1)
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(*lock.lock(), 2);
+    });
+}
+
+#[test]
+fn spin_yield_lock_concurrent_increments_produce_two() {
+    loom::model(|| {
+        let lock = Arc::new(SpinLock::new(0u64));
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let lock = Arc::clone(&lock);
+                thread::spawn(move || *lock.lock_spin_then_yield() += 1)
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(*lock.lock(), 2);
+    });
+}
+
+#[test]
+fn spin_lock_provides_mutual_exclusion() {
+    loom::model(|| {
+        let lock = Arc::new(SpinLock::new(()));
+        let inside = Arc::new(AtomicBool::new(false));
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let lock = Arc::clone(&lock);
+                let inside = Arc::clone(&inside);
+                thread::spawn(move || {
+                    let _guard = lock.lock();
+                    assert!(
+                        !inside.swap(true, Ordering::SeqCst),
+                        "two threads held the lock at once"
+                    );
+                    thread::yield_now();
+                    inside.store(false, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    });
+}
+
+#[test]
+fn sharded_counter_concurrent_increments_produce_two() {
+    loom::model(|| {
+        let counter = Arc::new(ShardedCounter::new(2));
+        let handles: Vec<_> = (0..2)
+            .map(|shard| {
+                let counter = Arc::clone(&counter);
+                thread::spawn(move || counter.increment(shard))
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(counter.sum(), 2);
+    });
+}
+
+#[test]
+fn ticket_lock_concurrent_increments_produce_two() {
+    loom::model(|| {
+        let lock = Arc::new(TicketLock::new(0u64));
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let lock = Arc::clone(&lock);
+                thread::spawn(move || *lock.lock() += 1)
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(*lock.lock(), 2);
+    });
+}
+
+#[test]
+fn ticket_lock_provides_mutual_exclusion() {
+    loom::model(|| {
+        let lock = Arc::new(TicketLock::new(()));
+        let inside = Arc::new(AtomicBool::new(false));
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let lock = Arc::clone(&lock);
+                let inside = Arc::clone(&inside);
+                thread::spawn(move || {
+                    let _guard = lock.lock();
+                    assert!(
+                        !inside.swap(true, Ordering::SeqCst),
+                        "two threads held the lock at once"
+                    );
+                    thread::yield_now();
+                    inside.store(false, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    });
+}
+
+#[test]
+fn mcs_lock_concurrent_increments_produce_two() {
+    loom::model(|| {
+        let lock = Arc::new(McsLock::new(0u64));
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let lock = Arc::clone(&lock);
+                thread::spawn(move || *lock.lock(&mut McsNode::new()) += 1)
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+        assert_eq!(*lock.lock(&mut McsNode::new()), 2);
+    });
+}
+
+#[test]
+fn mcs_lock_provides_mutual_exclusion() {
+    loom::model(|| {
+        let lock = Arc::new(McsLock::new(()));
+        let inside = Arc::new(AtomicBool::new(false));
+        let handles: Vec<_> = (0..2)
+            .map(|_| {
+                let lock = Arc::clone(&lock);
+                let inside = Arc::clone(&inside);
+                thread::spawn(move || {
+                    let mut node = McsNode::new();
+                    let _guard = lock.lock(&mut node);
+                    assert!(
+                        !inside.swap(true, Ordering::SeqCst),
+                        "two threads held the lock at once"
+                    );
+                    thread::yield_now();
+                    inside.store(false, Ordering::SeqCst);
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+    });
+}
@@ -0,0 +1,740 @@
+use basic_rust_concurrency_benchmarks::config::{parse_scenarios, RunLength};
+use basic_rust_concurrency_benchmarks::locks::{McsLock, SeqLock, TicketLock};
+use basic_rust_concurrency_benchmarks::plot::scaling_charts;
+use basic_rust_concurrency_benchmarks::registry::{self, primitive_names, SCENARIOS};
+use basic_rust_concurrency_benchmarks::rss::resident_set_size;
+use basic_rust_concurrency_benchmarks::scenario::SharedState;
+use basic_rust_concurrency_benchmarks::work;
+use basic_rust_concurrency_benchmarks::{
+    available_threads, diff_reports, find_regressions, read_reports, run_contention_scenario,
+    run_for_duration, scaled_thread_counts, write_reports, FairMutex, HostInfo, Scenario,
+    ScenarioReport, ScenarioResult, SharedCounter, StallPlacement, StallPolicy, StartMode,
+    StripedMutex, Workload, CSV_HEADER, SCHEMA_VERSION,
+};
+use std::sync::atomic::AtomicU64;
+use std::sync::{Arc, Mutex, RwLock};
+use std::thread;
+use std::time::Duration;
+
+fn assert_counts_match<S: SharedState>(state: S) {
+    for write_percent in [0, 10, 50, 100] {
+        let scenario = Scenario::new(4, 250, write_percent);
+        let result = run_contention_scenario(&scenario, &state);
+        assert_eq!(
+            result.final_value,
+            result.initial_value + scenario.expected_writes()
+        );
+        assert_eq!(result.writes(), scenario.expected_writes());
+        assert_eq!(result.reads() + result.writes(), scenario.total_ops());
+        assert!(result.reads_in_range());
+    }
+}
+
+#[test]
+fn mutex_final_value_matches_write_count() {
+    assert_counts_match(Arc::new(Mutex::new(0u64)));
+}
+
+#[test]
+fn rwlock_final_value_matches_write_count() {
+    assert_counts_match(Arc::new(RwLock::new(0u64)));
+}
+
+#[test]
+fn atomic_final_value_matches_write_count() {
+    assert_counts_match(Arc::new(AtomicU64::new(0)));
+}
+
+#[test]
+fn read_only_scenario_reads_the_stored_value() {
+    let state = Arc::new(Mutex::new(42u64));
+    let result: ScenarioResult = run_contention_scenario(&Scenario::new(3, 10, 0), &state);
+    assert_eq!(result.final_value, 42);
+    assert_eq!(result.threads.len(), 3);
+    for thread in &result.threads {
+        assert_eq!(thread.reads, 10);
+        assert_eq!(thread.writes, 0);
+        assert_eq!(thread.read_sum, 42 * 10);
+    }
+}
+
+fn draws(scenario: &Scenario, thread: usize) -> Vec<bool> {
+    let mut operations = scenario.operations(thread);
+    (0..1000).map(|_| operations.next_is_write()).collect()
+}
+
+#[test]
+fn operations_are_random_but_replay_from_the_seed() {
+    let scenario = Scenario::new(4, 1000, 25).with_seed(7);
+    let writes = draws(&scenario, 0).iter().filter(|&&write| write).count();
+    assert!((150..350).contains(&writes), "{} writes of 1000", writes);
+    assert_ne!(draws(&scenario, 0), draws(&scenario, 1));
+    assert_ne!(draws(&scenario, 0), draws(&scenario.with_seed(8), 0));
+    // Thread 2 draws the same operations however many threads run alongside it.
+    let more_threads = Scenario::new(16, 1000, 25).with_seed(7);
+    assert_eq!(draws(&scenario, 2), draws(&more_threads, 2));
+
+    let state = Arc::new(Mutex::new(0u64));
+    let first = run_contention_scenario(&scenario, &state);
+    let second = run_contention_scenario(&scenario, &state);
+    assert_eq!(first.seed, 7);
+    assert_eq!(first.report("mutex", Workload::Mixed).seed, Some(7));
+    assert_eq!(first.writes(), scenario.expected_writes());
+    for (first, second) in first.threads.iter().zip(&second.threads) {
+        assert_eq!(first.writes, second.writes);
+    }
+}
+
+#[test]
+fn stalls_replay_from_the_seed() {
+    let policy = StallPolicy::Poisson {
+        mean: Duration::from_millis(1),
+    };
+    let scenario = Scenario::new(4, 1, 100).with_seed(7);
+    let draws = |scenario: Scenario, thread| -> Vec<Option<Duration>> {
+        let mut rng = scenario.stall_rng(thread);
+        (0..8).map(|i| policy.duration(i, &mut rng)).collect()
+    };
+    assert_eq!(draws(scenario, 2), draws(scenario, 2));
+    assert_ne!(draws(scenario, 2), draws(scenario, 3));
+    assert_ne!(draws(scenario, 2), draws(scenario.with_seed(8), 2));
+}
+
+#[test]
+fn critical_section_work_busy_waits_inside_the_lock() {
+    assert!(work::calibrate() > 0);
+    assert_eq!(work::calibrate(), work::calibrate());
+    let state = Arc::new(Mutex::new(0u64));
+    let scenario = Scenario::new(2, 20, 50)
+        .with_critical_section_work(Duration::from_micros(500))
+        .with_timing();
+    assert!(scenario.deadline() > Scenario::new(2, 20, 50).deadline());
+    let result = run_contention_scenario(&scenario, &state);
+    assert_eq!(result.final_value, scenario.expected_writes());
+    // 40 operations of 500µs each is 20ms; calibration under a loaded test
+    // run can be off, but not by ten times.
+    assert!(
+        result.in_critical_section() >= Duration::from_millis(2),
+        "{:?}",
+        result.in_critical_section()
+    );
+}
+
+#[test]
+fn work_between_ops_happens_outside_the_lock() {
+    let state = Arc::new(Mutex::new(0u64));
+    let scenario = Scenario::new(1, 20, 50)
+        .with_work_between_ops(Duration::from_micros(500))
+        .with_timing();
+    let result = run_contention_scenario(&scenario, &state);
+    assert_eq!(result.final_value, scenario.expected_writes());
+    let thread = result.threads[0];
+    assert!(thread.busy >= Duration::from_millis(1), "{:?}", thread.busy);
+    assert!(
+        thread.in_critical_section < thread.busy / 2,
+        "{:?} of {:?} inside the lock",
+        thread.in_critical_section,
+        thread.busy
+    );
+}
+
+#[test]
+fn start_modes_delay_threads_after_the_barrier() {
+    let state = Arc::new(Mutex::new(0u64));
+    let staggered = Scenario::new(3, 1, 100).with_start(StartMode::Staggered {
+        interval: Duration::from_millis(5),
+    });
+    assert_eq!(
+        staggered.start.upper_bound(staggered.threads),
+        Duration::from_millis(10)
+    );
+    let result = run_contention_scenario(&staggered, &state);
+    assert_eq!(result.final_value, result.initial_value + 3);
+    assert!(
+        result.elapsed >= Duration::from_millis(10),
+        "{:?}",
+        result.elapsed
+    );
+    let jittered = Scenario::new(4, 10, 50).with_start(StartMode::RandomJitter {
+        max: Duration::from_millis(2),
+    });
+    assert!(jittered.deadline() > Scenario::new(4, 10, 50).deadline());
+    let delays = |scenario: Scenario| -> Vec<Duration> {
+        (0..scenario.threads)
+            .map(|i| scenario.start.delay(scenario.seed, i))
+            .collect()
+    };
+    assert_eq!(delays(jittered), delays(jittered.with_seed(jittered.seed)));
+    assert_ne!(delays(jittered), delays(jittered.with_seed(1)));
+    assert!(delays(jittered)
+        .iter()
+        .all(|&delay| delay <= Duration::from_millis(2)));
+    let result = run_contention_scenario(&jittered, &state);
+    assert_eq!(
+        result.final_value,
+        result.initial_value + jittered.expected_writes()
+    );
+}
+
+#[test]
+fn stalled_scenario_still_counts_every_write() {
+    let state = Arc::new(RwLock::new(0u64));
+    for placement in [
+        StallPlacement::InCriticalSection,
+        StallPlacement::OutsideCriticalSection,
+    ] {
+        let scenario = Scenario::new(4, 5, 100).with_stall(
+            StallPolicy::FixedEvenIterations(Duration::from_millis(1)),
+            placement,
+        );
+        let result = run_contention_scenario(&scenario, &state);
+        assert_eq!(result.final_value, result.initial_value + 20);
+    }
+}
+
+#[test]
+fn uniform_stall_swaps_reversed_bounds() {
+    let reversed = StallPolicy::Uniform {
+        min: Duration::from_millis(2),
+        max: Duration::from_millis(1),
+    };
+    assert_eq!(reversed.upper_bound(), Duration::from_millis(2));
+    for i in 0..100 {
+        let stall = reversed.duration(i, &mut rand::rng()).unwrap();
+        assert!(
+            (Duration::from_millis(1)..=Duration::from_millis(2)).contains(&stall),
+            "{:?}",
+            stall
+        );
+    }
+}
+
+#[test]
+fn parking_lot_final_value_matches_write_count() {
+    assert_counts_match(Arc::new(parking_lot::Mutex::new(0u64)));
+    assert_counts_match(Arc::new(parking_lot::RwLock::new(0u64)));
+    assert_counts_match(Arc::new(FairMutex::from(0)));
+}
+
+#[test]
+fn queue_locks_final_value_matches_write_count() {
+    assert_counts_match(Arc::new(TicketLock::new(0u64)));
+    assert_counts_match(Arc::new(McsLock::new(0u64)));
+}
+
+#[test]
+fn seqlock_never_returns_a_torn_pair() {
+    let lock = SeqLock::new((0u64, 0u64));
+    thread::scope(|scope| {
+        scope.spawn(|| {
+            for i in 1..=100_000 {
+                lock.write((i, i));
+            }
+        });
+        for _ in 0..3 {
+            scope.spawn(|| {
+                let mut last = 0;
+                for _ in 0..100_000 {
+                    let (first, second) = lock.read();
+                    assert_eq!(first, second, "torn read");
+                    assert!(first >= last, "read went backwards");
+                    last = first;
+                }
+            });
+        }
+    });
+    assert_eq!(lock.read(), (100_000, 100_000));
+}
+
+#[test]
+fn fixed_count_scenario_is_perfectly_fair() {
+    let state = Arc::new(Mutex::new(0u64));
+    let fairness = run_contention_scenario(&Scenario::new(4, 100, 50), &state).fairness();
+    assert_eq!(fairness.min_max_ratio, 1.0);
+    assert_eq!(fairness.coefficient_of_variation, 0.0);
+}
+
+#[test]
+fn timed_scenario_fairness_is_in_range() {
+    let state = Arc::new(FairMutex::from(0));
+    let scenario = Scenario::new(3, 0, 100);
+    let fairness = run_for_duration(&scenario, &state, Duration::from_millis(20)).fairness();
+    assert!((0.0..=1.0).contains(&fairness.min_max_ratio));
+    assert!(fairness.coefficient_of_variation >= 0.0);
+}
+
+#[test]
+fn timed_scenario_counts_every_write() {
+    let state = Arc::new(Mutex::new(0u64));
+    let scenario = Scenario::new(3, 0, Workload::Mixed.write_percent());
+    let result = run_for_duration(&scenario, &state, Duration::from_millis(20));
+    assert!(result.ops() > 0);
+    assert_eq!(result.final_value, result.initial_value + result.writes());
+    assert!(result.elapsed >= Duration::from_millis(20));
+    assert!(result.reads_in_range());
+}
+
+#[test]
+fn timed_scenario_stops_stalled_threads_after_one_operation() {
+    let state = Arc::new(Mutex::new(0u64));
+    let scenario = Scenario::new(2, 0, 100)
+        .with_stall(StallPolicy::EVEN_25MS, StallPlacement::InCriticalSection);
+    let result = run_for_duration(&scenario, &state, Duration::from_millis(5));
+    // Thread 0 stalls for 25ms on its first operation, well past the window.
+    let stalled = result.threads[0].ops();
+    assert!(
+        stalled <= 1,
+        "stalled thread completed {} operations",
+        stalled
+    );
+    assert_eq!(result.final_value, result.writes());
+}
+
+#[test]
+fn std_locks_keep_counting_after_a_panic_poisons_them() {
+    let mutex = Arc::new(Mutex::new(0u64));
+    let rwlock = Arc::new(RwLock::new(0u64));
+    let poisoner = {
+        let (mutex, rwlock) = (Arc::clone(&mutex), Arc::clone(&rwlock));
+        thread::spawn(move || {
+            let _mutex_guard = mutex.lock().unwrap();
+            let _rwlock_guard = RwLock::write(&rwlock).unwrap();
+            panic!("injected panic while holding the locks");
+        })
+    };
+    assert!(poisoner.join().is_err());
+    assert!(mutex.is_poisoned() && rwlock.is_poisoned());
+
+    mutex.increment();
+    rwlock.increment();
+    assert_eq!(SharedCounter::get(&*mutex), 1);
+    assert_eq!(SharedCounter::get(&*rwlock), 1);
+    assert_counts_match(mutex);
+    assert_counts_match(rwlock);
+}
+
+/// A counter whose increments never return, standing in for a deadlocked lock.
+struct Deadlocked;
+
+impl SharedCounter for Deadlocked {
+    fn increment(&self) {
+        loop {
+            thread::park();
+        }
+    }
+
+    fn get(&self) -> u64 {
+        0
+    }
+}
+
+#[test]
+#[should_panic(expected = "likely deadlock")]
+fn deadlocked_scenario_panics_at_the_deadline() {
+    let scenario = Scenario::new(2, 1, 100).with_deadline(Duration::from_millis(50));
+    run_contention_scenario(&scenario, &Arc::new(Deadlocked));
+}
+
+#[test]
+fn default_deadline_scales_with_stalls_and_ops() {
+    let unstalled = Scenario::new(8, 0, 100);
+    let stalled = unstalled.with_stall(StallPolicy::EVEN_25MS, StallPlacement::InCriticalSection);
+    assert_eq!(
+        stalled.deadline(),
+        unstalled.deadline() + Duration::from_millis(8 * 25)
+    );
+    assert!(Scenario::new(8, 10_000, 100).deadline() > unstalled.deadline());
+    let huge = Scenario::new(8, 1, 100)
+        .with_stall(
+            StallPolicy::Poisson {
+                mean: Duration::MAX,
+            },
+            StallPlacement::InCriticalSection,
+        )
+        .with_start(StartMode::RandomJitter { max: Duration::MAX });
+    assert_eq!(huge.deadline(), Duration::MAX);
+}
+
+#[test]
+fn workload_names_round_trip() {
+    for workload in Workload::ALL {
+        assert_eq!(workload.name().parse::<Workload>(), Ok(workload));
+    }
+    assert!("heavy".parse::<Workload>().is_err());
+}
+
+#[test]
+fn json_report_round_trips_through_serde() {
+    let state = Arc::new(RwLock::new(0u64));
+    let result = run_contention_scenario(&Scenario::new(2, 100, 50), &state);
+    let json = result.to_json("rwlock", Workload::Mixed);
+    let report: ScenarioReport = serde_json::from_str(&json).unwrap();
+    assert_eq!(report, result.report("rwlock", Workload::Mixed));
+    assert_eq!(report.schema_version, SCHEMA_VERSION);
+    assert_eq!(report.workload, "mixed");
+    assert_eq!(report.threads, 2);
+    assert_eq!(report.ops, 200);
+    assert_eq!(report.p50_latency_ns, None);
+    assert_eq!(report.context_switches, None);
+    assert_eq!(serde_json::to_string(&report).unwrap(), json);
+}
+
+#[test]
+fn host_info_serializes_with_nonzero_core_counts() {
+    let host = HostInfo::current();
+    assert!(host.logical_cores > 0);
+    assert!(host.physical_cores > 0);
+    assert!(!host.rustc_version.is_empty());
+    let json = serde_json::to_string(&host).unwrap();
+    assert!(json.contains("\"physical_cores\""));
+    assert_eq!(serde_json::from_str::<HostInfo>(&json).unwrap(), host);
+
+    // Reports from schema version 1 only had `cores`, `os`, and `arch`.
+    let old: HostInfo =
+        serde_json::from_str(r#"{"cores":8,"os":"linux","arch":"x86_64"}"#).unwrap();
+    assert_eq!(old.logical_cores, 8);
+    assert_eq!(old.cpu_model, "unknown");
+}
+
+#[test]
+fn timed_scenario_records_lock_timing_per_thread() {
+    let state = Arc::new(Mutex::new(0u64));
+    let scenario = Scenario::new(2, 50, 100)
+        .with_stall(
+            StallPolicy::FixedEvenIterations(Duration::from_millis(2)),
+            StallPlacement::InCriticalSection,
+        )
+        .with_timing();
+    let result = run_contention_scenario(&scenario, &state);
+    let stalled = &result.threads[0];
+    assert_eq!(stalled.ops(), 50);
+    assert!(stalled.in_critical_section >= Duration::from_millis(2));
+    assert!(stalled.busy >= stalled.waiting + stalled.in_critical_section);
+    assert!(result.in_critical_section() >= stalled.in_critical_section);
+    assert!(result.waiting() >= stalled.waiting);
+
+    let mut csv = vec![];
+    result.write_csv("mutex_write_heavy", &mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    let rows: Vec<&str> = csv.lines().collect();
+    assert_eq!(rows.len(), 2);
+    assert_eq!(CSV_HEADER.split(',').count(), rows[0].split(',').count());
+    assert!(rows[1].starts_with("mutex_write_heavy,1,50,"));
+}
+
+#[test]
+fn untimed_scenario_leaves_lock_timing_zero() {
+    let state = Arc::new(RwLock::new(0u64));
+    let result = run_contention_scenario(&Scenario::new(2, 10, 50), &state);
+    for thread in &result.threads {
+        assert_eq!(thread.waiting, Duration::ZERO);
+        assert_eq!(thread.in_critical_section, Duration::ZERO);
+    }
+}
+
+#[test]
+fn regressions_match_scenarios_and_respect_the_threshold() {
+    let state = Arc::new(Mutex::new(0u64));
+    let result = run_contention_scenario(&Scenario::new(2, 10, 100), &state);
+    let report = |primitive: &str, ops_per_sec: f64| ScenarioReport {
+        ops_per_sec,
+        ..result.report(primitive, Workload::WriteHeavy)
+    };
+    let baseline = vec![report("mutex", 1000.0), report("rwlock", 1000.0)];
+
+    let path = std::env::temp_dir().join(format!("baseline-{}.jsonl", std::process::id()));
+    write_reports(&path, &baseline).unwrap();
+    let baseline = read_reports(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(baseline.len(), 2);
+
+    let current = vec![
+        report("mutex", 850.0),
+        report("rwlock", 700.0),
+        report("atomic", 1.0),
+    ];
+    let regressions = find_regressions(&baseline, &current, 20.0);
+    assert_eq!(regressions.len(), 1);
+    assert_eq!(regressions[0].primitive, "rwlock");
+    assert_eq!(regressions[0].baseline_ops_per_sec, 1000.0);
+    assert_eq!(regressions[0].current_ops_per_sec, 700.0);
+    assert!(regressions[0].to_string().contains("1000"));
+    assert!(find_regressions(&baseline, &current, 40.0).is_empty());
+}
+
+#[test]
+fn report_diffs_sort_by_change_and_list_unmatched_scenarios() {
+    let state = Arc::new(Mutex::new(0u64));
+    let result = run_contention_scenario(&Scenario::new(2, 10, 100), &state);
+    let report = |name: &str, ops_per_sec: f64, p99: Option<u64>| ScenarioReport {
+        name: Some(name.to_string()),
+        ops_per_sec,
+        p99_latency_ns: p99,
+        ..result.report("mutex", Workload::WriteHeavy)
+    };
+    let old = vec![
+        report("steady", 1000.0, None),
+        report("faster", 1000.0, Some(100)),
+        report("slower_tail", 1000.0, Some(100)),
+        report("dropped", 1000.0, None),
+    ];
+    let new = vec![
+        report("steady", 990.0, None),
+        report("faster", 1500.0, Some(80)),
+        report("slower_tail", 1000.0, Some(300)),
+        report("added", 1.0, None),
+    ];
+    let diff = diff_reports(&old, &new);
+    let labels: Vec<String> = diff
+        .changes
+        .iter()
+        .map(|change| change.new.label())
+        .collect();
+    assert_eq!(labels, ["slower_tail", "faster", "steady"]);
+    assert_eq!(diff.changes[0].p99_latency_percent(), Some(200.0));
+    assert_eq!(diff.changes[0].regression_percent(), 200.0);
+    assert_eq!(diff.changes[1].ops_per_sec_percent(), 50.0);
+    assert!(diff.changes[1].regression_percent() < 0.0);
+    assert_eq!(diff.changes[2].p99_latency_percent(), None);
+    assert_eq!(diff.only_old.len(), 1);
+    assert_eq!(diff.only_old[0].label(), "dropped");
+    assert_eq!(diff.only_new.len(), 1);
+    assert_eq!(diff.only_new[0].label(), "added");
+}
+
+#[test]
+fn scaling_charts_break_lines_at_missing_thread_counts() {
+    let state = Arc::new(Mutex::new(0u64));
+    let result = run_contention_scenario(&Scenario::new(2, 10, 100), &state);
+    let report =
+        |primitive: &str, workload: Workload, threads: usize, ops_per_sec: f64| ScenarioReport {
+            threads,
+            ops_per_sec,
+            ..result.report(primitive, workload)
+        };
+    let reports = vec![
+        report("mutex", Workload::Mixed, 1, 10.0),
+        report("mutex", Workload::Mixed, 2, 20.0),
+        report("mutex", Workload::Mixed, 4, 40.0),
+        report("rwlock", Workload::Mixed, 1, 11.0),
+        report("rwlock", Workload::Mixed, 4, 44.0),
+        report("rwlock", Workload::Mixed, 4, 45.0),
+        report("atomic", Workload::WriteHeavy, 8, 80.0),
+    ];
+    let charts = scaling_charts(&reports);
+    assert_eq!(charts.len(), 2);
+    let mixed = &charts[0];
+    assert_eq!(mixed.workload, "mixed");
+    assert_eq!(mixed.thread_counts, [1, 2, 4]);
+    assert_eq!(mixed.series.len(), 2);
+    assert_eq!(
+        mixed.segments(&mixed.series[0]),
+        [vec![(1, 10.0), (2, 20.0), (4, 40.0)]]
+    );
+    // The later of two reports at the same thread count wins.
+    assert_eq!(
+        mixed.segments(&mixed.series[1]),
+        [vec![(1, 11.0)], vec![(4, 45.0)]]
+    );
+    assert_eq!(charts[1].workload, "write_heavy");
+    assert_eq!(charts[1].series[0].ops_per_sec, [Some(80.0)]);
+}
+
+#[test]
+fn resident_set_size_is_read_on_linux() {
+    assert_eq!(resident_set_size().is_some(), cfg!(target_os = "linux"));
+}
+
+#[test]
+fn cpu_time_is_measured_only_with_the_feature() {
+    let state = Arc::new(Mutex::new(0u64));
+    let result = run_contention_scenario(&Scenario::new(2, 10_000, 100), &state);
+    let report = result.report("mutex", Workload::WriteHeavy);
+    if cfg!(all(
+        feature = "cpu-time",
+        any(target_os = "linux", target_os = "macos")
+    )) {
+        assert!(result.threads.iter().all(|thread| thread.cpu.is_some()));
+        let efficiency = result.efficiency().unwrap();
+        assert!(efficiency > 0.0);
+        assert_eq!(report.efficiency, Some(efficiency));
+        assert_eq!(
+            report.cpu_time_ns,
+            result.cpu_time().map(|cpu| cpu.as_nanos() as u64)
+        );
+    } else {
+        assert_eq!(result.cpu_time(), None);
+        assert_eq!(report.efficiency, None);
+    }
+}
+
+#[test]
+fn context_switches_are_counted_only_with_the_feature() {
+    let state = Arc::new(Mutex::new(0u64));
+    // Sleeping parks the thread, which is a voluntary context switch.
+    let scenario = Scenario::new(2, 10, 100).with_stall(
+        StallPolicy::FixedEvenIterations(Duration::from_millis(1)),
+        StallPlacement::OutsideCriticalSection,
+    );
+    let result = run_contention_scenario(&scenario, &state);
+    let report = result.report("mutex", Workload::WriteHeavy);
+    if cfg!(all(feature = "context-switches", target_os = "linux")) {
+        let switches = result.context_switches.unwrap();
+        assert!(switches.voluntary > 0);
+        assert_eq!(report.voluntary_context_switches, Some(switches.voluntary));
+        assert_eq!(
+            report.involuntary_context_switches,
+            Some(switches.involuntary)
+        );
+        assert!(result.involuntary_switches_per_op().is_some());
+    } else {
+        assert_eq!(result.context_switches, None);
+        assert_eq!(result.involuntary_switches_per_op(), None);
+        assert_eq!(report.involuntary_context_switches, None);
+    }
+}
+
+#[cfg(feature = "latency")]
+#[test]
+fn latency_scenario_records_every_acquisition() {
+    use basic_rust_concurrency_benchmarks::latency::LatencySummary;
+
+    let state = Arc::new(Mutex::new(0u64));
+    let scenario = Scenario::new(3, 200, 50).with_latency();
+    let result = run_contention_scenario(&scenario, &state);
+    let histogram = result.acquisition_latency.as_ref().unwrap();
+    assert_eq!(histogram.len(), 600);
+    let summary = LatencySummary::of(histogram);
+    assert!(summary.p50 <= summary.p99 && summary.p99 <= summary.max);
+    let report = result.report("mutex", Workload::Mixed);
+    assert_eq!(report.p50_latency_ns, Some(summary.p50));
+    assert_eq!(report.p99_latency_ns, Some(summary.p99));
+}
+
+#[cfg(feature = "perf")]
+#[test]
+fn perf_scenario_reports_its_counts() {
+    // Whether each counter opens depends on the kernel and the machine, so
+    // only check that whatever was counted reaches the report.
+    let state = Arc::new(Mutex::new(0u64));
+    let result = run_contention_scenario(&Scenario::new(3, 200, 50).with_perf_counters(), &state);
+    let perf = result.perf_counts.unwrap();
+    let report = result.report("mutex", Workload::Mixed);
+    assert_eq!(report.context_switches, perf.context_switches);
+    assert_eq!(report.cache_misses, perf.cache_misses);
+    assert_eq!(report.cpu_cycles, perf.cpu_cycles);
+    let untracked = run_contention_scenario(&Scenario::new(3, 200, 50), &state);
+    assert_eq!(untracked.perf_counts, None);
+}
+
+#[cfg(feature = "pin")]
+#[test]
+fn pinned_scenario_counts_every_write() {
+    use basic_rust_concurrency_benchmarks::affinity;
+
+    let cores = affinity::assign_cores(1).expect("a machine has at least one core");
+    assert_eq!(cores.len(), 1);
+    assert!(affinity::assign_cores(usize::MAX).is_none());
+
+    // Whether or not there are enough cores to pin every thread, every write lands.
+    for threads in [1, 64] {
+        let state = Arc::new(Mutex::new(0u64));
+        let scenario = Scenario::new(threads, 100, 100).with_pinned_threads();
+        let result = run_contention_scenario(&scenario, &state);
+        assert_eq!(result.final_value, threads as u64 * 100);
+    }
+}
+
+#[test]
+fn scaled_thread_counts_bracket_available_parallelism() {
+    let available = available_threads();
+    let counts = scaled_thread_counts();
+    assert!(counts.contains(&available));
+    assert_eq!(counts.last(), Some(&(available * 2)));
+    assert!(counts[0] >= 1);
+    assert!(counts.windows(2).all(|pair| pair[0] < pair[1]));
+}
+
+#[test]
+fn striped_mutex_keeps_each_key_in_one_stripe() {
+    let striped = Arc::new(StripedMutex::<Vec<u64>>::new(16));
+    assert_eq!(striped.stripes(), 16);
+    thread::scope(|scope| {
+        for _ in 0..4 {
+            scope.spawn(|| {
+                for key in 0..100u64 {
+                    striped.lock(&key).push(key);
+                }
+            });
+        }
+    });
+    assert_eq!(striped.fold(0, |count, stripe| count + stripe.len()), 400);
+    for key in 0..100u64 {
+        let stripe = striped.lock_stripe(striped.stripe_of(&key));
+        assert_eq!(stripe.iter().filter(|&&pushed| pushed == key).count(), 4);
+    }
+}
+
+#[test]
+fn shipped_scenario_file_covers_the_registry() {
+    let scenarios = parse_scenarios(include_str!("../scenarios.toml"), &primitive_names()).unwrap();
+    for registered in &SCENARIOS {
+        let scenario = scenarios
+            .iter()
+            .find(|scenario| scenario.name == registered.name)
+            .unwrap_or_else(|| panic!("scenarios.toml has no {}", registered.name));
+        assert_eq!(scenario.primitive, registered.primitive);
+        assert_eq!(scenario.workload, registered.workload);
+        assert_eq!(scenario.length, RunLength::Duration(Duration::from_secs(1)));
+    }
+}
+
+#[test]
+fn registered_scenarios_have_unique_names_and_known_primitives() {
+    for (index, registered) in SCENARIOS.iter().enumerate() {
+        assert!(
+            registry::primitive(registered.primitive).is_some(),
+            "{} uses unknown primitive {}",
+            registered.name,
+            registered.primitive
+        );
+        assert!(SCENARIOS[..index]
+            .iter()
+            .all(|earlier| earlier.name != registered.name));
+    }
+    let result = registry::primitive("atomic")
+        .unwrap()
+        .run(&Scenario::new(2, 0, 50), RunLength::Ops(100));
+    assert_eq!(result.ops(), 200);
+    assert_eq!(result.final_value, result.writes());
+}
+
+#[test]
+fn scenario_file_rejects_unknown_primitives_listing_the_valid_ones() {
+    let toml = "[[scenario]]\nname = \"spin\"\nprimitive = \"spin_lock\"\n";
+    let err = parse_scenarios(toml, &primitive_names()).unwrap_err();
+    assert!(err.starts_with("scenario 1 (`spin`): unknown primitive `spin_lock`"));
+    assert!(err.contains(&primitive_names().join(", ")), "{}", err);
+}
+
+#[test]
+fn scenario_file_rejects_conflicting_or_unknown_fields() {
+    let both = "[[scenario]]\nname = \"a\"\nprimitive = \"mutex\"\nops = 10\nduration = \"1s\"\n";
+    assert!(parse_scenarios(both, &primitive_names())
+        .unwrap_err()
+        .contains("either `ops` or `duration`"));
+    let typo = "[[scenario]]\nname = \"a\"\nprimitive = \"mutex\"\nthread = 4\n";
+    assert!(parse_scenarios(typo, &primitive_names())
+        .unwrap_err()
+        .contains("unknown field `thread`"));
+    let twice = "[[scenario]]\nname = \"a\"\nprimitive = \"mutex\"\nops = 10\n\n\
+                 [[scenario]]\nname = \"a\"\nprimitive = \"atomic\"\n";
+    assert!(parse_scenarios(twice, &primitive_names())
+        .unwrap_err()
+        .contains("already used"));
+    let reversed =
+        "[[scenario]]\nname = \"a\"\nprimitive = \"mutex\"\nstall = \"uniform:10ms-1ms\"\n";
+    assert!(parse_scenarios(reversed, &primitive_names())
+        .unwrap_err()
+        .contains("uniform stall needs min <= max"));
+}
@@ -0,0 +1,64 @@
+//! Checks that the scenario runner's tracing spans exist only with the `trace`
+//! feature, so the instrumentation can never leak into published numbers.
+//! Run it both ways:
+//!
+//! ```text
+//! cargo test --test trace
+//! cargo test --features trace --test trace
+//! ```
+
+use basic_rust_concurrency_benchmarks::{run_contention_scenario, Scenario};
+use std::collections::HashSet;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+/// Records the name of every span created anywhere in the process.
+struct SpanNames(Mutex<HashSet<&'static str>>);
+
+impl Subscriber for SpanNames {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, span: &Attributes<'_>) -> Id {
+        static NEXT_ID: AtomicU64 = AtomicU64::new(1);
+        self.0.lock().unwrap().insert(span.metadata().name());
+        Id::from_u64(NEXT_ID.fetch_add(1, Ordering::Relaxed))
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, _: &Event<'_>) {}
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+#[test]
+fn scenario_spans_exist_only_with_the_trace_feature() {
+    // The subscriber is process-wide so it also sees the worker threads,
+    // which is why this is the only test in the binary.
+    let names = Arc::new(SpanNames(Mutex::new(HashSet::new())));
+    tracing::subscriber::set_global_default(Arc::clone(&names)).unwrap();
+    let state = Arc::new(Mutex::new(0u64));
+    run_contention_scenario(&Scenario::new(2, 10, 50), &state);
+    let names = names.0.lock().unwrap();
+    if cfg!(feature = "trace") {
+        for name in [
+            "spawn",
+            "worker",
+            "lock_acquire",
+            "critical_section",
+            "join",
+        ] {
+            assert!(names.contains(name), "no `{}` span in {:?}", name, names);
+        }
+    } else {
+        assert!(names.is_empty(), "spans without the feature: {:?}", names);
+    }
+}